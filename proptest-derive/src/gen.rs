@@ -0,0 +1,98 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generation of the `Arbitrary` implementation for a parsed item.
+
+use parse::{Body, Field, FieldSource, Fields, Item};
+
+/// Return the source of the `Arbitrary` implementation for `item`.
+///
+/// The strategy is boxed, since its type would otherwise have to name the
+/// closures which construct the values.
+pub fn derive(item: &Item) -> String {
+    let strategy = match item.body {
+        Body::Struct(ref fields) => construct(&item.name, fields),
+        Body::Enum(ref variants) => {
+            let options = variants.iter().map(|variant| format!(
+                "({}, {}.boxed())", variant.weight,
+                construct(&format!("{}::{}", item.name, variant.name),
+                          &variant.fields)))
+                .collect::<Vec<_>>();
+            format!("::proptest::strategy::Union::new_weighted(vec![{}])",
+                    options.join(", "))
+        },
+    };
+
+    format!("
+        impl ::proptest::arbitrary::Arbitrary for {name} {{
+            type Parameters = ();
+            type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+            type ValueTree = <Self::Strategy as
+                              ::proptest::strategy::Strategy>::Value;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {{
+                #[allow(unused_imports)]
+                use ::proptest::strategy::Strategy;
+                {strategy}.boxed()
+            }}
+        }}", name = item.name, strategy = strategy)
+}
+
+/// Return a strategy expression which generates values of the fields of
+/// `path` and constructs `path` from them.
+fn construct(path: &str, fields: &Fields) -> String {
+    // The strategies of the generated fields, and the names they are bound
+    // to once generated.
+    let mut generated = vec![];
+    let values = fields.iter().enumerate().map(|(index, field)| {
+        let binding = format!("proptest_field_{}", index);
+        match field.source {
+            FieldSource::Value(ref expr) => return format!("({})", expr),
+            FieldSource::Strategy(ref expr) =>
+                generated.push((format!("({})", expr), binding.clone())),
+            FieldSource::Arbitrary =>
+                generated.push((any(field), binding.clone())),
+        }
+        binding
+    }).collect::<Vec<_>>();
+
+    let value = match *fields {
+        Fields::Named(ref fields) => format!(
+            "{} {{ {} }}", path,
+            fields.iter().zip(&values).map(|(field, value)| format!(
+                "{}: {}", field.name.as_ref().expect("unnamed field"), value))
+                .collect::<Vec<_>>().join(", ")),
+        Fields::Unnamed(_) => format!("{}({})", path, values.join(", ")),
+        Fields::Unit => path.to_owned(),
+    };
+
+    let (strategy, pattern) = nest(&generated);
+    format!("{}.prop_map(|{}| {})", strategy, pattern, value)
+}
+
+fn any(field: &Field) -> String {
+    format!("::proptest::arbitrary::any::<{}>()", field.ty)
+}
+
+/// Combine the `(strategy, binding)` pairs of `generated` into one strategy
+/// of nested pairs, and the pattern binding its values.
+///
+/// Tuple strategies only go up to a fixed size, so nesting pairs allows any
+/// number of fields.
+fn nest(generated: &[(String, String)]) -> (String, String) {
+    match generated.split_first() {
+        None => ("::proptest::strategy::Just(())".to_owned(), "()".to_owned()),
+        Some((first, [])) => first.clone(),
+        Some(((strategy, binding), rest)) => {
+            let (rest_strategy, rest_pattern) = nest(rest);
+            (format!("({}, {})", strategy, rest_strategy),
+             format!("({}, {})", binding, rest_pattern))
+        },
+    }
+}
@@ -0,0 +1,80 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Custom-derive for the `Arbitrary` trait of proptest.
+//!
+//! `#[derive(Arbitrary)]` implements `proptest::arbitrary::Arbitrary` for a
+//! struct or enum, so that `any::<T>()` generates values of it. Each field
+//! is generated by `any()` for its type, so the types of all fields must
+//! implement `Arbitrary` themselves, unless overridden with one of the
+//! following field attributes:
+//!
+//! - `#[proptest(strategy = "expr")]` generates the field from the strategy
+//!   `expr` instead.
+//!
+//! - `#[proptest(value = "expr")]` sets the field to `expr` instead of
+//!   generating it. `expr` is evaluated for every value.
+//!
+//! Each variant of an enum is chosen equally often, unless given a weight
+//! with `#[proptest(weight = N)]`, which defaults to 1. As with
+//! `prop_oneof!`, values shrink towards earlier variants, so these should be
+//! listed in order of ascending complexity where possible.
+//!
+//! Like `Arbitrary` itself, this requires the type to implement `Debug`.
+//! Generic types are not supported.
+//!
+//! ## Example
+//!
+//! ```
+//! #[macro_use] extern crate proptest_derive;
+//! extern crate proptest;
+//!
+//! use proptest::arbitrary::any;
+//! use proptest::strategy::{Strategy, ValueTree};
+//! use proptest::test_runner::TestRunner;
+//!
+//! #[derive(Arbitrary, Debug)]
+//! enum Shape {
+//!     #[proptest(weight = 3)]
+//!     Circle { radius: u32 },
+//!     Rectangle {
+//!         #[proptest(strategy = "1..100u32")]
+//!         width: u32,
+//!         #[proptest(value = "1")]
+//!         height: u32,
+//!     },
+//! }
+//!
+//! # fn main() {
+//! let mut runner = TestRunner::default();
+//! let shape = any::<Shape>().new_value(&mut runner).unwrap().current();
+//! if let Shape::Rectangle { width, height } = shape {
+//!     assert!(width >= 1 && width < 100);
+//!     assert_eq!(1, height);
+//! }
+//! # }
+//! ```
+
+extern crate proc_macro;
+
+mod gen;
+mod parse;
+
+use proc_macro::TokenStream;
+
+/// Implements `Arbitrary` for a struct or enum, as described in the crate
+/// documentation.
+#[proc_macro_derive(Arbitrary, attributes(proptest))]
+pub fn derive_arbitrary(input: TokenStream) -> TokenStream {
+    let code = match parse::parse_item(input) {
+        Ok(item) => gen::derive(&item),
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+    code.parse().expect("derived invalid code")
+}
@@ -0,0 +1,365 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of the items given to `#[derive(Arbitrary)]`.
+//!
+//! Only as much of the item is understood as is needed to construct values
+//! of it; field types and the expressions given in attributes are kept as
+//! source text to be pasted into the generated code.
+
+use std::iter::Peekable;
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree, token_stream};
+
+/// The result of parsing, failing with a message for `compile_error!`.
+pub type Result<T> = ::std::result::Result<T, String>;
+
+type Tokens = Peekable<token_stream::IntoIter>;
+
+/// A struct or enum deriving `Arbitrary`.
+#[derive(Debug)]
+pub struct Item {
+    pub name: String,
+    pub body: Body,
+}
+
+#[derive(Debug)]
+pub enum Body {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+/// The fields of a struct or enum variant.
+#[derive(Debug)]
+pub enum Fields {
+    Named(Vec<Field>),
+    Unnamed(Vec<Field>),
+    Unit,
+}
+
+impl Fields {
+    pub fn iter<'a>(&'a self) -> ::std::slice::Iter<'a, Field> {
+        match *self {
+            Fields::Named(ref fields) | Fields::Unnamed(ref fields) =>
+                fields.iter(),
+            Fields::Unit => [].iter(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Field {
+    /// The name of the field, or `None` in a tuple struct or variant.
+    pub name: Option<String>,
+    pub ty: String,
+    pub source: FieldSource,
+}
+
+/// Where the values of a field come from.
+#[derive(Debug)]
+pub enum FieldSource {
+    /// `any::<T>()` for the field's type `T`.
+    Arbitrary,
+    /// The strategy given by `#[proptest(strategy = "...")]`.
+    Strategy(String),
+    /// The fixed value given by `#[proptest(value = "...")]`.
+    Value(String),
+}
+
+#[derive(Debug)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Fields,
+    /// The weight given by `#[proptest(weight = N)]`, 1 by default.
+    pub weight: u32,
+}
+
+/// A `key = value` (or bare `key`) entry of a `#[proptest(..)]` attribute.
+/// The value is kept as the source text of its literal.
+type AttrEntry = (String, Option<String>);
+
+/// Parse the item given to the derive.
+pub fn parse_item(input: TokenStream) -> Result<Item> {
+    let mut tokens = input.into_iter().peekable();
+    if !parse_attrs(&mut tokens)?.is_empty() {
+        return Err("#[proptest(..)] is only supported on fields and enum \
+                    variants".to_owned());
+    }
+    skip_visibility(&mut tokens);
+
+    let kind = expect_ident(&mut tokens)?;
+    let name = expect_ident(&mut tokens)?;
+    if is_punct(tokens.peek(), '<') {
+        return Err("#[derive(Arbitrary)] does not support generic types"
+                   .to_owned());
+    }
+
+    let body = match &kind[..] {
+        "struct" => Body::Struct(match tokens.next() {
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Brace == group.delimiter() =>
+                Fields::Named(parse_fields(group.stream(), true)?),
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Parenthesis == group.delimiter() =>
+                Fields::Unnamed(parse_fields(group.stream(), false)?),
+            _ => Fields::Unit,
+        }),
+        "enum" => match tokens.next() {
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Brace == group.delimiter() =>
+                Body::Enum(parse_variants(group.stream())?),
+            _ => return Err(format!("Expected the variants of `{}`", name)),
+        },
+        _ => return Err("#[derive(Arbitrary)] only supports structs and \
+                         enums".to_owned()),
+    };
+
+    Ok(Item { name, body })
+}
+
+fn parse_variants(input: TokenStream) -> Result<Vec<Variant>> {
+    let variants = split_commas(input).into_iter().map(|tokens| {
+        let mut tokens = tokens.into_iter().collect::<TokenStream>()
+            .into_iter().peekable();
+        let mut weight = 1;
+        for (key, value) in parse_attrs(&mut tokens)? {
+            match (&key[..], value) {
+                ("weight", Some(value)) => weight = parse_weight(&value)?,
+                _ => return Err(format!(
+                    "Unsupported #[proptest({})] on an enum variant; only \
+                     `weight = N` is supported here", key)),
+            }
+        }
+
+        let name = expect_ident(&mut tokens)?;
+        // Any discriminant (`= ...`) is not needed to construct the variant.
+        let fields = match tokens.next() {
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Brace == group.delimiter() =>
+                Fields::Named(parse_fields(group.stream(), true)?),
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Parenthesis == group.delimiter() =>
+                Fields::Unnamed(parse_fields(group.stream(), false)?),
+            _ => Fields::Unit,
+        };
+        Ok(Variant { name, fields, weight })
+    }).collect::<Result<Vec<_>>>()?;
+
+    if variants.is_empty() {
+        return Err("#[derive(Arbitrary)] cannot generate values of an enum \
+                    without variants".to_owned());
+    }
+    Ok(variants)
+}
+
+fn parse_fields(input: TokenStream, named: bool) -> Result<Vec<Field>> {
+    split_commas(input).into_iter().map(|tokens| {
+        let mut tokens = tokens.into_iter().collect::<TokenStream>()
+            .into_iter().peekable();
+        let mut source = FieldSource::Arbitrary;
+        for (key, value) in parse_attrs(&mut tokens)? {
+            if let FieldSource::Arbitrary = source {} else {
+                return Err("Only one of `strategy` and `value` may be given \
+                            for a field".to_owned());
+            }
+            let expr = value.as_ref().and_then(|value| string_literal(value));
+            source = match (&key[..], expr) {
+                ("strategy", Some(expr)) => FieldSource::Strategy(expr),
+                ("value", Some(expr)) => FieldSource::Value(expr),
+                _ => return Err(format!(
+                    "Unsupported #[proptest({})] on a field; only `strategy = \
+                     \"...\"` and `value = \"...\"` are supported here", key)),
+            };
+        }
+
+        skip_visibility(&mut tokens);
+        let name = if named {
+            let name = expect_ident(&mut tokens)?;
+            if !is_punct(tokens.next().as_ref(), ':') {
+                return Err(format!("Expected `:` after field `{}`", name));
+            }
+            Some(name)
+        } else {
+            None
+        };
+        let ty = tokens.collect::<TokenStream>().to_string();
+        Ok(Field { name, ty, source })
+    }).collect()
+}
+
+/// Parse the attributes at the start of `tokens`, returning the entries of
+/// any `#[proptest(..)]` among them and skipping all others.
+fn parse_attrs(tokens: &mut Tokens) -> Result<Vec<AttrEntry>> {
+    let mut entries = vec![];
+    while is_punct(tokens.peek(), '#') {
+        tokens.next();
+        let attr = match tokens.next() {
+            Some(TokenTree::Group(group)) => group.stream(),
+            _ => return Err("Expected an attribute after `#`".to_owned()),
+        };
+
+        let mut attr = attr.into_iter();
+        match attr.next() {
+            Some(TokenTree::Ident(ref ident))
+                if "proptest" == ident.to_string() => (),
+            _ => continue,
+        }
+        let args = match attr.next() {
+            Some(TokenTree::Group(ref group))
+                if Delimiter::Parenthesis == group.delimiter() =>
+                group.stream(),
+            _ => return Err("Expected #[proptest(key = value, ..)]"
+                            .to_owned()),
+        };
+
+        for entry in split_commas(args) {
+            let mut entry = entry.into_iter();
+            let key = match entry.next() {
+                Some(TokenTree::Ident(ident)) => ident.to_string(),
+                _ => return Err("Expected #[proptest(key = value, ..)]"
+                                .to_owned()),
+            };
+            let value = match (entry.next(), entry.next()) {
+                (None, _) => None,
+                (Some(TokenTree::Punct(ref punct)), Some(value))
+                    if '=' == punct.as_char() => Some(value.to_string()),
+                _ => return Err(format!(
+                    "Expected a literal value after `{} =`", key)),
+            };
+            entries.push((key, value));
+        }
+    }
+    Ok(entries)
+}
+
+/// Skip `pub`, `pub(crate)` and the like.
+fn skip_visibility(tokens: &mut Tokens) {
+    let is_pub = match tokens.peek() {
+        Some(TokenTree::Ident(ident)) => "pub" == ident.to_string(),
+        _ => false,
+    };
+    if is_pub {
+        tokens.next();
+        let restricted = match tokens.peek() {
+            Some(TokenTree::Group(group)) =>
+                Delimiter::Parenthesis == group.delimiter(),
+            _ => false,
+        };
+        if restricted {
+            tokens.next();
+        }
+    }
+}
+
+fn expect_ident(tokens: &mut Tokens) -> Result<String> {
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) => Ok(ident.to_string()),
+        Some(other) => Err(format!("Expected an identifier, found `{}`",
+                                   other)),
+        None => Err("Expected an identifier".to_owned()),
+    }
+}
+
+fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+    match token {
+        Some(TokenTree::Punct(punct)) => ch == punct.as_char(),
+        _ => false,
+    }
+}
+
+/// Split `input` at each comma outside of any group or angle brackets,
+/// dropping any trailing comma.
+fn split_commas(input: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![vec![]];
+    let mut depth = 0;
+    // Whether the previous token was the `-` of a `->`.
+    let mut arrow = false;
+    for token in input {
+        let mut dash = false;
+        if let TokenTree::Punct(ref punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow && depth > 0 => depth -= 1,
+                ',' if 0 == depth => {
+                    parts.push(vec![]);
+                    arrow = false;
+                    continue;
+                },
+                '-' => dash = Spacing::Joint == punct.spacing(),
+                _ => (),
+            }
+        }
+        arrow = dash;
+        parts.last_mut().expect("no parts").push(token);
+    }
+
+    if let Some(true) = parts.last().map(Vec::is_empty) {
+        parts.pop();
+    }
+    parts
+}
+
+/// Return the contents of the string literal whose source text is `lit`.
+fn string_literal(lit: &str) -> Option<String> {
+    let raw = lit.trim_start_matches('r');
+    if raw.len() < lit.len() {
+        let hashes = raw.chars().take_while(|&ch| '#' == ch).count();
+        let quoted = &raw[hashes..raw.len() - hashes];
+        return if quoted.len() >= 2 && quoted.starts_with('"') &&
+            quoted.ends_with('"')
+        {
+            Some(quoted[1..quoted.len() - 1].to_owned())
+        } else {
+            None
+        };
+    }
+
+    if lit.len() < 2 || !lit.starts_with('"') || !lit.ends_with('"') {
+        return None;
+    }
+
+    let mut contents = String::new();
+    let mut chars = lit[1..lit.len() - 1].chars();
+    while let Some(ch) = chars.next() {
+        if '\\' != ch {
+            contents.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => contents.push('\n'),
+            Some('r') => contents.push('\r'),
+            Some('t') => contents.push('\t'),
+            Some('0') => contents.push('\0'),
+            Some('x') => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                contents.push(u8::from_str_radix(&hex, 16).ok()? as char);
+            },
+            Some('u') => {
+                let hex = chars.by_ref().skip(1).take_while(|&ch| '}' != ch)
+                    .collect::<String>();
+                let code = u32::from_str_radix(&hex, 16).ok()?;
+                contents.push(::std::char::from_u32(code)?);
+            },
+            Some(escaped) => contents.push(escaped),
+            None => return None,
+        }
+    }
+    Some(contents)
+}
+
+/// Parse the integer literal whose source text is `lit` as a weight.
+fn parse_weight(lit: &str) -> Result<u32> {
+    let digits = lit.chars().take_while(|&ch| ch.is_ascii_digit() || '_' == ch)
+        .filter(|&ch| '_' != ch).collect::<String>();
+    match digits.parse::<u32>() {
+        Ok(0) => Err("Enum variant weights must be positive".to_owned()),
+        Ok(weight) => Ok(weight),
+        Err(_) => Err(format!("Invalid enum variant weight `{}`", lit)),
+    }
+}
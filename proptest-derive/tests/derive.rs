@@ -0,0 +1,122 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use] extern crate proptest_derive;
+extern crate proptest;
+
+use std::collections::HashMap;
+
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+fn generate<A : Arbitrary>(runner: &mut TestRunner) -> A {
+    any::<A>().new_value(runner).unwrap().current()
+}
+
+fn minimal<A : Arbitrary>() -> A {
+    let mut runner = TestRunner::default();
+    let mut case = any::<A>().new_value(&mut runner).unwrap();
+    while case.simplify() { }
+    case.current()
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+struct Tuple(u8, pub bool);
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+pub struct Named {
+    pub small: u8,
+    #[proptest(strategy = "10..20u32")]
+    ranged: u32,
+    #[proptest(value = "String::from(\"fixed\")")]
+    fixed: String,
+    /// A type with a comma in it is still one field.
+    #[proptest(strategy = "::proptest::collection::hash_map(
+        0..10u8, any::<u8>(), 0..5)")]
+    counts: HashMap<u8, u8>,
+    nested: Tuple,
+}
+
+#[derive(Arbitrary, Clone, Debug, PartialEq)]
+enum Enum {
+    Empty,
+    Pair(u8, #[proptest(strategy = "1..2u8")] u8),
+    #[proptest(weight = 2)]
+    Struct { unit: Unit, named: Named },
+}
+
+#[derive(Arbitrary, Clone, Copy, Debug, PartialEq)]
+enum Lopsided {
+    #[proptest(weight = 1)]
+    Rare,
+    #[proptest(weight = 1_000_000)]
+    Common,
+}
+
+#[allow(dead_code)]
+#[derive(Arbitrary, Debug)]
+struct ManyFields(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8);
+
+#[test]
+fn fields_use_their_strategies_and_values() {
+    let mut runner = TestRunner::default();
+    for _ in 0..256 {
+        let named = generate::<Named>(&mut runner);
+        assert!(named.ranged >= 10 && named.ranged < 20);
+        assert_eq!("fixed", named.fixed);
+    }
+}
+
+#[test]
+fn all_variants_are_generated() {
+    let mut runner = TestRunner::default();
+    let (mut empty, mut pair, mut strukt) = (false, false, false);
+    for _ in 0..256 {
+        match generate::<Enum>(&mut runner) {
+            Enum::Empty => empty = true,
+            Enum::Pair(_, second) => {
+                assert_eq!(1, second);
+                pair = true;
+            },
+            Enum::Struct { .. } => strukt = true,
+        }
+    }
+    assert!(empty && pair && strukt);
+}
+
+#[test]
+fn variants_are_weighted() {
+    let mut runner = TestRunner::default();
+    let rare = (0..256).filter(
+        |_| Lopsided::Rare == generate::<Lopsided>(&mut runner)).count();
+    assert!(rare < 4, "Rare variant generated {} times", rare);
+}
+
+#[test]
+fn values_shrink_like_their_fields() {
+    assert_eq!(Unit, minimal::<Unit>());
+    assert_eq!(Tuple(0, false), minimal::<Tuple>());
+    assert_eq!(Enum::Empty, minimal::<Enum>());
+
+    let named = minimal::<Named>();
+    assert_eq!(0, named.small);
+    assert_eq!(10, named.ranged);
+    assert!(named.counts.is_empty());
+    assert_eq!(Tuple(0, false), named.nested);
+}
+
+#[test]
+fn any_number_of_fields_is_supported() {
+    let mut runner = TestRunner::default();
+    let _ = generate::<ManyFields>(&mut runner);
+}
@@ -0,0 +1,358 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Defines the `Arbitrary` trait for types which have a canonical strategy.
+//!
+//! This allows writing code which is generic over the type to generate:
+//!
+//! ```
+//! use proptest::arbitrary::{any, Arbitrary};
+//! use proptest::prelude::*;
+//!
+//! fn arb_pair<T : Arbitrary>() -> impl Strategy {
+//!     (any::<T>(), any::<T>())
+//! }
+//! # let _ = arb_pair::<u32>();
+//! ```
+//!
+//! Structs and enums can implement `Arbitrary` with `#[derive(Arbitrary)]`
+//! from the `proptest-derive` crate.
+
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+#[cfg(feature = "saturating")]
+use std::num::Saturating;
+use std::num::Wrapping;
+
+use bool;
+use char;
+use num;
+use sample::{self, Select, SelectValueTree};
+use strategy::*;
+
+/// A type which has a canonical strategy for generating arbitrary values of
+/// it.
+///
+/// Rather than calling the methods of this trait directly, it is usually more
+/// convenient to use the `any()` and `any_with()` functions.
+///
+/// ## Example
+///
+/// A field-less enum can be implemented the same way as `Ordering` is, by
+/// selecting one of its variants:
+///
+/// ```
+/// use proptest::arbitrary::{any, Arbitrary};
+/// use proptest::sample::{self, Select, SelectValueTree};
+///
+/// #[derive(Clone, Copy, Debug)]
+/// enum Suit { Clubs, Diamonds, Hearts, Spades }
+///
+/// impl Arbitrary for Suit {
+///     type Parameters = ();
+///     type Strategy = Select<Suit>;
+///     type ValueTree = SelectValueTree<Suit>;
+///
+///     fn arbitrary_with(_: ()) -> Self::Strategy {
+///         // Values shrink towards the first variant.
+///         sample::select(&[Suit::Clubs, Suit::Diamonds,
+///                          Suit::Hearts, Suit::Spades][..])
+///     }
+/// }
+///
+/// let suits = any::<Suit>();
+/// # let _ = suits;
+/// ```
+pub trait Arbitrary : Sized + fmt::Debug {
+    /// The parameters which `arbitrary_with()` accepts to customise the
+    /// strategy. Types which cannot be customised use `()`.
+    type Parameters : Default;
+    /// The strategy returned by `arbitrary_with()`.
+    type Strategy : Strategy<Value = Self::ValueTree>;
+    /// The `ValueTree` produced by `Self::Strategy`.
+    type ValueTree : ValueTree<Value = Self>;
+
+    /// Returns the canonical strategy for `Self` with the given parameters.
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy;
+
+    /// Returns the canonical strategy for `Self` with the default parameters.
+    fn arbitrary() -> Self::Strategy {
+        Self::arbitrary_with(Default::default())
+    }
+}
+
+/// The strategy returned by `any::<A>()`.
+pub type StrategyFor<A> = <A as Arbitrary>::Strategy;
+
+/// Returns the canonical strategy for generating values of type `A`.
+///
+/// ```
+/// use proptest::arbitrary::any;
+/// use proptest::strategy::{Strategy, ValueTree};
+/// use proptest::test_runner::TestRunner;
+///
+/// let mut runner = TestRunner::default();
+/// let value: u8 = any::<u8>().new_value(&mut runner).unwrap().current();
+/// # let _ = value;
+/// ```
+pub fn any<A : Arbitrary>() -> StrategyFor<A> {
+    A::arbitrary()
+}
+
+/// Returns the canonical strategy for generating values of type `A`,
+/// customised by `params`.
+pub fn any_with<A : Arbitrary>(params: A::Parameters) -> StrategyFor<A> {
+    A::arbitrary_with(params)
+}
+
+macro_rules! int_arbitrary {
+    ($($typ:ident),*) => {$(
+        /// Generates values over the whole range of the type.
+        impl Arbitrary for $typ {
+            type Parameters = ();
+            type Strategy = num::$typ::Any;
+            type ValueTree = num::$typ::BinarySearch;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                num::$typ::ANY
+            }
+        }
+    )*}
+}
+
+int_arbitrary!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+#[cfg(feature = "unstable")]
+int_arbitrary!(i128, u128);
+
+macro_rules! float_arbitrary {
+    ($($typ:ident),*) => {$(
+        /// Generates any value other than NaN, i.e., any finite value of
+        /// either sign, including subnormals and both zeros, or an infinity.
+        impl Arbitrary for $typ {
+            type Parameters = ();
+            type Strategy = num::$typ::Any;
+            type ValueTree = num::$typ::BinarySearch;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                num::$typ::POSITIVE | num::$typ::NEGATIVE |
+                num::$typ::NORMAL | num::$typ::SUBNORMAL |
+                num::$typ::ZERO | num::$typ::INFINITE
+            }
+        }
+    )*}
+}
+
+float_arbitrary!(f32, f64);
+
+/// Generates `true` and `false` with equal probability.
+impl Arbitrary for bool {
+    type Parameters = ();
+    type Strategy = bool::Any;
+    type ValueTree = bool::BoolValueTree;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        bool::ANY
+    }
+}
+
+/// Generates any `char`, as per `char::any()`.
+impl Arbitrary for char {
+    type Parameters = ();
+    type Strategy = char::CharStrategy<'static>;
+    type ValueTree = char::CharValueTree;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        char::any()
+    }
+}
+
+/// Generates `Less`, `Equal` and `Greater` with equal probability, shrinking
+/// towards `Equal` and then `Less`.
+impl Arbitrary for Ordering {
+    type Parameters = ();
+    type Strategy = Select<Ordering>;
+    type ValueTree = SelectValueTree<Ordering>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        sample::select(&[Ordering::Equal, Ordering::Less,
+                         Ordering::Greater][..])
+    }
+}
+
+mapfn! {
+    /// Wraps a value in `Wrapping`. Used by `Arbitrary for Wrapping<A>`.
+    [pub] fn WrapWrapping[<T : fmt::Debug>](t: T) -> Wrapping<T> {
+        Wrapping(t)
+    }
+}
+
+/// Generates values of the inner type as per `any::<A>()`, shrinking as the
+/// inner type does.
+impl<A : Arbitrary> Arbitrary for Wrapping<A> {
+    type Parameters = A::Parameters;
+    type Strategy = statics::Map<A::Strategy, WrapWrapping>;
+    type ValueTree = statics::Map<A::ValueTree, WrapWrapping>;
+
+    fn arbitrary_with(params: A::Parameters) -> Self::Strategy {
+        statics::Map::new(A::arbitrary_with(params), WrapWrapping)
+    }
+}
+
+mapfn! {
+    /// Wraps a value in `Reverse`. Used by `Arbitrary for Reverse<A>`.
+    [pub] fn WrapReverse[<T : fmt::Debug>](t: T) -> Reverse<T> {
+        Reverse(t)
+    }
+}
+
+/// Generates values of the inner type as per `any::<A>()`, shrinking as the
+/// inner type does.
+impl<A : Arbitrary> Arbitrary for Reverse<A> {
+    type Parameters = A::Parameters;
+    type Strategy = statics::Map<A::Strategy, WrapReverse>;
+    type ValueTree = statics::Map<A::ValueTree, WrapReverse>;
+
+    fn arbitrary_with(params: A::Parameters) -> Self::Strategy {
+        statics::Map::new(A::arbitrary_with(params), WrapReverse)
+    }
+}
+
+#[cfg(feature = "saturating")]
+mapfn! {
+    /// Wraps a value in `Saturating`. Used by `Arbitrary for Saturating<A>`.
+    [pub] fn WrapSaturating[<T : fmt::Debug>](t: T) -> Saturating<T> {
+        Saturating(t)
+    }
+}
+
+/// Generates values of the inner type as per `any::<A>()`, shrinking as the
+/// inner type does.
+///
+/// `Saturating` requires Rust 1.74, which is newer than the minimum supported
+/// Rust version, so this requires the `saturating` feature.
+#[cfg(feature = "saturating")]
+impl<A : Arbitrary> Arbitrary for Saturating<A> {
+    type Parameters = A::Parameters;
+    type Strategy = statics::Map<A::Strategy, WrapSaturating>;
+    type ValueTree = statics::Map<A::ValueTree, WrapSaturating>;
+
+    fn arbitrary_with(params: A::Parameters) -> Self::Strategy {
+        statics::Map::new(A::arbitrary_with(params), WrapSaturating)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_runner::*;
+
+    use super::*;
+
+    fn generate<A : Arbitrary>(runner: &mut TestRunner) -> A {
+        any::<A>().new_value(runner).unwrap().current()
+    }
+
+    #[test]
+    fn integers_cover_whole_range() {
+        let mut runner = TestRunner::default();
+        let mut saw_negative = false;
+        let mut saw_large = false;
+        for _ in 0..256 {
+            saw_negative |= generate::<i32>(&mut runner) < 0;
+            saw_large |= generate::<u64>(&mut runner) > 1 << 63;
+        }
+
+        assert!(saw_negative);
+        assert!(saw_large);
+    }
+
+    #[test]
+    fn wrapping_covers_whole_range_and_shrinks() {
+        let mut runner = TestRunner::default();
+        let mut saw_negative = false;
+        for _ in 0..256 {
+            let mut case = any::<Wrapping<i32>>().new_value(&mut runner)
+                .unwrap();
+            saw_negative |= case.current().0 < 0;
+            while case.simplify() { }
+            assert_eq!(Wrapping(0), case.current());
+        }
+
+        assert!(saw_negative);
+    }
+
+    #[test]
+    fn orderings_are_uniform_and_shrink_to_equal() {
+        let mut runner = TestRunner::default();
+        let mut counts = [0; 3];
+        for _ in 0..1024 {
+            let mut case = any::<Ordering>().new_value(&mut runner).unwrap();
+            counts[(case.current() as i32 + 1) as usize] += 1;
+            while case.simplify() { }
+            assert_eq!(Ordering::Equal, case.current());
+        }
+
+        assert!(counts.iter().all(|&n| n > 256 && n < 428),
+                "Bad counts: {:?}", counts);
+    }
+
+    #[test]
+    fn reverse_shrinks_as_inner() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = any::<Reverse<u32>>().new_value(&mut runner)
+                .unwrap();
+            while case.simplify() { }
+            assert_eq!(Reverse(0), case.current());
+        }
+    }
+
+    #[cfg(feature = "saturating")]
+    #[test]
+    fn saturating_covers_whole_range() {
+        let mut runner = TestRunner::default();
+        let mut saw_large = false;
+        for _ in 0..256 {
+            saw_large |= generate::<Saturating<u64>>(&mut runner).0 > 1 << 63;
+        }
+
+        assert!(saw_large);
+    }
+
+    #[test]
+    fn floats_are_never_nan() {
+        let mut runner = TestRunner::default();
+        let mut saw_negative = false;
+        for _ in 0..1024 {
+            let mut case = any::<f64>().new_value(&mut runner).unwrap();
+            saw_negative |= case.current() < 0.0;
+            loop {
+                assert!(!case.current().is_nan());
+                if !case.simplify() { break; }
+            }
+            assert!(!generate::<f32>(&mut runner).is_nan());
+        }
+
+        assert!(saw_negative);
+    }
+
+    #[test]
+    fn arbitrary_is_usable_generically() {
+        fn arb_pair<T : Arbitrary>() -> (StrategyFor<T>, StrategyFor<T>) {
+            (any::<T>(), any_with::<T>(Default::default()))
+        }
+
+        let mut runner = TestRunner::default();
+        let (a, b): (bool, bool) =
+            arb_pair::<bool>().new_value(&mut runner).unwrap().current();
+        let _ = (a, b);
+        let (a, b): (char, char) =
+            arb_pair::<char>().new_value(&mut runner).unwrap().current();
+        let _ = (a, b);
+    }
+}
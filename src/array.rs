@@ -160,6 +160,11 @@ macro_rules! small_array {
                     false
                 }
             }
+
+            fn current_complexity(&self) -> u64 {
+                self.tree.iter().fold(
+                    0, |sum, t| sum.saturating_add(t.current_complexity()))
+            }
         }
     }
 }
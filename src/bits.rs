@@ -24,6 +24,8 @@ use std::ops::Range;
 use bit_set::BitSet;
 use rand::{self, Rng};
 
+use array::UniformArrayStrategy;
+use bool;
 use strategy::*;
 use test_runner::*;
 
@@ -122,7 +124,8 @@ impl BitSetLike for BitSet {
 pub struct BitSetStrategy<T : BitSetLike> {
     min: usize,
     max: usize,
-    mask: Option<T>
+    mask: Option<T>,
+    msb_first: bool,
 }
 
 impl<T : BitSetLike> BitSetStrategy<T> {
@@ -133,7 +136,7 @@ impl<T : BitSetLike> BitSetStrategy<T> {
     /// preferable to calling this directly.
     pub fn new(min: usize, max: usize) -> Self {
         BitSetStrategy {
-            min, max, mask: None,
+            min, max, mask: None, msb_first: false,
         }
     }
 
@@ -143,7 +146,17 @@ impl<T : BitSetLike> BitSetStrategy<T> {
         BitSetStrategy {
             min: 0,
             max: mask.len(),
-            mask: Some(mask)
+            mask: Some(mask),
+            msb_first: false,
+        }
+    }
+
+    /// Like `masked()`, but values shrink by clearing bits starting from the
+    /// most significant rather than the least significant one.
+    pub fn masked_msb_first(mask: T) -> Self {
+        BitSetStrategy {
+            msb_first: true,
+            .. Self::masked(mask)
         }
     }
 }
@@ -163,7 +176,9 @@ impl<T : BitSetLike> Strategy for BitSetStrategy<T> {
 
         Ok(BitSetValueTree {
             inner,
-            shrink: self.min,
+            shrink: if self.msb_first { self.max } else { self.min },
+            min: self.min,
+            msb_first: self.msb_first,
             prev_shrink: None,
             min_count: 0
         })
@@ -224,6 +239,8 @@ impl<T : BitSetLike> Strategy for SampledBitSetStrategy<T> {
         Ok(BitSetValueTree {
             inner: bits,
             shrink: self.bits.start,
+            min: self.bits.start,
+            msb_first: false,
             prev_shrink: None,
             min_count: self.size.start,
         })
@@ -234,11 +251,45 @@ impl<T : BitSetLike> Strategy for SampledBitSetStrategy<T> {
 #[derive(Clone, Copy, Debug)]
 pub struct BitSetValueTree<T : BitSetLike> {
     inner: T,
+    /// The next bit to try clearing or, if `msb_first`, one past it.
     shrink: usize,
+    /// The least significant bit which may have been set.
+    min: usize,
+    msb_first: bool,
     prev_shrink: Option<usize>,
     min_count: usize,
 }
 
+impl<T : BitSetLike> BitSetValueTree<T> {
+    /// Advance `shrink` past the next set bit in shrinking order, returning
+    /// that bit, if any.
+    fn next_set_bit(&mut self) -> Option<usize> {
+        if self.msb_first {
+            while self.shrink > self.min && !self.inner.test(self.shrink - 1) {
+                self.shrink -= 1;
+            }
+
+            if self.shrink > self.min {
+                self.shrink -= 1;
+                Some(self.shrink)
+            } else {
+                None
+            }
+        } else {
+            while self.shrink < self.inner.len() &&
+                !self.inner.test(self.shrink)
+            { self.shrink += 1; }
+
+            if self.shrink < self.inner.len() {
+                self.shrink += 1;
+                Some(self.shrink - 1)
+            } else {
+                None
+            }
+        }
+    }
+}
+
 impl<T : BitSetLike> ValueTree for BitSetValueTree<T> {
     type Value = T;
 
@@ -251,18 +302,13 @@ impl<T : BitSetLike> ValueTree for BitSetValueTree<T> {
             return false;
         }
 
-        while self.shrink < self.inner.len() &&
-            !self.inner.test(self.shrink)
-        { self.shrink += 1; }
-
-        if self.shrink >= self.inner.len() {
+        if let Some(bit) = self.next_set_bit() {
+            self.prev_shrink = Some(bit);
+            self.inner.clear(bit);
+            true
+        } else {
             self.prev_shrink = None;
             false
-        } else {
-            self.prev_shrink = Some(self.shrink);
-            self.inner.clear(self.shrink);
-            self.shrink += 1;
-            true
         }
     }
 
@@ -274,6 +320,10 @@ impl<T : BitSetLike> ValueTree for BitSetValueTree<T> {
             false
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.inner.count() as u64
+    }
 }
 
 macro_rules! int_api {
@@ -287,6 +337,7 @@ macro_rules! int_api {
                 min: 0,
                 max: $max,
                 mask: None,
+                msb_first: false,
             };
 
             /// Generates values where bits between the given bounds may be
@@ -363,6 +414,38 @@ minimal_api!(usize, usize);
 minimal_api!(isize, isize);
 minimal_api!(bitset, BitSet);
 
+macro_rules! bits_fn {
+    ($($fun:ident: $typ:ident),*) => { $(
+        /// Generates values whose set bits are a subset of those in `mask`,
+        /// for integers used as flag fields.
+        ///
+        /// Values shrink by clearing one bit at a time, starting with the
+        /// most significant bit, so the flags that remain in a minimal input
+        /// are the least significant ones needed to reproduce a failure.
+        pub fn $fun(mask: $typ) -> BitSetStrategy<$typ> {
+            BitSetStrategy::masked_msb_first(mask)
+        }
+    )* }
+}
+bits_fn!(u8_bits: u8, u16_bits: u16, u32_bits: u32, u64_bits: u64);
+
+/// Create a strategy to generate arrays of independent, uniformly random
+/// `bool`s, where `A` is an array type of `bool`s of length 1 to 32.
+///
+/// Each element shrinks to `false` independently, as with
+/// `array::uniformN(bool::ANY)`.
+///
+/// ```
+/// use proptest::bits::bool_array;
+///
+/// let flags = bool_array::<[bool; 12]>();
+/// # let _ = flags;
+/// ```
+pub fn bool_array<A>() -> UniformArrayStrategy<bool::Any, A>
+where UniformArrayStrategy<bool::Any, A> : Strategy {
+    UniformArrayStrategy::new(bool::ANY)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -492,8 +575,65 @@ mod test {
         }
     }
 
+    #[test]
+    fn bits_stay_within_mask_and_shrink_from_msb() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut value = u8_bits(0b1011_0110).new_value(&mut runner)
+                .unwrap();
+            let mut prev = value.current();
+            assert_eq!(0, prev & !0b1011_0110);
+            while value.simplify() {
+                let v = value.current();
+                let cleared = prev & !v;
+                assert_eq!(1, cleared.count_ones());
+                // The highest bit still set is the one which was cleared.
+                assert!(cleared > v, "Shrank from {:b} to {:b}", prev, v);
+                prev = v;
+            }
+            assert_eq!(0, value.current());
+        }
+
+        let mut accum = 0;
+        for _ in 0..1024 {
+            accum |= u64_bits(0xdead_beef_0000_0001).new_value(&mut runner)
+                .unwrap().current();
+        }
+        assert_eq!(0xdead_beef_0000_0001, accum);
+    }
+
+    #[test]
+    fn bits_complicate_to_previous() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut value = u16_bits(0xf0f0).new_value(&mut runner).unwrap();
+            let orig = value.current();
+            if value.simplify() {
+                assert!(value.complicate());
+                assert_eq!(orig, value.current());
+                assert!(value.simplify() || orig.count_ones() == 1);
+            }
+        }
+    }
+
+    #[test]
+    fn bool_arrays_have_independent_elements() {
+        let mut runner = TestRunner::default();
+        let mut trues = [0; 20];
+        for _ in 0..1024 {
+            let array = bool_array::<[bool; 20]>().new_value(&mut runner)
+                .unwrap().current();
+            for (count, &b) in trues.iter_mut().zip(array.iter()) {
+                if b { *count += 1; }
+            }
+        }
+        assert!(trues.iter().all(|&n| n > 400 && n < 624),
+                "Bad counts: {:?}", trues);
+    }
+
     #[test]
     fn test_sanity() {
         check_strategy_sanity(u32::masked(0xdeadbeef), None);
+        check_strategy_sanity(u32_bits(0xdeadbeef), None);
     }
 }
@@ -69,6 +69,7 @@ impl ValueTree for BoolValueTree {
         self.0 = true;
         !r
     }
+    fn current_complexity(&self) -> u64 { self.0 as u64 }
 }
 
 #[cfg(test)]
@@ -0,0 +1,220 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `std::ops::Bound` values and pairs of them, as
+//! accepted by APIs taking `RangeBounds`.
+//!
+//! Each bound is `Unbounded`, `Included` or `Excluded` with equal
+//! probability, and shrinks from `Excluded` to `Included` to `Unbounded`.
+
+use std::cmp::min;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Bound, Range};
+
+use num;
+use strategy::*;
+use test_runner::*;
+use tuple::TupleValueTree;
+
+const UNBOUNDED: u8 = 0;
+const INCLUDED: u8 = 1;
+const EXCLUDED: u8 = 2;
+
+fn to_bound<T>(kind: u8, value: T) -> Bound<T> {
+    match kind {
+        UNBOUNDED => Bound::Unbounded,
+        INCLUDED => Bound::Included(value),
+        _ => Bound::Excluded(value),
+    }
+}
+
+struct ToBound<T>(PhantomData<T>);
+impl<T> Clone for ToBound<T> {
+    fn clone(&self) -> Self { *self }
+}
+impl<T> Copy for ToBound<T> { }
+impl<T> fmt::Debug for ToBound<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToBound")
+    }
+}
+impl<T : fmt::Debug> statics::MapFn<(u8, T)> for ToBound<T> {
+    type Output = Bound<T>;
+    fn apply(&self, (kind, value): (u8, T)) -> Bound<T> {
+        to_bound(kind, value)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Bound` values whose bounding values are
+    /// generated by another strategy.
+    ///
+    /// Created by the `of()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct BoundStrategy[<T>][where T : Strategy](
+        statics::Map<(Range<u8>, T), ToBound<ValueFor<T>>>)
+        -> BoundValueTree<T::Value>;
+    /// `ValueTree` type corresponding to `BoundStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct BoundValueTree[<T>][where T : ValueTree](
+        statics::Map<TupleValueTree<(num::u8::BinarySearch, T)>,
+                     ToBound<T::Value>>)
+        -> Bound<T::Value>;
+}
+
+/// Return a strategy producing `Bound` values wrapping values from the given
+/// delegate strategy.
+///
+/// Bounds shrink towards `Unbounded`.
+pub fn of<T : Strategy>(t: T) -> BoundStrategy<T> {
+    BoundStrategy(statics::Map::new((UNBOUNDED..EXCLUDED + 1, t),
+                                    ToBound(PhantomData)))
+}
+
+struct ToRangeBounds<T>(PhantomData<T>);
+impl<T> Clone for ToRangeBounds<T> {
+    fn clone(&self) -> Self { *self }
+}
+impl<T> Copy for ToRangeBounds<T> { }
+impl<T> fmt::Debug for ToRangeBounds<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToRangeBounds")
+    }
+}
+impl<T : Ord + fmt::Debug> statics::MapFn<(u8, u8, T, T)>
+for ToRangeBounds<T> {
+    type Output = (Bound<T>, Bound<T>);
+    fn apply(&self, (mut start_kind, mut end_kind, a, b): (u8, u8, T, T))
+             -> (Bound<T>, Bound<T>) {
+        let (start, end) = if b < a { (b, a) } else { (a, b) };
+
+        // A range is not empty if both ends are included, nor, for
+        // `start < end`, if either end is. A range unbounded at one end is
+        // not empty if the other end is included, whereas excluding it would
+        // leave nothing if it is the greatest or least value of `T`.
+        if UNBOUNDED == start_kind {
+            end_kind = min(end_kind, INCLUDED);
+        } else if UNBOUNDED == end_kind {
+            start_kind = min(start_kind, INCLUDED);
+        } else if start == end {
+            start_kind = INCLUDED;
+            end_kind = INCLUDED;
+        } else if EXCLUDED == start_kind && EXCLUDED == end_kind {
+            end_kind = INCLUDED;
+        }
+
+        (to_bound(start_kind, start), to_bound(end_kind, end))
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates non-empty ranges as pairs of `Bound`s.
+    ///
+    /// Created by the `range_bounds()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct RangeBoundsStrategy[<T>][where T : Strategy,
+                                              ValueFor<T> : Ord](
+        statics::Map<(Range<u8>, Range<u8>, T, T),
+                     ToRangeBounds<ValueFor<T>>>)
+        -> RangeBoundsValueTree<T::Value>;
+    /// `ValueTree` type corresponding to `RangeBoundsStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct RangeBoundsValueTree[<T>][where T : ValueTree,
+                                               T::Value : Ord](
+        statics::Map<TupleValueTree<(num::u8::BinarySearch,
+                                     num::u8::BinarySearch, T, T)>,
+                     ToRangeBounds<T::Value>>)
+        -> (Bound<T::Value>, Bound<T::Value>);
+}
+
+/// Return a strategy producing `(start, end)` pairs of `Bound`s, which
+/// implement `RangeBounds`, with the bounding values drawn from `element`.
+///
+/// The start value is never greater than the end value, and the range is
+/// never empty: if both ends are bounded by the same value, both are
+/// `Included`, if one end is `Unbounded`, the other is not `Excluded`, and
+/// otherwise at most one of them is `Excluded`.
+///
+/// Both bounds shrink towards `Unbounded`, so the simplest range is the
+/// unbounded `(Unbounded, Unbounded)`.
+pub fn range_bounds<T : Strategy + Clone>(element: T)
+                                          -> RangeBoundsStrategy<T>
+where ValueFor<T> : Ord {
+    RangeBoundsStrategy(statics::Map::new(
+        (UNBOUNDED..EXCLUDED + 1, UNBOUNDED..EXCLUDED + 1,
+         element.clone(), element),
+        ToRangeBounds(PhantomData)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn contains<T : Ord>(range: &(Bound<T>, Bound<T>), v: T) -> bool {
+        (match range.0 {
+            Bound::Unbounded => true,
+            Bound::Included(ref start) => *start <= v,
+            Bound::Excluded(ref start) => *start < v,
+        }) && (match range.1 {
+            Bound::Unbounded => true,
+            Bound::Included(ref end) => v <= *end,
+            Bound::Excluded(ref end) => v < *end,
+        })
+    }
+
+    #[test]
+    fn bounds_cover_all_kinds_and_shrink_to_unbounded() {
+        let mut runner = TestRunner::default();
+        let mut counts = [0; 3];
+        for _ in 0..256 {
+            let mut case = of(0..10).new_value(&mut runner).unwrap();
+            match case.current() {
+                Bound::Unbounded => counts[0] += 1,
+                Bound::Included(_) => counts[1] += 1,
+                Bound::Excluded(_) => counts[2] += 1,
+            }
+            while case.simplify() { }
+            assert_eq!(Bound::Unbounded, case.current());
+        }
+
+        assert!(counts.iter().all(|&n| n > 0), "{:?}", counts);
+    }
+
+    #[test]
+    fn range_bounds_are_never_empty() {
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            let mut case = range_bounds(0..4).new_value(&mut runner)
+                .unwrap();
+            loop {
+                let range = case.current();
+                assert!((-1..5).any(|v| contains(&range, v)),
+                        "Empty range: {:?}", range);
+                if !case.simplify() { break; }
+            }
+            assert_eq!((Bound::Unbounded, Bound::Unbounded), case.current());
+        }
+    }
+
+    #[test]
+    fn range_bounds_at_extremes_of_type_are_never_empty() {
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            let mut case = range_bounds(prop_oneof![Just(0u8), Just(255u8)])
+                .new_value(&mut runner).unwrap();
+            loop {
+                let range = case.current();
+                assert!((0..=255).any(|v| contains(&range, v)),
+                        "Empty range: {:?}", range);
+                if !case.simplify() { break; }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `bytes::Bytes` and `bytes::BytesMut`.
+//!
+//! This module is only available with the `bytes` feature.
+//!
+//! The buffers are generated like a `Vec<u8>` from `collection::vec()`, so
+//! they shrink by first removing bytes and then simplifying the remaining
+//! ones. Every generated buffer owns a copy of its contents.
+
+use std::fmt;
+
+use bytes::{Bytes, BytesMut};
+
+use collection::{self, SizeRange, VecStrategy, VecValueTree};
+use num;
+use strategy::*;
+use test_runner::*;
+
+mapfn! {
+    [] fn VecToBytes[](bytes: Vec<u8>) -> Bytes {
+        Bytes::from(bytes)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `Bytes`.
+    ///
+    /// Created by the `bytes()` and `bytes_with()` functions in the same
+    /// module.
+    #[derive(Clone, Debug)]
+    pub struct BytesStrategy[<T>][where T : Strategy + fmt::Debug,
+                                        T::Value : ValueTree<Value = u8>](
+        statics::Map<VecStrategy<T>, VecToBytes>)
+        -> BytesValueTree<T::Value>;
+    /// `ValueTree` corresponding to `BytesStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct BytesValueTree[<T>][where T : ValueTree<Value = u8>](
+        statics::Map<VecValueTree<T>, VecToBytes>)
+        -> Bytes;
+}
+
+/// Create a strategy to generate `Bytes` of any byte values, with a length
+/// given by `size`.
+pub fn bytes(size: impl Into<SizeRange>) -> BytesStrategy<num::u8::Any> {
+    bytes_with(num::u8::ANY, size)
+}
+
+/// Create a strategy to generate `Bytes` whose bytes are drawn from
+/// `element`, with a length given by `size`.
+pub fn bytes_with<T : Strategy>(element: T, size: impl Into<SizeRange>)
+                                -> BytesStrategy<T>
+where T::Value : ValueTree<Value = u8> {
+    BytesStrategy(statics::Map::new(
+        collection::vec(element, size), VecToBytes))
+}
+
+mapfn! {
+    [] fn VecToBytesMut[](bytes: Vec<u8>) -> BytesMut {
+        BytesMut::from(&bytes[..])
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `BytesMut`.
+    ///
+    /// Created by the `bytes_mut()` and `bytes_mut_with()` functions in the
+    /// same module.
+    #[derive(Clone, Debug)]
+    pub struct BytesMutStrategy[<T>][where T : Strategy + fmt::Debug,
+                                           T::Value : ValueTree<Value = u8>](
+        statics::Map<VecStrategy<T>, VecToBytesMut>)
+        -> BytesMutValueTree<T::Value>;
+    /// `ValueTree` corresponding to `BytesMutStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct BytesMutValueTree[<T>][where T : ValueTree<Value = u8>](
+        statics::Map<VecValueTree<T>, VecToBytesMut>)
+        -> BytesMut;
+}
+
+/// Create a strategy to generate `BytesMut` of any byte values, with a length
+/// given by `size`.
+pub fn bytes_mut(size: impl Into<SizeRange>)
+                 -> BytesMutStrategy<num::u8::Any> {
+    bytes_mut_with(num::u8::ANY, size)
+}
+
+/// Create a strategy to generate `BytesMut` whose bytes are drawn from
+/// `element`, with a length given by `size`.
+pub fn bytes_mut_with<T : Strategy>(element: T, size: impl Into<SizeRange>)
+                                    -> BytesMutStrategy<T>
+where T::Value : ValueTree<Value = u8> {
+    BytesMutStrategy(statics::Map::new(
+        collection::vec(element, size), VecToBytesMut))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bytes_have_requested_size_and_elements() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let b = bytes(4..8).new_value(&mut runner).unwrap().current();
+            assert!(b.len() >= 4 && b.len() < 8, "Bad length: {}", b.len());
+
+            let b = bytes_mut_with(b'a'..b'e', 3).new_value(&mut runner)
+                .unwrap().current();
+            assert_eq!(3, b.len());
+            assert!(b.iter().all(|&c| c >= b'a' && c < b'e'), "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn bytes_shrink_length_then_values() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = bytes(2..10).new_value(&mut runner).unwrap();
+            let initial = case.current();
+            let mut prev = initial.clone();
+            while case.simplify() {
+                let b = case.current();
+                if b.len() == prev.len() {
+                    assert_eq!(2, b.len(), "{:?} from {:?}", b, initial);
+                }
+                prev = b;
+            }
+            assert_eq!(&[0, 0][..], &case.current()[..]);
+        }
+    }
+}
@@ -336,7 +336,7 @@ mod test {
                 }
 
                 Ok(())
-            }).unwrap()
+            }).unwrap();
     }
 
     #[test]
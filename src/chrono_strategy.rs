@@ -0,0 +1,247 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `chrono` dates, times and durations.
+//!
+//! This module is only available with the `chrono` feature.
+//!
+//! Dates and times are generated as an offset from the Unix epoch, so they
+//! are always valid and shrink towards 1970-01-01 (or whichever end of the
+//! requested range is closest to it). Durations shrink towards zero.
+
+use std::ops::{Range, RangeInclusive};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+
+use num;
+use strategy::*;
+use test_runner::*;
+
+/// `NaiveDate::num_days_from_ce()` of 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+mapfn! {
+    [] fn EpochDaysToDate[](days: i32) -> NaiveDate {
+        NaiveDate::from_num_days_from_ce_opt(EPOCH_DAYS_FROM_CE + days)
+            .expect("generated date out of range")
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `NaiveDate`s.
+    ///
+    /// Created by the `naive_date()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct NaiveDateStrategy[][](
+        statics::Map<Range<i32>, EpochDaysToDate>)
+        -> NaiveDateValueTree;
+    /// `ValueTree` corresponding to `NaiveDateStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct NaiveDateValueTree[][](
+        statics::Map<num::i32::BinarySearch, EpochDaysToDate>)
+        -> NaiveDate;
+}
+
+/// Create a strategy to generate `NaiveDate`s whose year is within
+/// `year_range`.
+///
+/// Every day in the range is equally likely, and only dates which exist are
+/// generated.
+///
+/// ## Panics
+///
+/// Panics if `year_range` is empty or lies outside the years supported by
+/// `NaiveDate`.
+pub fn naive_date(year_range: RangeInclusive<i32>) -> NaiveDateStrategy {
+    let first = NaiveDate::from_ymd_opt(*year_range.start(), 1, 1)
+        .expect("start year out of range");
+    let last = NaiveDate::from_ymd_opt(*year_range.end(), 12, 31)
+        .expect("end year out of range");
+    assert!(first <= last, "empty year range {:?}", year_range);
+
+    NaiveDateStrategy(statics::Map::new(
+        first.num_days_from_ce() - EPOCH_DAYS_FROM_CE ..
+            last.num_days_from_ce() - EPOCH_DAYS_FROM_CE + 1,
+        EpochDaysToDate))
+}
+
+/// The number of nanoseconds in a second which is not a leap second.
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Maps a Unix timestamp and a nanosecond in `0..NANOS_PER_SEC` into an
+/// inclusive range of `DateTime<Utc>`s given as timestamp and nanosecond
+/// pairs.
+#[derive(Clone, Copy, Debug)]
+struct TimestampToDateTime {
+    first: (i64, u32),
+    last: (i64, u32),
+}
+
+impl statics::MapFn<(i64, u32)> for TimestampToDateTime {
+    type Output = DateTime<Utc>;
+
+    fn apply(&self, (secs, nanos): (i64, u32)) -> DateTime<Utc> {
+        // Only the first and last second of the range can be partly outside
+        // it, so scale `nanos` into the part of the second which is inside.
+        let low = if secs == self.first.0 { self.first.1 } else { 0 };
+        let high = if secs == self.last.0 {
+            self.last.1 + 1
+        } else {
+            NANOS_PER_SEC
+        };
+        let nanos = low + (u64::from(nanos) * u64::from(high - low) /
+                           u64::from(NANOS_PER_SEC)) as u32;
+        Utc.timestamp_opt(secs, nanos).single()
+            .expect("generated time out of range")
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `DateTime<Utc>`s.
+    ///
+    /// Created by the `datetime_utc()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct DateTimeUtcStrategy[][](
+        statics::Map<(Range<i64>, Range<u32>), TimestampToDateTime>)
+        -> DateTimeUtcValueTree;
+    /// `ValueTree` corresponding to `DateTimeUtcStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct DateTimeUtcValueTree[][](
+        statics::Map<::tuple::TupleValueTree<(num::i64::BinarySearch,
+                                             num::u32::BinarySearch)>,
+                     TimestampToDateTime>)
+        -> DateTime<Utc>;
+}
+
+/// Create a strategy to generate `DateTime<Utc>`s within `range`.
+///
+/// The whole seconds and the nanoseconds are generated, and shrink,
+/// separately. In the first and last second of `range`, the nanoseconds are
+/// scaled into the part of the second which lies within `range`. Leap
+/// seconds are never generated.
+///
+/// ## Panics
+///
+/// Panics if `range` is empty or lies entirely within a leap second.
+pub fn datetime_utc(range: Range<DateTime<Utc>>) -> DateTimeUtcStrategy {
+    assert!(range.start < range.end, "empty time range {:?}", range);
+
+    let mut first = (range.start.timestamp(),
+                     range.start.timestamp_subsec_nanos());
+    if first.1 >= NANOS_PER_SEC {
+        first = (first.0 + 1, 0);
+    }
+    let last = range.end - Duration::nanoseconds(1);
+    let last = (last.timestamp(),
+                last.timestamp_subsec_nanos().min(NANOS_PER_SEC - 1));
+    assert!(first <= last, "time range {:?} only has leap seconds", range);
+
+    DateTimeUtcStrategy(statics::Map::new(
+        (first.0 .. last.0 + 1, 0..NANOS_PER_SEC),
+        TimestampToDateTime { first, last }))
+}
+
+mapfn! {
+    [] fn NanosToDuration[](nanos: i64) -> Duration {
+        Duration::nanoseconds(nanos)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `chrono::Duration`s.
+    ///
+    /// Created by the `duration()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct DurationStrategy[][](
+        statics::Map<num::i64::Any, NanosToDuration>)
+        -> DurationValueTree;
+    /// `ValueTree` corresponding to `DurationStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct DurationValueTree[][](
+        statics::Map<num::i64::BinarySearch, NanosToDuration>)
+        -> Duration;
+}
+
+/// Create a strategy to generate positive and negative `chrono::Duration`s
+/// of any number of nanoseconds representable as an `i64`, i.e., up to about
+/// 292 years.
+pub fn duration() -> DurationStrategy {
+    DurationStrategy(statics::Map::new(num::i64::ANY, NanosToDuration))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dates_stay_in_year_range_and_shrink_to_epoch() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = naive_date(1900..=2100).new_value(&mut runner)
+                .unwrap();
+            let date = case.current();
+            assert!(date.year() >= 1900 && date.year() <= 2100,
+                    "Bad date: {}", date);
+            while case.simplify() { }
+            assert_eq!(NaiveDate::from_ymd_opt(1970, 1, 1),
+                       Some(case.current()));
+        }
+
+        let mut saw_leap_day = false;
+        for _ in 0..4096 {
+            let date = naive_date(2000..=2001).new_value(&mut runner)
+                .unwrap().current();
+            assert!(2000 == date.year() || 2001 == date.year());
+            saw_leap_day |= 2 == date.month() && 29 == date.day();
+        }
+        assert!(saw_leap_day);
+    }
+
+    #[test]
+    fn datetimes_stay_in_range() {
+        let start = Utc.timestamp_opt(1_000, 500).single().unwrap();
+        let end = Utc.timestamp_opt(1_002, 500).single().unwrap();
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = datetime_utc(start..end).new_value(&mut runner)
+                .unwrap();
+            loop {
+                let time = case.current();
+                assert!(time >= start && time < end, "Bad time: {}", time);
+                if !case.simplify() { break; }
+            }
+        }
+    }
+
+    #[test]
+    fn datetimes_within_one_second_are_not_clamped() {
+        let start = Utc.timestamp_opt(1_000, 250_000_000).single().unwrap();
+        let end = Utc.timestamp_opt(1_000, 750_000_000).single().unwrap();
+        let mut runner = TestRunner::default();
+        let mut times = Vec::new();
+        for _ in 0..256 {
+            let time = datetime_utc(start..end).new_value(&mut runner)
+                .unwrap().current();
+            assert!(time >= start && time < end, "Bad time: {}", time);
+            times.push(time);
+        }
+
+        times.sort();
+        times.dedup();
+        assert!(times.len() > 250, "Only {} distinct times", times.len());
+    }
+
+    #[test]
+    fn durations_shrink_to_zero() {
+        let mut runner = TestRunner::default();
+        let mut case = duration().new_value(&mut runner).unwrap();
+        while case.simplify() { }
+        assert_eq!(Duration::zero(), case.current());
+    }
+}
@@ -11,39 +11,142 @@
 
 #![cfg_attr(feature="cargo-clippy", allow(type_complexity))]
 
-use std::cmp::Ord;
+use std::cmp::{self, Ord};
 use std::collections::*;
 use std::fmt;
 use std::hash::Hash;
-use std::ops::Range;
+use std::marker::PhantomData;
+use std::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
 
 use bit_set::BitSet;
 use rand;
 use rand::distributions::IndependentSample;
+#[cfg(feature = "smallvec")]
+use rand::Rng;
+#[cfg(feature = "smallvec")]
+use smallvec::{Array, SmallVec};
 
 use strategy::*;
 use tuple::TupleValueTree;
 use test_runner::*;
 
+/// The minimum and maximum (exclusive) size of a generated collection.
+///
+/// Collection strategies accept anything which converts into a `SizeRange`:
+///
+/// - A `usize` gives an exact size, e.g., `5` means exactly 5 elements.
+///
+/// - `Range<usize>` and `RangeInclusive<usize>` give an upper-exclusive and
+///   upper-inclusive range of sizes, respectively.
+///
+/// - `RangeTo<usize>` and `RangeToInclusive<usize>` are the same but with a
+///   minimum size of 0.
+///
+/// - `RangeFrom<usize>` gives a minimum size, with a maximum size of
+///   `DEFAULT_MAX_SIZE` (or the minimum, if that is greater).
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SizeRange(Range<usize>);
+
+/// The maximum size, inclusive, of collections whose `SizeRange` was given as
+/// a `RangeFrom`, such as `1..`.
+pub const DEFAULT_MAX_SIZE: usize = 100;
+
+impl SizeRange {
+    /// Return the minimum size, inclusive.
+    pub fn start(&self) -> usize {
+        self.0.start
+    }
+
+    /// Return the maximum size, exclusive.
+    pub fn end_excl(&self) -> usize {
+        self.0.end
+    }
+}
+
+impl From<usize> for SizeRange {
+    fn from(exact: usize) -> Self {
+        SizeRange(exact..exact.saturating_add(1))
+    }
+}
+
+impl From<Range<usize>> for SizeRange {
+    fn from(range: Range<usize>) -> Self {
+        SizeRange(range)
+    }
+}
+
+impl From<RangeInclusive<usize>> for SizeRange {
+    fn from(range: RangeInclusive<usize>) -> Self {
+        SizeRange(*range.start()..range.end().saturating_add(1))
+    }
+}
+
+impl From<RangeTo<usize>> for SizeRange {
+    fn from(range: RangeTo<usize>) -> Self {
+        SizeRange(0..range.end)
+    }
+}
+
+impl From<RangeToInclusive<usize>> for SizeRange {
+    fn from(range: RangeToInclusive<usize>) -> Self {
+        SizeRange(0..range.end.saturating_add(1))
+    }
+}
+
+impl From<RangeFrom<usize>> for SizeRange {
+    fn from(range: RangeFrom<usize>) -> Self {
+        let max = cmp::max(range.start, DEFAULT_MAX_SIZE);
+        SizeRange(range.start..max.saturating_add(1))
+    }
+}
+
 /// Strategy to create `Vec`s with a length in a certain range.
 ///
 /// Created by the `vec()` function in the same module.
 #[derive(Clone, Debug)]
 pub struct VecStrategy<T : Strategy> {
     element: T,
-    size: Range<usize>,
+    size: SizeRange,
+    shrink_from_ends: bool,
 }
 
 /// Create a strategy to generate `Vec`s containing elements drawn from
 /// `element` and with a size range given by `size`.
-pub fn vec<T : Strategy>(element: T, size: Range<usize>)
+///
+/// See `SizeRange` for the types which may be given as `size`; e.g., `5`
+/// gives exactly 5 elements and `1..=10` between 1 and 10 elements.
+pub fn vec<T : Strategy>(element: T, size: impl Into<SizeRange>)
                          -> VecStrategy<T> {
     VecStrategy {
         element: element,
-        size: size,
+        size: size.into(),
+        shrink_from_ends: false,
     }
 }
 
+/// Create a strategy to generate non-empty `Vec`s containing elements drawn
+/// from `element`.
+///
+/// This is equivalent to `vec(element, 1..)`, i.e., generates between 1 and
+/// `DEFAULT_MAX_SIZE` elements. Values shrink towards a single element.
+pub fn vec_nonempty<T : Strategy>(element: T) -> VecStrategy<T> {
+    vec(element, 1..)
+}
+
+/// Create a strategy to generate non-empty `Vec`s of at most `max` elements
+/// drawn from `element`.
+///
+/// This is equivalent to `vec(element, 1..=max)`.
+///
+/// ## Panics
+///
+/// Panics if `max` is 0.
+pub fn vec_nonempty_with_max<T : Strategy>(element: T, max: usize)
+                                           -> VecStrategy<T> {
+    assert!(max > 0, "vec_nonempty_with_max() given a maximum of 0");
+    vec(element, 1..=max)
+}
+
 mapfn! {
     [] fn VecToDeque[<T : fmt::Debug>](vec: Vec<T>) -> VecDeque<T> {
         vec.into()
@@ -67,11 +170,18 @@ opaque_strategy_wrapper! {
 
 /// Create a strategy to generate `VecDeque`s containing elements drawn from
 /// `element` and with a size range given by `size`.
+///
+/// Unlike `vec()`, elements are only removed from the front and back of the
+/// queue while shrinking, never from the middle.
 pub fn vec_deque<T : Strategy>
-    (element: T, size: Range<usize>)
+    (element: T, size: impl Into<SizeRange>)
     -> VecDequeStrategy<T>
 {
-    VecDequeStrategy(statics::Map::new(vec(element, size), VecToDeque))
+    let vec = VecStrategy {
+        shrink_from_ends: true,
+        .. vec(element, size)
+    };
+    VecDequeStrategy(statics::Map::new(vec, VecToDeque))
 }
 
 mapfn! {
@@ -83,7 +193,7 @@ mapfn! {
 opaque_strategy_wrapper! {
     /// Strategy to create `LinkedList`s with a length in a certain range.
     ///
-    /// Created by the `linkedlist()` function in the same module.
+    /// Created by the `linked_list()` function in the same module.
     #[derive(Clone, Debug)]
     pub struct LinkedListStrategy[<T>][where T : Strategy](
         statics::Map<VecStrategy<T>, VecToLl>)
@@ -97,9 +207,12 @@ opaque_strategy_wrapper! {
 
 /// Create a strategy to generate `LinkedList`s containing elements drawn from
 /// `element` and with a size range given by `size`.
+///
+/// Lists shrink like the `Vec`s generated by `vec()`, by removing elements
+/// and then simplifying the remaining ones.
 pub fn linked_list<T : Strategy>
-    (element: T, size: Range<usize>)
-     -> LinkedListStrategy<T>
+    (element: T, size: impl Into<SizeRange>)
+    -> LinkedListStrategy<T>
 {
     LinkedListStrategy(statics::Map::new(vec(element, size), VecToLl))
 }
@@ -128,7 +241,7 @@ opaque_strategy_wrapper! {
 /// Create a strategy to generate `BinaryHeap`s containing elements drawn from
 /// `element` and with a size range given by `size`.
 pub fn binary_heap<T : Strategy>
-    (element: T, size: Range<usize>)
+    (element: T, size: impl Into<SizeRange>)
     -> BinaryHeapStrategy<T>
 where ValueFor<T> : Ord {
     BinaryHeapStrategy(statics::Map::new(vec(element, size), VecToBinHeap))
@@ -156,7 +269,9 @@ opaque_strategy_wrapper! {
     /// Created by the `hash_set()` function in the same module.
     #[derive(Clone, Debug)]
     pub struct HashSetStrategy[<T>][where T : Strategy, ValueFor<T> : Hash + Eq](
-        statics::Filter<statics::Map<VecStrategy<T>, VecToHashSet>, MinSize>)
+        statics::Filter<statics::Map<
+            DistinctVecStrategy<T, HashSet<ValueFor<T>>>, VecToHashSet>,
+                        MinSize>)
         -> HashSetValueTree<T::Value>;
     /// `ValueTree` corresponding to `HashSetStrategy`.
     #[derive(Clone, Debug)]
@@ -168,18 +283,25 @@ opaque_strategy_wrapper! {
 /// Create a strategy to generate `HashSet`s containing elements drawn from
 /// `element` and with a size range given by `size`.
 ///
-/// This strategy will implicitly do local rejects to ensure that the `HashSet`
-/// has at least the minimum number of elements, in case `element` should
-/// produce duplicate values.
+/// The set has exactly the number of elements chosen from `size`: whenever
+/// `element` produces a duplicate value, it is regenerated, which counts as a
+/// local reject. Only if `element` produces 64 duplicates in a row, e.g.
+/// because it has fewer distinct values than the chosen size, is the set
+/// left smaller, and then only if it has at least the minimum size.
+///
+/// Values shrink by first removing elements and then by shrinking the
+/// remaining elements, while never shrinking below the minimum size.
 pub fn hash_set<T : Strategy>
-    (element: T, size: Range<usize>)
+    (element: T, size: impl Into<SizeRange>)
     -> HashSetStrategy<T>
 where ValueFor<T> : Hash + Eq {
-    let min_size = size.start;
+    let size = size.into();
     HashSetStrategy(statics::Filter::new(
-        statics::Map::new(vec(element, size), VecToHashSet),
+        statics::Map::new(DistinctVecStrategy::new(
+            element, size.clone(), "HashSet duplicate element"),
+                          VecToHashSet),
         "HashSet minimum size".into(),
-        MinSize(min_size)))
+        MinSize(size.start())))
 }
 
 mapfn! {
@@ -201,7 +323,9 @@ opaque_strategy_wrapper! {
     /// Created by the `btree_set()` function in the same module.
     #[derive(Clone, Debug)]
     pub struct BTreeSetStrategy[<T>][where T : Strategy, ValueFor<T> : Ord](
-        statics::Filter<statics::Map<VecStrategy<T>, VecToBTreeSet>, MinSize>)
+        statics::Filter<statics::Map<
+            DistinctVecStrategy<T, BTreeSet<ValueFor<T>>>, VecToBTreeSet>,
+                        MinSize>)
         -> BTreeSetValueTree<T::Value>;
     /// `ValueTree` corresponding to `BTreeSetStrategy`.
     #[derive(Clone, Debug)]
@@ -213,19 +337,108 @@ opaque_strategy_wrapper! {
 /// Create a strategy to generate `BTreeSet`s containing elements drawn from
 /// `element` and with a size range given by `size`.
 ///
-/// This strategy will implicitly do local rejects to ensure that the
-/// `BTreeSet` has at least the minimum number of elements, in case `element`
-/// should produce duplicate values.
+/// As with `hash_set()`, duplicate elements are regenerated so that the set
+/// has exactly the number of elements chosen from `size`.
+///
+/// Values shrink by first removing elements, starting with the largest, and
+/// then by shrinking the remaining elements, while never shrinking below the
+/// minimum size.
 pub fn btree_set<T : Strategy>
-    (element: T, size: Range<usize>)
+    (element: T, size: impl Into<SizeRange>)
     -> BTreeSetStrategy<T>
 where ValueFor<T> : Ord {
-    let min_size = size.start;
-
+    let size = size.into();
     BTreeSetStrategy(statics::Filter::new(
-        statics::Map::new(vec(element, size), VecToBTreeSet),
+        statics::Map::new(DistinctVecStrategy::new(
+            element, size.clone(), "BTreeSet duplicate element"),
+                          VecToBTreeSet),
         "BTreeSet minimum size".into(),
-        MinSize(min_size)))
+        MinSize(size.start())))
+}
+
+/// Create a strategy to generate non-empty `BTreeSet`s containing elements
+/// drawn from `element`.
+///
+/// This is equivalent to `btree_set(element, 1..)`.
+pub fn btree_set_nonempty<T : Strategy>(element: T) -> BTreeSetStrategy<T>
+where ValueFor<T> : Ord {
+    btree_set(element, 1..)
+}
+
+mapfn! {
+    [] fn SortVec[<T : fmt::Debug + Ord>](vec: Vec<T>) -> Vec<T> {
+        let mut vec = vec;
+        vec.sort();
+        vec
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create sorted `Vec`s with a length in a certain range.
+    ///
+    /// Created by the `sorted_vec()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct SortedVecStrategy[<T>][where T : Strategy, ValueFor<T> : Ord](
+        statics::Map<LargestFirstVecStrategy<T>, SortVec>)
+        -> SortedVecValueTree<T::Value>;
+    /// `ValueTree` corresponding to `SortedVecStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct SortedVecValueTree[<T>][where T : ValueTree, T::Value : Ord](
+        statics::Map<VecValueTree<T>, SortVec>)
+        -> Vec<T::Value>;
+}
+
+/// Create a strategy to generate `Vec`s sorted in ascending order, containing
+/// elements drawn from `element` and with a size range given by `size`.
+///
+/// The elements are generated exactly as by `vec()` and then sorted, so the
+/// distribution of the multiset of elements is unaffected. The value remains
+/// sorted throughout shrinking, which removes elements, largest first, and
+/// then simplifies the remaining ones.
+pub fn sorted_vec<T : Strategy>(element: T, size: impl Into<SizeRange>)
+                                -> SortedVecStrategy<T>
+where ValueFor<T> : Ord {
+    SortedVecStrategy(statics::Map::new(
+        LargestFirstVecStrategy(vec(element, size)), SortVec))
+}
+
+mapfn! {
+    [] fn BTreeSetToVec[<T : fmt::Debug + Ord>](set: BTreeSet<T>) -> Vec<T> {
+        set.into_iter().collect()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create strictly ascending `Vec`s with a length in a
+    /// certain range.
+    ///
+    /// Created by the `sorted_vec_no_duplicates()` function in the same
+    /// module.
+    #[derive(Clone, Debug)]
+    pub struct SortedVecNoDuplicatesStrategy[<T>]
+        [where T : Strategy, ValueFor<T> : Ord](
+            statics::Map<BTreeSetStrategy<T>, BTreeSetToVec>)
+        -> SortedVecNoDuplicatesValueTree<T::Value>;
+    /// `ValueTree` corresponding to `SortedVecNoDuplicatesStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct SortedVecNoDuplicatesValueTree[<T>]
+        [where T : ValueTree, T::Value : Ord](
+            statics::Map<BTreeSetValueTree<T>, BTreeSetToVec>)
+        -> Vec<T::Value>;
+}
+
+/// Create a strategy to generate strictly ascending `Vec`s, i.e., sorted and
+/// without duplicates, containing elements drawn from `element` and with a
+/// size range given by `size`.
+///
+/// This is `btree_set()` converted to a `Vec`, and likewise regenerates
+/// duplicate elements to reach the chosen size.
+pub fn sorted_vec_no_duplicates<T : Strategy>
+    (element: T, size: impl Into<SizeRange>)
+    -> SortedVecNoDuplicatesStrategy<T>
+where ValueFor<T> : Ord {
+    SortedVecNoDuplicatesStrategy(statics::Map::new(
+        btree_set(element, size), BTreeSetToVec))
 }
 
 mapfn! {
@@ -269,16 +482,145 @@ opaque_strategy_wrapper! {
 /// has at least the minimum number of elements, in case `key` should produce
 /// duplicate values.
 pub fn hash_map<K : Strategy, V : Strategy>
-    (key: K, value: V, size: Range<usize>)
+    (key: K, value: V, size: impl Into<SizeRange>)
     -> HashMapStrategy<K, V>
 where ValueFor<K> : Hash + Eq {
-    let min_size = size.start;
+    let size = size.into();
     HashMapStrategy(statics::Filter::new(
-        statics::Map::new(vec((key, value), size), VecToHashMap),
+        statics::Map::new(vec((key, value), size.clone()), VecToHashMap),
         "HashMap minimum size".into(),
-        MinSize(min_size)))
+        MinSize(size.start())))
+}
+
+/// Create a strategy to generate non-empty `HashMap`s containing keys and
+/// values drawn from `key` and `value` respectively.
+///
+/// This is equivalent to `hash_map(key, value, 1..)`.
+pub fn hash_map_nonempty<K : Strategy, V : Strategy>(key: K, value: V)
+                                                     -> HashMapStrategy<K, V>
+where ValueFor<K> : Hash + Eq {
+    hash_map(key, value, 1..)
+}
+
+#[cfg(feature = "indexmap")]
+mod index_collections {
+    use super::*;
+    use indexmap::{IndexMap, IndexSet};
+
+    // Elements are inserted in reverse, so that the elements `VecValueTree`
+    // tries to delete first are the ones inserted last.
+
+    mapfn! {
+        [] fn VecToIndexSet[<T : fmt::Debug + Hash + Eq>](vec: Vec<T>)
+                                                          -> IndexSet<T> {
+            vec.into_iter().rev().collect()
+        }
+    }
+
+    impl<T : Eq + Hash> statics::FilterFn<IndexSet<T>> for MinSize {
+        fn apply(&self, set: &IndexSet<T>) -> bool {
+            set.len() >= self.0
+        }
+    }
+
+    opaque_strategy_wrapper! {
+        /// Strategy to create `IndexSet`s with a length in a certain range.
+        ///
+        /// Created by the `index_set()` function in the same module.
+        #[derive(Clone, Debug)]
+        pub struct IndexSetStrategy[<T>]
+            [where T : Strategy, ValueFor<T> : Hash + Eq](
+                statics::Filter<statics::Map<VecStrategy<T>, VecToIndexSet>,
+                                MinSize>)
+            -> IndexSetValueTree<T::Value>;
+        /// `ValueTree` corresponding to `IndexSetStrategy`.
+        #[derive(Clone, Debug)]
+        pub struct IndexSetValueTree[<T>]
+            [where T : ValueTree, T::Value : Hash + Eq](
+                statics::Filter<statics::Map<VecValueTree<T>, VecToIndexSet>,
+                                MinSize>)
+            -> IndexSet<T::Value>;
+    }
+
+    /// Create a strategy to generate `indexmap::IndexSet`s containing
+    /// elements drawn from `element` and with a size range given by `size`.
+    ///
+    /// This is like `hash_set()`, but since an `IndexSet` iterates in
+    /// insertion order, generated sets are displayed the same way every time.
+    /// Values shrink by first removing elements, starting with the one
+    /// inserted last, and then by shrinking the remaining elements.
+    ///
+    /// This function is only available with the `indexmap` feature.
+    pub fn index_set<T : Strategy>
+        (element: T, size: impl Into<SizeRange>)
+        -> IndexSetStrategy<T>
+    where ValueFor<T> : Hash + Eq {
+        let size = size.into();
+        IndexSetStrategy(statics::Filter::new(
+            statics::Map::new(vec(element, size.clone()), VecToIndexSet),
+            "IndexSet minimum size".into(),
+            MinSize(size.start())))
+    }
+
+    mapfn! {
+        [] fn VecToIndexMap[<K : fmt::Debug + Hash + Eq, V : fmt::Debug>]
+            (vec: Vec<(K, V)>) -> IndexMap<K, V>
+        {
+            vec.into_iter().rev().collect()
+        }
+    }
+
+    impl<K : Hash + Eq, V> statics::FilterFn<IndexMap<K, V>> for MinSize {
+        fn apply(&self, map: &IndexMap<K, V>) -> bool {
+            map.len() >= self.0
+        }
+    }
+
+    opaque_strategy_wrapper! {
+        /// Strategy to create `IndexMap`s with a length in a certain range.
+        ///
+        /// Created by the `index_map()` function in the same module.
+        #[derive(Clone, Debug)]
+        pub struct IndexMapStrategy[<K, V>]
+            [where K : Strategy, V : Strategy, ValueFor<K> : Hash + Eq](
+                statics::Filter<statics::Map<VecStrategy<(K,V)>,
+                VecToIndexMap>, MinSize>)
+            -> IndexMapValueTree<K::Value, V::Value>;
+        /// `ValueTree` corresponding to `IndexMapStrategy`.
+        #[derive(Clone, Debug)]
+        pub struct IndexMapValueTree[<K, V>]
+            [where K : ValueTree, V : ValueTree, K::Value : Hash + Eq](
+                statics::Filter<statics::Map<
+                    VecValueTree<TupleValueTree<(K, V)>>,
+                VecToIndexMap>, MinSize>)
+            -> IndexMap<K::Value, V::Value>;
+    }
+
+    /// Create a strategy to generate `indexmap::IndexMap`s containing keys and
+    /// values drawn from `key` and `value` respectively, and with a size
+    /// within the given range.
+    ///
+    /// This is like `hash_map()`, but since an `IndexMap` iterates in
+    /// insertion order, generated maps are displayed the same way every time.
+    /// Values shrink by first removing entries, starting with the one
+    /// inserted last, and then by shrinking the remaining entries.
+    ///
+    /// This function is only available with the `indexmap` feature.
+    pub fn index_map<K : Strategy, V : Strategy>
+        (key: K, value: V, size: impl Into<SizeRange>)
+        -> IndexMapStrategy<K, V>
+    where ValueFor<K> : Hash + Eq {
+        let size = size.into();
+        IndexMapStrategy(statics::Filter::new(
+            statics::Map::new(vec((key, value), size.clone()), VecToIndexMap),
+            "IndexMap minimum size".into(),
+            MinSize(size.start())))
+    }
 }
 
+#[cfg(feature = "indexmap")]
+pub use self::index_collections::*;
+
 mapfn! {
     [] fn VecToBTreeMap[<K : fmt::Debug + Ord, V : fmt::Debug>]
         (vec: Vec<(K, V)>) -> BTreeMap<K, V>
@@ -320,19 +662,24 @@ opaque_strategy_wrapper! {
 /// `BTreeMap` has at least the minimum number of elements, in case `key`
 /// should produce duplicate values.
 pub fn btree_map<K : Strategy + 'static, V : Strategy + 'static>
-    (key: K, value: V, size: Range<usize>)
+    (key: K, value: V, size: impl Into<SizeRange>)
     -> BTreeMapStrategy<K, V>
 where ValueFor<K> : Ord {
-    let min_size = size.start;
+    let size = size.into();
     BTreeMapStrategy(statics::Filter::new(
         statics::Map::new(vec((key, value), size.clone()), VecToBTreeMap),
         "BTreeMap minimum size".into(),
-        MinSize(min_size)))
+        MinSize(size.start())))
 }
 
 #[derive(Clone, Copy, Debug)]
-enum Shrink {
+enum VecShrink {
     DeleteElement(usize),
+    // Delete elements from the front, starting at the given index.
+    DeleteFront(usize),
+    // Delete elements from the back; the first index may not be deleted, and
+    // the second is the (exclusive) end of the remaining elements.
+    DeleteBack(usize, usize),
     ShrinkElement(usize),
 }
 
@@ -342,8 +689,8 @@ pub struct VecValueTree<T : ValueTree> {
     elements: Vec<T>,
     included_elements: BitSet,
     min_size: usize,
-    shrink: Shrink,
-    prev_shrink: Option<Shrink>,
+    shrink: VecShrink,
+    prev_shrink: Option<VecShrink>,
 }
 
 impl<T : Strategy> Strategy for VecStrategy<T> {
@@ -351,19 +698,161 @@ impl<T : Strategy> Strategy for VecStrategy<T> {
 
     fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
         let max_size = rand::distributions::Range::new(
-            self.size.start, self.size.end).ind_sample(runner.rng());
+            self.size.start(), self.size.end_excl()).ind_sample(runner.rng());
+        self.new_value_of_size(runner, max_size)
+    }
+}
+
+impl<T : Strategy> VecStrategy<T> {
+    /// Generate a `VecValueTree` starting with exactly `max_size` elements,
+    /// which may still shrink down to the minimum size of `self.size`.
+    fn new_value_of_size(&self, runner: &mut TestRunner, max_size: usize)
+                         -> Result<VecValueTree<T::Value>, Reason> {
         let mut elements = Vec::with_capacity(max_size);
         while elements.len() < max_size {
             elements.push(self.element.new_value(runner)?);
         }
 
-        Ok(VecValueTree {
+        Ok(self.value_tree(elements))
+    }
+
+    /// Make a `VecValueTree` starting with all of `elements`.
+    fn value_tree(&self, elements: Vec<T::Value>) -> VecValueTree<T::Value> {
+        VecValueTree {
+            included_elements: (0..elements.len()).collect(),
             elements: elements,
-            included_elements: (0..max_size).collect(),
-            min_size: self.size.start,
-            shrink: Shrink::DeleteElement(0),
+            min_size: self.size.start(),
+            shrink: if self.shrink_from_ends {
+                VecShrink::DeleteFront(0)
+            } else {
+                VecShrink::DeleteElement(0)
+            },
             prev_shrink: None,
-        })
+        }
+    }
+}
+
+/// A set used by `DistinctVecStrategy` to detect duplicate elements.
+trait DistinctSet<T> : Default {
+    /// Add `value` to the set, returning whether it was not already present.
+    fn insert_new(&mut self, value: T) -> bool;
+
+    /// Reorder `elements`, which are all distinct, into the order in which
+    /// they should be deleted while shrinking.
+    fn deletion_order<V : ValueTree<Value = T>>(_elements: &mut Vec<V>) { }
+}
+
+impl<T : Hash + Eq> DistinctSet<T> for HashSet<T> {
+    fn insert_new(&mut self, value: T) -> bool {
+        self.insert(value)
+    }
+}
+
+impl<T : Ord> DistinctSet<T> for BTreeSet<T> {
+    fn insert_new(&mut self, value: T) -> bool {
+        self.insert(value)
+    }
+
+    /// Since a `BTreeSet` is ordered, the largest elements go first.
+    fn deletion_order<V : ValueTree<Value = T>>(elements: &mut Vec<V>) {
+        elements.sort_by(|a, b| b.current().cmp(&a.current()));
+    }
+}
+
+/// Strategy to create `Vec`s whose elements are deleted from the largest to
+/// the smallest while shrinking, as the basis for `sorted_vec()`.
+#[derive(Clone, Debug)]
+struct LargestFirstVecStrategy<T : Strategy>(VecStrategy<T>);
+
+impl<T : Strategy> Strategy for LargestFirstVecStrategy<T>
+where ValueFor<T> : Ord {
+    type Value = VecValueTree<T::Value>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut value_tree = self.0.new_value(runner)?;
+        // All elements are still included, so they can be freely reordered.
+        value_tree.elements.sort_by(|a, b| b.current().cmp(&a.current()));
+        Ok(value_tree)
+    }
+}
+
+/// The number of duplicate elements in a row after which
+/// `DistinctVecStrategy` settles for fewer elements than it aimed for, as
+/// long as the minimum size is met.
+const MAX_DUPLICATES_IN_A_ROW: u32 = 64;
+
+/// Strategy to create `Vec`s of elements which are distinct according to the
+/// set type `C`, as the basis for the set strategies.
+///
+/// Each element which duplicates an earlier one is regenerated, counting as
+/// a local rejection, so that the `Vec` has the size chosen from the size
+/// range unless `element` keeps producing duplicates.
+struct DistinctVecStrategy<T : Strategy, C> {
+    vec: VecStrategy<T>,
+    whence: Reason,
+    set: PhantomData<fn () -> C>,
+}
+
+impl<T : Strategy + Clone, C> Clone for DistinctVecStrategy<T, C> {
+    fn clone(&self) -> Self {
+        DistinctVecStrategy {
+            vec: self.vec.clone(),
+            whence: self.whence.clone(),
+            set: PhantomData,
+        }
+    }
+}
+
+impl<T : Strategy + fmt::Debug, C> fmt::Debug for DistinctVecStrategy<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DistinctVecStrategy")
+            .field("vec", &self.vec)
+            .field("whence", &self.whence)
+            .finish()
+    }
+}
+
+impl<T : Strategy, C : DistinctSet<ValueFor<T>>>
+DistinctVecStrategy<T, C> {
+    fn new(element: T, size: SizeRange, whence: &'static str) -> Self {
+        DistinctVecStrategy {
+            vec: vec(element, size),
+            whence: whence.into(),
+            set: PhantomData,
+        }
+    }
+}
+
+impl<T : Strategy, C : DistinctSet<ValueFor<T>>>
+Strategy for DistinctVecStrategy<T, C> {
+    type Value = VecValueTree<T::Value>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let size = &self.vec.size;
+        let max_size = rand::distributions::Range::new(
+            size.start(), size.end_excl()).ind_sample(runner.rng());
+
+        let mut seen = C::default();
+        let mut elements = Vec::with_capacity(max_size);
+        let mut duplicates = 0;
+        while elements.len() < max_size {
+            let element = self.vec.element.new_value(runner)?;
+            if seen.insert_new(element.current()) {
+                elements.push(element);
+                duplicates = 0;
+            } else {
+                runner.reject_local(self.whence.clone())?;
+                duplicates += 1;
+                if duplicates >= MAX_DUPLICATES_IN_A_ROW &&
+                    elements.len() >= size.start()
+                {
+                    break;
+                }
+            }
+        }
+
+        C::deletion_order(&mut elements);
+        Ok(self.vec.value_tree(elements))
     }
 }
 
@@ -384,22 +873,48 @@ impl<T : ValueTree> ValueTree for VecValueTree<T> {
         //
         // For `complicate()`, we simply undo the last shrink operation, if
         // there was any.
-        if let Shrink::DeleteElement(ix) = self.shrink {
+        if let VecShrink::DeleteElement(ix) = self.shrink {
             // Can't delete an element if beyond the end of the vec or if it
             // would put us under the minimum length.
             if ix >= self.elements.len() ||
                 self.included_elements.len() == self.min_size
             {
-                self.shrink = Shrink::ShrinkElement(0);
+                self.shrink = VecShrink::ShrinkElement(0);
+            } else {
+                self.included_elements.remove(ix);
+                self.prev_shrink = Some(self.shrink);
+                self.shrink = VecShrink::DeleteElement(ix + 1);
+                return true;
+            }
+        }
+
+        if let VecShrink::DeleteFront(ix) = self.shrink {
+            if ix >= self.elements.len() ||
+                self.included_elements.len() == self.min_size
+            {
+                self.shrink = VecShrink::ShrinkElement(0);
             } else {
                 self.included_elements.remove(ix);
                 self.prev_shrink = Some(self.shrink);
-                self.shrink = Shrink::DeleteElement(ix + 1);
+                self.shrink = VecShrink::DeleteFront(ix + 1);
+                return true;
+            }
+        }
+
+        if let VecShrink::DeleteBack(start, end) = self.shrink {
+            // Everything in `start..end` is still included, since elements
+            // are only ever deleted from the outside in.
+            if end <= start || self.included_elements.len() == self.min_size {
+                self.shrink = VecShrink::ShrinkElement(0);
+            } else {
+                self.included_elements.remove(end - 1);
+                self.shrink = VecShrink::DeleteBack(start, end - 1);
+                self.prev_shrink = Some(self.shrink);
                 return true;
             }
         }
 
-        while let Shrink::ShrinkElement(ix) = self.shrink {
+        while let VecShrink::ShrinkElement(ix) = self.shrink {
             if ix >= self.elements.len() {
                 // Nothing more we can do
                 return false;
@@ -407,13 +922,13 @@ impl<T : ValueTree> ValueTree for VecValueTree<T> {
 
             if !self.included_elements.contains(ix) {
                 // No use shrinking something we're not including.
-                self.shrink = Shrink::ShrinkElement(ix + 1);
+                self.shrink = VecShrink::ShrinkElement(ix + 1);
                 continue;
             }
 
             if !self.elements[ix].simplify() {
                 // Move on to the next element
-                self.shrink = Shrink::ShrinkElement(ix + 1);
+                self.shrink = VecShrink::ShrinkElement(ix + 1);
             } else {
                 self.prev_shrink = Some(self.shrink);
                 return true;
@@ -426,14 +941,29 @@ impl<T : ValueTree> ValueTree for VecValueTree<T> {
     fn complicate(&mut self) -> bool {
         match self.prev_shrink {
             None => false,
-            Some(Shrink::DeleteElement(ix)) => {
+            Some(VecShrink::DeleteElement(ix)) => {
                 // Undo the last item we deleted. Can't complicate any further,
                 // so unset prev_shrink.
                 self.included_elements.insert(ix);
                 self.prev_shrink = None;
                 true
             },
-            Some(Shrink::ShrinkElement(ix)) => {
+            Some(VecShrink::DeleteFront(ix)) => {
+                // The front element must stay, so continue by deleting from
+                // the back, stopping before the element we just restored.
+                self.included_elements.insert(ix);
+                self.prev_shrink = None;
+                self.shrink = VecShrink::DeleteBack(ix + 1, self.elements.len());
+                true
+            },
+            Some(VecShrink::DeleteBack(_, ix)) => {
+                // Both ends are now pinned; move on to shrinking elements.
+                self.included_elements.insert(ix);
+                self.prev_shrink = None;
+                self.shrink = VecShrink::ShrinkElement(0);
+                true
+            },
+            Some(VecShrink::ShrinkElement(ix)) => {
                 if self.elements[ix].complicate() {
                     // Don't unset prev_shrink; we may be able to complicate
                     // again.
@@ -446,6 +976,125 @@ impl<T : ValueTree> ValueTree for VecValueTree<T> {
             }
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        // Each element counts for one in addition to its own complexity, so
+        // that deleting an element always reduces the estimate.
+        self.included_elements.iter().fold(
+            0u64, |sum, ix| sum.saturating_add(1).saturating_add(
+                self.elements[ix].current_complexity()))
+    }
+}
+
+/// Strategy to create `SmallVec`s with a length in a certain range.
+///
+/// Created by the `small_vec()` function in the same module.
+#[cfg(feature = "smallvec")]
+pub struct SmallVecStrategy<T : Strategy, A> {
+    vec: VecStrategy<T>,
+    _array: PhantomData<fn () -> A>,
+}
+
+#[cfg(feature = "smallvec")]
+impl<T : Strategy + Clone, A> Clone for SmallVecStrategy<T, A> {
+    fn clone(&self) -> Self {
+        SmallVecStrategy { vec: self.vec.clone(), _array: PhantomData }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T : Strategy + fmt::Debug, A> fmt::Debug for SmallVecStrategy<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SmallVecStrategy")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+/// Create a strategy to generate `SmallVec`s containing elements drawn from
+/// `element` and with a size range given by `size`.
+///
+/// When `size` admits both lengths which fit inline in `A` and lengths which
+/// spill onto the heap, each of the two cases is chosen half of the time, so
+/// that code handling either representation is exercised. Shrinking behaves
+/// exactly as for `vec()`.
+///
+/// This function is only available with the `smallvec` feature.
+#[cfg(feature = "smallvec")]
+pub fn small_vec<T : Strategy, A : Array<Item = ValueFor<T>>>(
+    element: T, size: impl Into<SizeRange>) -> SmallVecStrategy<T, A>
+{
+    SmallVecStrategy {
+        vec: vec(element, size),
+        _array: PhantomData,
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T : Strategy, A : Array<Item = ValueFor<T>>> Strategy
+for SmallVecStrategy<T, A> {
+    type Value = SmallVecValueTree<T::Value, A>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let (start, end) = (self.vec.size.start(), self.vec.size.end_excl());
+        // Smallest length which no longer fits inline.
+        let spilled = A::size() + 1;
+        let (start, end) = if start < spilled && spilled < end {
+            if runner.rng().gen() { (start, spilled) } else { (spilled, end) }
+        } else {
+            (start, end)
+        };
+
+        let max_size = rand::distributions::Range::new(start, end)
+            .ind_sample(runner.rng());
+        Ok(SmallVecValueTree(self.vec.new_value_of_size(runner, max_size)?,
+                             PhantomData))
+    }
+}
+
+/// `ValueTree` corresponding to `SmallVecStrategy`.
+#[cfg(feature = "smallvec")]
+pub struct SmallVecValueTree<T : ValueTree, A>(
+    VecValueTree<T>, PhantomData<fn () -> A>);
+
+#[cfg(feature = "smallvec")]
+impl<T : ValueTree + Clone, A> Clone for SmallVecValueTree<T, A> {
+    fn clone(&self) -> Self {
+        SmallVecValueTree(self.0.clone(), PhantomData)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T : ValueTree + fmt::Debug, A> fmt::Debug for SmallVecValueTree<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SmallVecValueTree").field(&self.0).finish()
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T : ValueTree, A : Array<Item = T::Value>> ValueTree
+for SmallVecValueTree<T, A> {
+    type Value = SmallVec<A>;
+
+    fn current(&self) -> SmallVec<A> {
+        self.0.current().into_iter().collect()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.0.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.0.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.0.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.0.is_terminal()
+    }
 }
 
 #[cfg(test)]
@@ -494,6 +1143,81 @@ mod test {
         check_strategy_sanity(vec(0i32..1000, 5..10), None);
     }
 
+    #[test]
+    fn nonempty_collections_are_never_empty() {
+        let mut runner = TestRunner::default();
+
+        for _ in 0..64 {
+            let mut case = vec_nonempty(0u32..10).new_value(&mut runner)
+                .unwrap();
+            assert!(case.current().len() >= 1 &&
+                    case.current().len() <= DEFAULT_MAX_SIZE);
+            while case.simplify() {
+                assert!(!case.current().is_empty());
+            }
+            assert_eq!(vec![0], case.current());
+
+            let v = vec_nonempty_with_max(0u32..10, 3).new_value(&mut runner)
+                .unwrap().current();
+            assert!(v.len() >= 1 && v.len() <= 3);
+
+            let mut case = btree_set_nonempty(0u32..1000)
+                .new_value(&mut runner).unwrap();
+            while case.simplify() {
+                assert!(!case.current().is_empty());
+            }
+            assert_eq!(1, case.current().len());
+
+            assert!(!hash_map_nonempty(0u32..1000, 0u32..10)
+                    .new_value(&mut runner).unwrap().current().is_empty());
+        }
+    }
+
+    #[test]
+    fn sorted_vecs_stay_sorted_while_shrinking() {
+        let mut runner = TestRunner::default();
+
+        for _ in 0..64 {
+            let mut case = sorted_vec(0u32..10, 1..20)
+                .new_value(&mut runner).unwrap();
+            loop {
+                let v = case.current();
+                assert!(v.windows(2).all(|w| w[0] <= w[1]), "{:?}", v);
+                if !case.simplify() { break; }
+            }
+
+            let mut case = sorted_vec_no_duplicates(0u32..100, 1..20)
+                .new_value(&mut runner).unwrap();
+            loop {
+                let v = case.current();
+                assert!(v.windows(2).all(|w| w[0] < w[1]), "{:?}", v);
+                if !case.simplify() { break; }
+            }
+        }
+    }
+
+    #[test]
+    fn sorted_vec_deletes_largest_elements_first() {
+        let mut runner = TestRunner::default();
+
+        for _ in 0..64 {
+            let mut case = sorted_vec(0u32..100, 1..20)
+                .new_value(&mut runner).unwrap();
+            let mut v = case.current();
+            while v.len() > 1 {
+                assert!(case.simplify());
+                v.pop();
+                assert_eq!(v, case.current());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn vec_nonempty_with_max_rejects_zero() {
+        vec_nonempty_with_max(0u32..10, 0);
+    }
+
     #[test]
     fn test_map() {
         // Only 8 possible keys
@@ -506,6 +1230,185 @@ mod test {
         }
     }
 
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_collections_shrink_by_removing_last_inserted_first() {
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = index_map(0u64..1 << 48, 0u32..10, 1..10)
+                .new_value(&mut runner).unwrap();
+            let initial = case.current();
+            if initial.len() > 1 {
+                assert!(case.simplify());
+                let mut expected = initial.clone();
+                expected.pop();
+                assert_eq!(expected.into_iter().collect::<Vec<_>>(),
+                           case.current().into_iter().collect::<Vec<_>>());
+            }
+
+            let mut case = index_set(0u64..1 << 48, 1..10)
+                .new_value(&mut runner).unwrap();
+            let initial = case.current();
+            if initial.len() > 1 {
+                assert!(case.simplify());
+                let mut expected = initial.clone();
+                expected.pop();
+                assert_eq!(expected.into_iter().collect::<Vec<_>>(),
+                           case.current().into_iter().collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_collections_respect_minimum_size() {
+        // Only 8 possible keys
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let map = index_map("[ab]{3}", "a", 2..3)
+                .new_value(&mut runner).unwrap().current();
+            assert_eq!(2, map.len());
+            let set = index_set("[ab]{3}", 2..3)
+                .new_value(&mut runner).unwrap().current();
+            assert_eq!(2, set.len());
+        }
+    }
+
+    #[test]
+    fn test_map_size_range() {
+        let mut runner = TestRunner::default();
+
+        let exact = btree_map(0u32..1000, 0u32..10, 4);
+        for _ in 0..256 {
+            let v = exact.new_value(&mut runner).unwrap().current();
+            assert_eq!(4, v.len());
+        }
+
+        let inclusive = hash_map(0u32..1000, 0u32..10, 2..=3);
+        for _ in 0..256 {
+            let v = inclusive.new_value(&mut runner).unwrap().current();
+            assert!(v.len() >= 2 && v.len() <= 3, "Bad length {}", v.len());
+        }
+    }
+
+    #[test]
+    fn test_vec_size_range() {
+        fn lengths(size: impl Into<SizeRange>) -> (usize, usize) {
+            let input = vec(0u8..10, size);
+            let mut runner = TestRunner::default();
+            (0..256)
+                .map(|_| input.new_value(&mut runner).unwrap().current().len())
+                .fold((!0, 0), |(lo, hi), len| (lo.min(len), hi.max(len)))
+        }
+
+        assert_eq!((5, 5), lengths(5));
+        assert_eq!((1, 3), lengths(1..=3));
+        assert_eq!((0, 2), lengths(..3));
+        assert_eq!((0, 3), lengths(..=3));
+
+        let (lo, hi) = lengths(90..);
+        assert!(lo >= 90 && hi <= DEFAULT_MAX_SIZE,
+                "Bad range {}..={}", lo, hi);
+        assert_eq!(SizeRange::from(500..=500), SizeRange::from(500..));
+    }
+
+    #[test]
+    fn vec_deque_shrinks_from_ends() {
+        let input = vec_deque((0u32..1000).no_shrink(), 2..20);
+
+        for _ in 0..256 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let original: Vec<_> = case.current().into_iter().collect();
+            let result = runner.run_one(case, |deque| {
+                prop_assert!(deque.iter().sum::<u32>() < 1000);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, deque)) => {
+                    let window: Vec<_> = deque.into_iter().collect();
+                    assert!(window.len() >= 2);
+                    assert!(original.windows(window.len())
+                            .any(|w| w == &window[..]),
+                            "{:?} is not a window of {:?}", window, original);
+                },
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_vec_deque_and_binary_heap_sizes() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            assert_eq!(3, vec_deque(0u8..10, 3).new_value(&mut runner)
+                       .unwrap().current().len());
+            let len = binary_heap(0u8..10, ..=2).new_value(&mut runner)
+                .unwrap().current().len();
+            assert!(len <= 2);
+        }
+    }
+
+    #[test]
+    fn test_vec_deque_sanity() {
+        check_strategy_sanity(vec_deque(0i32..1000, 0..10), None);
+    }
+
+    #[test]
+    fn linked_list_sizes_and_shrinking() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let len = linked_list(0u8..10, 2..=4).new_value(&mut runner)
+                .unwrap().current().len();
+            assert!(len >= 2 && len <= 4);
+        }
+
+        for _ in 0..32 {
+            let case = linked_list(0u32..1000, 0..20)
+                .new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |list| {
+                prop_assert!(list.iter().all(|&v| v < 500));
+                Ok(())
+            });
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, list)) => assert_eq!(
+                    vec![500], list.into_iter().collect::<Vec<_>>()),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_linked_list_sanity() {
+        check_strategy_sanity(linked_list(0i32..1000, 0..10), None);
+    }
+
+    #[test]
+    fn test_vec_complexity() {
+        let input = vec(0u32..100, 5..10);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let value = case.current();
+            assert_eq!(value.len() as u64 +
+                       value.iter().map(|&v| u64::from(v)).sum::<u64>(),
+                       case.current_complexity());
+
+            let mut prev = case.current_complexity();
+            while case.simplify() {
+                let complexity = case.current_complexity();
+                assert!(complexity <= prev, "{} > {}", complexity, prev);
+                prev = complexity;
+            }
+            assert_eq!(5, case.current_complexity());
+        }
+    }
+
     #[test]
     fn test_set() {
         // Only 8 possible values
@@ -517,4 +1420,84 @@ mod test {
             assert_eq!(2, v.len());
         }
     }
+
+    #[test]
+    fn sets_have_exactly_the_chosen_size() {
+        let mut runner = TestRunner::default();
+        let mut sizes = BTreeSet::new();
+        for _ in 0..256 {
+            let len = hash_set(0u8..16, 10).new_value(&mut runner).unwrap()
+                .current().len();
+            assert_eq!(10, len);
+            let len = btree_set(0u8..16, 16).new_value(&mut runner).unwrap()
+                .current().len();
+            assert_eq!(16, len);
+
+            sizes.insert(btree_set(0u32..1000, 3..6).new_value(&mut runner)
+                         .unwrap().current().len());
+        }
+        assert_eq!((3..6).collect::<BTreeSet<_>>(), sizes);
+    }
+
+    #[test]
+    fn sets_settle_for_fewer_elements_than_possible() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let set = hash_set(0u8..4, 2..10).new_value(&mut runner).unwrap()
+                .current();
+            assert!(set.len() >= 2 && set.len() <= 4);
+        }
+    }
+
+    #[test]
+    fn btree_sets_shrink_by_removing_largest_first() {
+        let input = btree_set(0u32..1000, 2..10);
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let mut expected = case.current();
+            while expected.len() > 2 {
+                let largest = *expected.iter().next_back().unwrap();
+                expected.remove(&largest);
+                assert!(case.simplify());
+                assert_eq!(expected, case.current());
+            }
+        }
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn small_vecs_are_often_inline_and_often_spilled() {
+        let input = small_vec::<_, [u8; 4]>(0u8..10, 0..20);
+        let mut runner = TestRunner::default();
+        let mut spilled = 0;
+
+        for _ in 0..1024 {
+            let v = input.new_value(&mut runner).unwrap().current();
+            assert!(v.len() < 20);
+            assert_eq!(v.len() > 4, v.spilled());
+            if v.spilled() { spilled += 1; }
+        }
+
+        assert!(spilled > 1024 * 3 / 10 && spilled < 1024 * 7 / 10,
+                "Spilled {} times out of 1024", spilled);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn small_vecs_shrink_like_vecs() {
+        let input = small_vec::<_, [u8; 2]>(1u8..10, 1..8);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let mut inner = case.0.clone();
+            while case.simplify() {
+                assert!(inner.simplify());
+                assert_eq!(&inner.current()[..], &case.current()[..]);
+            }
+            assert!(!inner.simplify());
+            assert_eq!(&[1u8][..], &case.current()[..]);
+        }
+    }
 }
@@ -0,0 +1,211 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating inputs from a corpus directory.
+//!
+//! A corpus is a directory of files, each holding one interesting input, as
+//! maintained by many fuzzing workflows. The strategies here produce the raw
+//! contents of those files; use `prop_map()` or `prop_filter_map()` to parse
+//! them into the type under test.
+//!
+//! Files are loaded once, when the strategy is created, and in order of file
+//! name so that the same corpus always produces the same values from the same
+//! seed. Values shrink towards the first file of the corpus.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use collection::{self, VecStrategy};
+use num;
+use sample::{self, Select, SelectValueTree};
+use strategy::*;
+use test_runner::*;
+
+/// Read the contents of every file directly within `dir`, ordered by file
+/// name.
+///
+/// Subdirectories are ignored.
+pub fn load_dir<P : AsRef<Path>>(dir: P) -> io::Result<Vec<Vec<u8>>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    paths.into_iter().map(|path| {
+        let mut data = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut data)?;
+        Ok(data)
+    }).collect()
+}
+
+/// Strategy which samples the files of a corpus, or uses another strategy if
+/// the corpus is empty.
+///
+/// Created by the functions in this module.
+#[derive(Clone, Debug)]
+pub struct CorpusStrategy<S> {
+    samples: Option<Select<Vec<u8>>>,
+    fallback: S,
+}
+
+impl<S : Strategy> Strategy for CorpusStrategy<S>
+where S::Value : ValueTree<Value = Vec<u8>> {
+    type Value = CorpusValueTree<S::Value>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(CorpusValueTree(match self.samples {
+            Some(ref samples) =>
+                CorpusValueTreeInner::Sample(samples.new_value(runner)?),
+            None =>
+                CorpusValueTreeInner::Fallback(
+                    self.fallback.new_value(runner)?),
+        }))
+    }
+}
+
+/// `ValueTree` corresponding to `CorpusStrategy`.
+#[derive(Clone, Debug)]
+pub struct CorpusValueTree<T>(CorpusValueTreeInner<T>);
+
+#[derive(Clone, Debug)]
+enum CorpusValueTreeInner<T> {
+    Sample(SelectValueTree<Vec<u8>>),
+    Fallback(T),
+}
+
+impl<T : ValueTree<Value = Vec<u8>>> ValueTree for CorpusValueTree<T> {
+    type Value = Vec<u8>;
+
+    fn current(&self) -> Vec<u8> {
+        match self.0 {
+            CorpusValueTreeInner::Sample(ref t) => t.current(),
+            CorpusValueTreeInner::Fallback(ref t) => t.current(),
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        match self.0 {
+            CorpusValueTreeInner::Sample(ref mut t) => t.simplify(),
+            CorpusValueTreeInner::Fallback(ref mut t) => t.simplify(),
+        }
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.0 {
+            CorpusValueTreeInner::Sample(ref mut t) => t.complicate(),
+            CorpusValueTreeInner::Fallback(ref mut t) => t.complicate(),
+        }
+    }
+
+    fn current_complexity(&self) -> u64 {
+        match self.0 {
+            CorpusValueTreeInner::Sample(ref t) => t.current_complexity(),
+            CorpusValueTreeInner::Fallback(ref t) => t.current_complexity(),
+        }
+    }
+}
+
+/// Create a strategy which uniformly selects the contents of one of the files
+/// in `dir`, as per `load_dir()`.
+///
+/// If `dir` contains no files, `fallback` is used instead.
+pub fn from_dir_with_fallback<P : AsRef<Path>, S : Strategy>
+    (dir: P, fallback: S) -> io::Result<CorpusStrategy<S>>
+where S::Value : ValueTree<Value = Vec<u8>> {
+    let corpus = load_dir(dir)?;
+    Ok(CorpusStrategy {
+        samples: if corpus.is_empty() {
+            None
+        } else {
+            Some(sample::select(corpus))
+        },
+        fallback,
+    })
+}
+
+/// Create a strategy which uniformly selects the contents of one of the files
+/// in `dir`, as per `load_dir()`.
+///
+/// If `dir` contains no files, arbitrary byte strings of up to 255 bytes are
+/// generated instead.
+pub fn from_dir<P : AsRef<Path>>(dir: P)
+    -> io::Result<CorpusStrategy<VecStrategy<num::u8::Any>>>
+{
+    from_dir_with_fallback(dir, collection::vec(num::u8::ANY, 0..256))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn make_dir(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "proptest-corpus-{}-{}", name, ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("subdir")).unwrap();
+        for &(file, contents) in files {
+            fs::File::create(dir.join(file)).unwrap()
+                .write_all(contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn samples_files_in_name_order() {
+        let dir = make_dir("sample", &[
+            ("b", b"second"), ("a", b"first"), ("c", b"third")]);
+        assert_eq!(vec![b"first".to_vec(), b"second".to_vec(),
+                        b"third".to_vec()],
+                   load_dir(&dir).unwrap());
+
+        let input = from_dir(&dir).unwrap();
+        let mut seen = [false; 3];
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            match &case.current()[..] {
+                b"first" => seen[0] = true,
+                b"second" => seen[1] = true,
+                b"third" => seen[2] = true,
+                other => panic!("Unexpected value {:?}", other),
+            }
+            while case.simplify() { }
+            assert_eq!(b"first".to_vec(), case.current());
+        }
+        assert_eq!([true; 3], seen);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty_corpus_uses_fallback() {
+        let dir = make_dir("empty", &[]);
+        let input = from_dir_with_fallback(&dir, Just(b"x".to_vec())).unwrap();
+        let mut runner = TestRunner::default();
+        assert_eq!(b"x".to_vec(),
+                   input.new_value(&mut runner).unwrap().current());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_dir_is_an_error() {
+        assert!(from_dir("/nonexistent/proptest/corpus").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `Cow<'static, str>` and `Cow<'static, [T]>`
+//! values.
+//!
+//! Since a strategy cannot borrow from the values it generates, borrowed
+//! values are instead chosen from a fixed list of `'static` values, while
+//! owned values are generated by another strategy.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use sample::{self, Select};
+use strategy::*;
+use test_runner::*;
+
+struct ToBorrowed<B : ?Sized>(PhantomData<fn (&B)>);
+impl<B : ?Sized> Clone for ToBorrowed<B> {
+    fn clone(&self) -> Self { *self }
+}
+impl<B : ?Sized> Copy for ToBorrowed<B> { }
+impl<B : ?Sized> fmt::Debug for ToBorrowed<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToBorrowed")
+    }
+}
+impl<B : ?Sized + ToOwned + fmt::Debug + 'static>
+statics::MapFn<&'static B> for ToBorrowed<B>
+where B::Owned : fmt::Debug {
+    type Output = Cow<'static, B>;
+    fn apply(&self, value: &'static B) -> Cow<'static, B> {
+        Cow::Borrowed(value)
+    }
+}
+
+struct ToOwnedCow<B : ?Sized>(PhantomData<fn (&B)>);
+impl<B : ?Sized> Clone for ToOwnedCow<B> {
+    fn clone(&self) -> Self { *self }
+}
+impl<B : ?Sized> Copy for ToOwnedCow<B> { }
+impl<B : ?Sized> fmt::Debug for ToOwnedCow<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToOwnedCow")
+    }
+}
+impl<B : ?Sized + ToOwned + fmt::Debug + 'static>
+statics::MapFn<B::Owned> for ToOwnedCow<B>
+where B::Owned : fmt::Debug {
+    type Output = Cow<'static, B>;
+    fn apply(&self, value: B::Owned) -> Cow<'static, B> {
+        Cow::Owned(value)
+    }
+}
+
+type CowOptions<B, S> = (W<statics::Map<Select<&'static B>, ToBorrowed<B>>>,
+                         W<statics::Map<S, ToOwnedCow<B>>>);
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Cow<'static, B>` values which are either
+    /// borrowed from a fixed list or owned and generated by another strategy.
+    ///
+    /// Created by the `cow_str()` and `cow_slice()` functions in the same
+    /// module.
+    #[derive(Clone)]
+    pub struct CowStrategy[<B, S>][where B : ?Sized + ToOwned + fmt::Debug
+                                              + 'static,
+                                   B::Owned : fmt::Debug,
+                                   S : Strategy,
+                                   S::Value : ValueTree<Value = B::Owned>]
+        (TupleUnion<CowOptions<B, S>>)
+        -> CowValueTree<B, S>;
+    /// `ValueTree` type corresponding to `CowStrategy`.
+    pub struct CowValueTree[<B, S>][where B : ?Sized + ToOwned + fmt::Debug
+                                               + 'static,
+                                    B::Owned : fmt::Debug,
+                                    S : Strategy,
+                                    S::Value : ValueTree<Value = B::Owned>]
+        (TupleUnionValueTree<(
+            LazyValueTree<CowOptions<B, S>,
+                          statics::Map<Select<&'static B>, ToBorrowed<B>>>,
+            Option<LazyValueTree<CowOptions<B, S>,
+                                 statics::Map<S, ToOwnedCow<B>>>>)>)
+        -> Cow<'static, B>;
+}
+
+// As in the `option` module, deriving these would add constraints on `B`
+// that are not actually needed.
+impl<B, S> fmt::Debug for CowStrategy<B, S>
+where B : ?Sized + ToOwned + fmt::Debug + 'static, B::Owned : fmt::Debug,
+      S : Strategy + fmt::Debug, S::Value : ValueTree<Value = B::Owned> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CowStrategy({:?})", self.0)
+    }
+}
+
+impl<B, S> Clone for CowValueTree<B, S>
+where B : ?Sized + ToOwned + fmt::Debug + 'static, B::Owned : fmt::Debug,
+      S : Strategy, S::Value : ValueTree<Value = B::Owned> + Clone {
+    fn clone(&self) -> Self {
+        CowValueTree(self.0.clone())
+    }
+}
+impl<B, S> fmt::Debug for CowValueTree<B, S>
+where B : ?Sized + ToOwned + fmt::Debug + 'static, B::Owned : fmt::Debug,
+      S : Strategy, S::Value : ValueTree<Value = B::Owned> + fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CowValueTree({:?})", self.0)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Cow::Owned` values whose contents are
+    /// generated by another strategy.
+    ///
+    /// Created by the `cow_str_owned()` and `cow_slice_owned()` functions in
+    /// the same module.
+    #[derive(Clone, Debug)]
+    pub struct CowOwnedStrategy[<B, S>][where B : ?Sized + ToOwned
+                                                   + fmt::Debug + 'static,
+                                        B::Owned : fmt::Debug,
+                                        S : Strategy,
+                                        S::Value : ValueTree<Value = B::Owned>]
+        (statics::Map<S, ToOwnedCow<B>>)
+        -> CowOwnedValueTree<B, S::Value>;
+    /// `ValueTree` type corresponding to `CowOwnedStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct CowOwnedValueTree[<B, T>][where B : ?Sized + ToOwned
+                                                    + fmt::Debug + 'static,
+                                         B::Owned : fmt::Debug,
+                                         T : ValueTree<Value = B::Owned>]
+        (statics::Map<T, ToOwnedCow<B>>)
+        -> Cow<'static, B>;
+}
+
+fn cow<B, S>(borrowed: &'static [&'static B], owned: S) -> CowStrategy<B, S>
+where B : ?Sized + ToOwned + fmt::Debug + 'static, B::Owned : fmt::Debug,
+      S : Strategy, S::Value : ValueTree<Value = B::Owned> {
+    assert!(!borrowed.is_empty(), "No borrowed values to choose from");
+
+    CowStrategy(TupleUnion::new((
+        (1, statics::Map::new(sample::select(borrowed),
+                              ToBorrowed(PhantomData))),
+        (1, statics::Map::new(owned, ToOwnedCow(PhantomData))),
+    )))
+}
+
+/// Create a strategy to generate `Cow<'static, str>`s which are either
+/// `Cow::Borrowed` strings chosen from `borrowed` or `Cow::Owned` strings
+/// generated by `owned`, each with 50% probability.
+///
+/// Owned values shrink to borrowed ones, and borrowed values shrink towards
+/// the first element of `borrowed`.
+///
+/// ## Panics
+///
+/// Panics if `borrowed` is empty. Use `cow_str_owned()` to only generate
+/// owned strings.
+///
+/// ```
+/// use proptest::cow::cow_str;
+///
+/// let names = cow_str(&["alice", "bob"], "[a-z]{1,8}");
+/// # let _ = names;
+/// ```
+pub fn cow_str<S>(borrowed: &'static [&'static str], owned: S)
+                  -> CowStrategy<str, S>
+where S : Strategy, S::Value : ValueTree<Value = String> {
+    cow(borrowed, owned)
+}
+
+/// Create a strategy to generate `Cow<'static, str>`s which are always
+/// `Cow::Owned` strings generated by `owned`.
+pub fn cow_str_owned<S>(owned: S) -> CowOwnedStrategy<str, S>
+where S : Strategy, S::Value : ValueTree<Value = String> {
+    CowOwnedStrategy(statics::Map::new(owned, ToOwnedCow(PhantomData)))
+}
+
+/// Create a strategy to generate `Cow<'static, [T]>`s which are either
+/// `Cow::Borrowed` slices chosen from `borrowed` or `Cow::Owned` `Vec`s
+/// generated by `owned`, each with 50% probability.
+///
+/// Owned values shrink to borrowed ones, and borrowed values shrink towards
+/// the first element of `borrowed`.
+///
+/// ## Panics
+///
+/// Panics if `borrowed` is empty. Use `cow_slice_owned()` to only generate
+/// owned slices.
+pub fn cow_slice<T, S>(borrowed: &'static [&'static [T]], owned: S)
+                       -> CowStrategy<[T], S>
+where T : Clone + fmt::Debug + 'static,
+      S : Strategy, S::Value : ValueTree<Value = Vec<T>> {
+    cow(borrowed, owned)
+}
+
+/// Create a strategy to generate `Cow<'static, [T]>`s which are always
+/// `Cow::Owned` `Vec`s generated by `owned`.
+pub fn cow_slice_owned<T, S>(owned: S) -> CowOwnedStrategy<[T], S>
+where T : Clone + fmt::Debug + 'static,
+      S : Strategy, S::Value : ValueTree<Value = Vec<T>> {
+    CowOwnedStrategy(statics::Map::new(owned, ToOwnedCow(PhantomData)))
+}
+
+#[cfg(test)]
+mod test {
+    use collection;
+    use super::*;
+
+    const NAMES: &[&str] = &["alice", "bob"];
+
+    #[test]
+    fn cow_str_generates_both_variants_and_shrinks_to_borrowed() {
+        let input = cow_str(NAMES, "[a-z]{1,8}");
+        let mut runner = TestRunner::default();
+        let mut borrowed = 0;
+
+        for _ in 0..1024 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            match case.current() {
+                Cow::Borrowed(s) => {
+                    assert!(NAMES.contains(&s));
+                    borrowed += 1;
+                },
+                Cow::Owned(s) => assert!(!s.is_empty() && s.len() <= 8),
+            }
+
+            while case.simplify() { }
+            match case.current() {
+                Cow::Borrowed(s) => assert_eq!("alice", s),
+                owned => panic!("Did not shrink to borrowed: {:?}", owned),
+            }
+        }
+
+        assert!(borrowed > 400 && borrowed < 624,
+                "Borrowed {} times out of 1024", borrowed);
+    }
+
+    #[test]
+    fn cow_str_owned_generates_only_owned() {
+        let input = cow_str_owned("[a-z]{1,8}");
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            match input.new_value(&mut runner).unwrap().current() {
+                Cow::Owned(_) => (),
+                borrowed => panic!("Unexpected borrow: {:?}", borrowed),
+            }
+        }
+    }
+
+    #[test]
+    fn cow_slice_generates_both_variants() {
+        const SLICES: &[&[u8]] = &[&[], &[1, 2, 3]];
+        let input = cow_slice(SLICES, collection::vec(0u8..10, 0..4));
+        let mut runner = TestRunner::default();
+        let (mut borrowed, mut owned) = (0, 0);
+
+        for _ in 0..256 {
+            match input.new_value(&mut runner).unwrap().current() {
+                Cow::Borrowed(s) => {
+                    assert!(SLICES.contains(&s));
+                    borrowed += 1;
+                },
+                Cow::Owned(v) => {
+                    assert!(v.len() < 4);
+                    owned += 1;
+                },
+            }
+        }
+
+        assert!(borrowed > 0 && owned > 0);
+        let value: Cow<'static, [u8]> = cow_slice_owned(Just(vec![1u8]))
+            .new_value(&mut runner).unwrap().current();
+        assert_eq!(Cow::Owned::<[u8]>(vec![1]), value);
+    }
+
+    #[test]
+    #[should_panic(expected = "No borrowed values")]
+    fn cow_str_panics_without_borrowed_values() {
+        cow_str(&[], "[a-z]");
+    }
+}
@@ -0,0 +1,298 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `std::ffi` values.
+//!
+//! All strategies in this module shrink by first removing elements and then
+//! by simplifying the elements that remain.
+
+use std::ffi::{CString, OsString};
+use std::ops::Range;
+
+use char::{self, CharStrategy, CharValueTree};
+use collection::{self, SizeRange, VecStrategy, VecValueTree};
+use num::ShrinkTo;
+use strategy::*;
+use test_runner::*;
+
+/// The number of code units `os_string()` and `os_string_ascii()` generate.
+const OS_STRING_LEN: Range<usize> = 0..32;
+
+/// The platform-specific code unit making up an `OsString`.
+#[cfg(unix)]
+type Unit = u8;
+#[cfg(unix)]
+use num::u8 as unit;
+
+#[cfg(windows)]
+type Unit = u16;
+#[cfg(windows)]
+use num::u16 as unit;
+
+#[cfg(unix)]
+fn from_units(units: Vec<Unit>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(units)
+}
+
+#[cfg(windows)]
+fn from_units(units: Vec<Unit>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(&units)
+}
+
+// Not using `mapfn!` since it can't apply `cfg` to the `impl`.
+#[cfg(any(unix, windows))]
+#[derive(Clone, Copy, Debug)]
+struct UnitsToOsString;
+#[cfg(any(unix, windows))]
+impl statics::MapFn<Vec<Unit>> for UnitsToOsString {
+    type Output = OsString;
+    fn apply(&self, units: Vec<Unit>) -> OsString {
+        from_units(units)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create arbitrary, not necessarily valid Unicode,
+    /// `OsString`s.
+    ///
+    /// Created by the `os_string()` function in the same module.
+    #[cfg(any(unix, windows))]
+    #[derive(Clone, Debug)]
+    pub struct OsStringStrategy[][]
+        (statics::Map<VecStrategy<unit::Any>, UnitsToOsString>)
+        -> OsStringValueTree;
+    /// `ValueTree` corresponding to `OsStringStrategy`.
+    #[cfg(any(unix, windows))]
+    #[derive(Clone, Debug)]
+    pub struct OsStringValueTree[][]
+        (statics::Map<VecValueTree<unit::BinarySearch>, UnitsToOsString>)
+        -> OsString;
+}
+
+/// Create a strategy to generate arbitrary `OsString`s of up to 31 code
+/// units.
+///
+/// On Unix, the code units are arbitrary bytes, so the strings need not be
+/// valid UTF-8. On Windows, they are arbitrary 16-bit units, so the strings
+/// need not be valid UTF-16 (i.e., they are WTF-16).
+///
+/// This is only available on Unix and Windows, since other platforms do not
+/// allow constructing `OsString`s which are not valid Unicode.
+#[cfg(any(unix, windows))]
+pub fn os_string() -> OsStringStrategy {
+    OsStringStrategy(statics::Map::new(
+        collection::vec(unit::ANY, OS_STRING_LEN), UnitsToOsString))
+}
+
+mapfn! {
+    [] fn CharsToOsString[](chars: Vec<char>) -> OsString {
+        chars.into_iter().collect::<String>().into()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `OsString`s of printable ASCII characters.
+    ///
+    /// Created by the `os_string_ascii()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct OsStringAsciiStrategy[][]
+        (statics::Map<VecStrategy<CharStrategy<'static>>, CharsToOsString>)
+        -> OsStringAsciiValueTree;
+    /// `ValueTree` corresponding to `OsStringAsciiStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct OsStringAsciiValueTree[][]
+        (statics::Map<VecValueTree<CharValueTree>, CharsToOsString>)
+        -> OsString;
+}
+
+/// Create a strategy to generate `OsString`s of up to 31 printable ASCII
+/// characters (space through `~`).
+///
+/// This is useful for tests which deal with `OsString`s but are not
+/// specifically concerned with handling strings that are not valid Unicode.
+pub fn os_string_ascii() -> OsStringAsciiStrategy {
+    OsStringAsciiStrategy(statics::Map::new(
+        collection::vec(char::range(' ', '~'), OS_STRING_LEN),
+        CharsToOsString))
+}
+
+mapfn! {
+    [] fn BytesToCString[](bytes: Vec<u8>) -> CString {
+        CString::new(bytes.into_iter().filter(|&b| 0 != b).collect::<Vec<_>>())
+            .expect("CString has interior null after removing nulls")
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `CString`s from the bytes generated by another
+    /// strategy.
+    ///
+    /// Created by the `cstring()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct CStringStrategy[<S>][where S : Strategy,
+                                    S::Value : ValueTree<Value = Vec<u8>>]
+        (statics::Map<S, BytesToCString>)
+        -> CStringValueTree<S::Value>;
+    /// `ValueTree` corresponding to `CStringStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct CStringValueTree[<T>][where T : ValueTree<Value = Vec<u8>>]
+        (statics::Map<T, BytesToCString>)
+        -> CString;
+}
+
+/// Create a strategy to generate `CString`s from the bytes generated by
+/// `inner`, with any null bytes removed.
+///
+/// Values shrink as `inner` does. Note that removing null bytes makes the
+/// resulting strings shorter than the `Vec`s generated by `inner`.
+///
+/// ```
+/// use proptest::{collection, num};
+/// use proptest::ffi::cstring;
+///
+/// let input = cstring(collection::vec(num::u8::ANY, 0..16));
+/// # let _ = input;
+/// ```
+pub fn cstring<S>(inner: S) -> CStringStrategy<S>
+where S : Strategy, S::Value : ValueTree<Value = Vec<u8>> {
+    CStringStrategy(statics::Map::new(inner, BytesToCString))
+}
+
+/// Strategy for the printable ASCII bytes in `cstring_from_str()`, which
+/// shrink towards `b'a'`.
+#[derive(Clone, Copy, Debug)]
+struct PrintableByte;
+
+impl Strategy for PrintableByte {
+    type Value = ShrinkTo<u8>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(ShrinkTo::new((b' '..b'~' + 1).new_value(runner)?, b'a'))
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `CString`s of printable ASCII characters.
+    ///
+    /// Created by the `cstring_from_str()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct CStringFromStrStrategy[][]
+        (statics::Map<VecStrategy<PrintableByte>, BytesToCString>)
+        -> CStringFromStrValueTree;
+    /// `ValueTree` corresponding to `CStringFromStrStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct CStringFromStrValueTree[][]
+        (statics::Map<VecValueTree<ShrinkTo<u8>>, BytesToCString>)
+        -> CString;
+}
+
+/// Create a strategy to generate `CString`s of printable ASCII characters
+/// (space through `~`) with a length given by `length`.
+///
+/// Characters shrink towards `a` rather than towards space, since spaces
+/// rarely make for readable minimal inputs.
+pub fn cstring_from_str(length: impl Into<SizeRange>)
+                        -> CStringFromStrStrategy {
+    CStringFromStrStrategy(statics::Map::new(
+        collection::vec(PrintableByte, length), BytesToCString))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn os_string_produces_non_unicode() {
+        let mut runner = TestRunner::default();
+        let mut saw_non_unicode = false;
+        for _ in 0..256 {
+            let s = os_string().new_value(&mut runner).unwrap().current();
+            saw_non_unicode |= s.to_str().is_none();
+        }
+
+        assert!(saw_non_unicode);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn os_string_shrinks_length_first() {
+        for _ in 0..64 {
+            let mut runner = TestRunner::default();
+            let case = os_string().new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |s| {
+                prop_assert!(s.len() < 3);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, s)) =>
+                    assert_eq!(from_units(vec![0; 3]), s),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn os_string_ascii_is_printable_ascii() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let s = os_string_ascii().new_value(&mut runner).unwrap()
+                .current();
+            let s = s.to_str().expect("Not Unicode");
+            assert!(s.len() < 32);
+            assert!(s.chars().all(|c| c >= ' ' && c <= '~'),
+                    "Bad string: {:?}", s);
+        }
+    }
+
+    #[test]
+    fn cstring_removes_nulls() {
+        let input = cstring(collection::vec(0u8..4, 0..16));
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let s = input.new_value(&mut runner).unwrap().current();
+            assert!(s.as_bytes().len() < 16);
+            assert!(!s.as_bytes().contains(&0));
+        }
+    }
+
+    #[test]
+    fn cstring_from_str_shrinks_towards_a() {
+        for _ in 0..64 {
+            let mut runner = TestRunner::default();
+            let case = cstring_from_str(0..10).new_value(&mut runner)
+                .unwrap();
+            assert!(case.current().as_bytes().iter()
+                    .all(|&b| b >= b' ' && b <= b'~'));
+            let result = runner.run_one(case, |s| {
+                prop_assert!(s.as_bytes().len() < 3);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, s)) =>
+                    assert_eq!(CString::new("aaa").unwrap(), s),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sanity() {
+        #[cfg(any(unix, windows))]
+        check_strategy_sanity(os_string(), None);
+        check_strategy_sanity(os_string_ascii(), None);
+        check_strategy_sanity(cstring_from_str(0..10), None);
+    }
+}
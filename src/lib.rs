@@ -1081,8 +1081,9 @@
 //! generated function takes the first parameter list as arguments. These
 //! arguments are used to select the strategies in the second argument list.
 //! Values are then drawn from those strategies and transformed by the function
-//! body. The actual function as a return type of `BoxedStrategy<T>` where `T`
-//! is the declared return type.
+//! body. The actual function has a return type of
+//! `impl Strategy<Value = impl ValueTree<Value = T>>` where `T` is the declared
+//! return type.
 //!
 //! ### Filtering
 //!
@@ -1436,6 +1437,16 @@ extern crate bit_set;
 #[macro_use] extern crate quick_error;
 extern crate rand;
 extern crate regex_syntax;
+#[cfg(feature = "url")] extern crate url;
+#[cfg(feature = "uuid")] extern crate uuid;
+#[cfg(feature = "chrono")] extern crate chrono;
+#[cfg(feature = "bytes")] extern crate bytes;
+#[cfg(feature = "semver")] extern crate semver;
+#[cfg(feature = "smallvec")] extern crate smallvec;
+#[cfg(feature = "rayon")] extern crate rayon;
+#[cfg(feature = "indexmap")] extern crate indexmap;
+#[cfg(all(feature = "fork", unix))] extern crate libc;
+#[cfg(feature = "unicode-normalization")] extern crate unicode_normalization;
 
 #[cfg(test)] extern crate regex;
 
@@ -1470,6 +1481,14 @@ macro_rules! delegate_vt_0 {
         fn complicate(&mut self) -> bool {
             self.0.complicate()
         }
+
+        fn current_complexity(&self) -> u64 {
+            self.0.current_complexity()
+        }
+
+        fn is_terminal(&self) -> bool {
+            self.0.is_terminal()
+        }
     }
 }
 
@@ -1507,6 +1526,7 @@ macro_rules! opaque_strategy_wrapper {
 
 pub mod test_runner;
 pub mod strategy;
+pub mod arbitrary;
 pub mod bool;
 pub mod num;
 pub mod bits;
@@ -1517,6 +1537,25 @@ pub mod char;
 pub mod string;
 pub mod option;
 pub mod result;
+pub mod bound;
 pub mod sample;
+pub mod net;
+pub mod time;
+pub mod path;
+pub mod unicode;
+pub mod ffi;
+pub mod corpus;
+pub mod cow;
+pub mod smart_ptr;
+#[cfg(feature = "url")]
+pub mod url_strategy;
+#[cfg(feature = "uuid")]
+pub mod uuid_strategy;
+#[cfg(feature = "chrono")]
+pub mod chrono_strategy;
+#[cfg(feature = "bytes")]
+pub mod bytes_strategy;
+#[cfg(feature = "semver")]
+pub mod semver_strategy;
 
 pub mod prelude;
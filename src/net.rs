@@ -0,0 +1,360 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `std::net` addresses.
+//!
+//! Addresses shrink towards the unspecified address (`0.0.0.0` or `::`), and
+//! ports shrink towards 0. Where both IPv4 and IPv6 are possible, IPv6
+//! addresses shrink to IPv4 ones.
+
+use std::net::*;
+
+use rand::Rng;
+
+use array::{ArrayValueTree, UniformArrayStrategy};
+use strategy::*;
+use tuple::TupleValueTree;
+use test_runner::*;
+
+/// Number of IPv4 addresses outside the loopback and multicast ranges.
+const UNRESERVED_IPV4_COUNT: u64 = (1 << 32) - (1 << 24) - (1 << 28);
+
+/// Strategy to create `Ipv4Addr`s.
+///
+/// Created by the `ipv4_addr()` and `ipv4_addr_any()` functions in the same
+/// module.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv4AddrStrategy {
+    skip_reserved: bool,
+}
+
+/// `ValueTree` corresponding to `Ipv4AddrStrategy`.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv4AddrValueTree {
+    index: ::num::u32::BinarySearch,
+    skip_reserved: bool,
+}
+
+impl Strategy for Ipv4AddrStrategy {
+    type Value = Ipv4AddrValueTree;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        // When skipping reserved ranges, we generate an index into the
+        // unreserved addresses rather than rejecting, so that shrinking can
+        // binary search the index without ever landing in a reserved range.
+        let index = if self.skip_reserved {
+            runner.rng().gen_range(0, UNRESERVED_IPV4_COUNT) as u32
+        } else {
+            runner.rng().gen()
+        };
+
+        Ok(Ipv4AddrValueTree {
+            index: ::num::u32::BinarySearch::new(index),
+            skip_reserved: self.skip_reserved,
+        })
+    }
+}
+
+impl ValueTree for Ipv4AddrValueTree {
+    type Value = Ipv4Addr;
+
+    fn current(&self) -> Ipv4Addr {
+        let mut bits = self.index.current();
+        if self.skip_reserved {
+            // Skip over 127.0.0.0/8, then 224.0.0.0/4.
+            if bits >= 127 << 24 {
+                bits += 1 << 24;
+            }
+            if bits >= 224 << 24 {
+                bits += 1 << 28;
+            }
+        }
+
+        bits.into()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.index.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.index.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.index.current_complexity()
+    }
+}
+
+/// Create a strategy to generate `Ipv4Addr`s outside the loopback
+/// (`127.0.0.0/8`) and multicast (`224.0.0.0/4`) ranges.
+pub fn ipv4_addr() -> Ipv4AddrStrategy {
+    Ipv4AddrStrategy { skip_reserved: true }
+}
+
+/// Create a strategy to generate completely arbitrary `Ipv4Addr`s.
+pub fn ipv4_addr_any() -> Ipv4AddrStrategy {
+    Ipv4AddrStrategy { skip_reserved: false }
+}
+
+mapfn! {
+    [] fn SegmentsToIpv6[](segments: [u16; 8]) -> Ipv6Addr {
+        segments.into()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `Ipv6Addr`s.
+    ///
+    /// Created by the `ipv6_addr()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct Ipv6AddrStrategy[][](
+        statics::Map<UniformArrayStrategy<::num::u16::Any, [u16; 8]>,
+                     SegmentsToIpv6>)
+        -> Ipv6AddrValueTree;
+    /// `ValueTree` corresponding to `Ipv6AddrStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct Ipv6AddrValueTree[][](
+        statics::Map<ArrayValueTree<[::num::u16::BinarySearch; 8]>,
+                     SegmentsToIpv6>)
+        -> Ipv6Addr;
+}
+
+/// Create a strategy to generate completely arbitrary `Ipv6Addr`s.
+///
+/// Each of the eight segments shrinks independently, from the first segment
+/// to the last.
+pub fn ipv6_addr() -> Ipv6AddrStrategy {
+    Ipv6AddrStrategy(statics::Map::new(
+        ::array::uniform8(::num::u16::ANY), SegmentsToIpv6))
+}
+
+mapfn! {
+    [] fn V4ToIp[](addr: Ipv4Addr) -> IpAddr {
+        IpAddr::V4(addr)
+    }
+}
+
+mapfn! {
+    [] fn V6ToIp[](addr: Ipv6Addr) -> IpAddr {
+        IpAddr::V6(addr)
+    }
+}
+
+type IpAddrOptions = (W<statics::Map<Ipv4AddrStrategy, V4ToIp>>,
+                     W<statics::Map<Ipv6AddrStrategy, V6ToIp>>);
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `IpAddr`s.
+    ///
+    /// Created by the `ip_addr()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct IpAddrStrategy[][](
+        TupleUnion<IpAddrOptions>)
+        -> IpAddrValueTree;
+    /// `ValueTree` corresponding to `IpAddrStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct IpAddrValueTree[][](
+        TupleUnionValueTree<(
+            LazyValueTree<IpAddrOptions,
+                          statics::Map<Ipv4AddrStrategy, V4ToIp>>,
+            Option<LazyValueTree<IpAddrOptions,
+                                 statics::Map<Ipv6AddrStrategy, V6ToIp>>>)>)
+        -> IpAddr;
+}
+
+/// Create a strategy to generate `IpAddr`s, choosing IPv4 and IPv6 with equal
+/// probability.
+///
+/// IPv4 addresses are drawn from `ipv4_addr()` and IPv6 addresses from
+/// `ipv6_addr()`.
+pub fn ip_addr() -> IpAddrStrategy {
+    IpAddrStrategy(TupleUnion::new((
+        (1, statics::Map::new(ipv4_addr(), V4ToIp)),
+        (1, statics::Map::new(ipv6_addr(), V6ToIp)),
+    )))
+}
+
+mapfn! {
+    [] fn PartsToSocketV4[](parts: (Ipv4Addr, u16)) -> SocketAddrV4 {
+        SocketAddrV4::new(parts.0, parts.1)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `SocketAddrV4`s.
+    ///
+    /// Created by the `socket_addr_v4()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrV4Strategy[][](
+        statics::Map<(Ipv4AddrStrategy, ::num::u16::Any), PartsToSocketV4>)
+        -> SocketAddrV4ValueTree;
+    /// `ValueTree` corresponding to `SocketAddrV4Strategy`.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrV4ValueTree[][](
+        statics::Map<TupleValueTree<(Ipv4AddrValueTree,
+                                     ::num::u16::BinarySearch)>,
+                     PartsToSocketV4>)
+        -> SocketAddrV4;
+}
+
+/// Create a strategy to generate `SocketAddrV4`s with an address drawn from
+/// `ipv4_addr()` and an arbitrary port.
+pub fn socket_addr_v4() -> SocketAddrV4Strategy {
+    SocketAddrV4Strategy(statics::Map::new(
+        (ipv4_addr(), ::num::u16::ANY), PartsToSocketV4))
+}
+
+mapfn! {
+    [] fn PartsToSocketV6[](parts: (Ipv6Addr, u16)) -> SocketAddrV6 {
+        SocketAddrV6::new(parts.0, parts.1, 0, 0)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `SocketAddrV6`s.
+    ///
+    /// Created by the `socket_addr_v6()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrV6Strategy[][](
+        statics::Map<(Ipv6AddrStrategy, ::num::u16::Any), PartsToSocketV6>)
+        -> SocketAddrV6ValueTree;
+    /// `ValueTree` corresponding to `SocketAddrV6Strategy`.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrV6ValueTree[][](
+        statics::Map<TupleValueTree<(Ipv6AddrValueTree,
+                                     ::num::u16::BinarySearch)>,
+                     PartsToSocketV6>)
+        -> SocketAddrV6;
+}
+
+/// Create a strategy to generate `SocketAddrV6`s with an address drawn from
+/// `ipv6_addr()` and an arbitrary port.
+///
+/// The flow information and scope ID are always 0.
+pub fn socket_addr_v6() -> SocketAddrV6Strategy {
+    SocketAddrV6Strategy(statics::Map::new(
+        (ipv6_addr(), ::num::u16::ANY), PartsToSocketV6))
+}
+
+mapfn! {
+    [] fn V4ToSocket[](addr: SocketAddrV4) -> SocketAddr {
+        SocketAddr::V4(addr)
+    }
+}
+
+mapfn! {
+    [] fn V6ToSocket[](addr: SocketAddrV6) -> SocketAddr {
+        SocketAddr::V6(addr)
+    }
+}
+
+type SocketAddrOptions = (
+    W<statics::Map<SocketAddrV4Strategy, V4ToSocket>>,
+    W<statics::Map<SocketAddrV6Strategy, V6ToSocket>>);
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `SocketAddr`s.
+    ///
+    /// Created by the `socket_addr()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrStrategy[][](
+        TupleUnion<SocketAddrOptions>)
+        -> SocketAddrValueTree;
+    /// `ValueTree` corresponding to `SocketAddrStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct SocketAddrValueTree[][](
+        TupleUnionValueTree<(
+            LazyValueTree<SocketAddrOptions,
+                          statics::Map<SocketAddrV4Strategy, V4ToSocket>>,
+            Option<LazyValueTree<
+                SocketAddrOptions,
+                statics::Map<SocketAddrV6Strategy, V6ToSocket>>>)>)
+        -> SocketAddr;
+}
+
+/// Create a strategy to generate `SocketAddr`s, choosing IPv4 and IPv6 with
+/// equal probability.
+pub fn socket_addr() -> SocketAddrStrategy {
+    SocketAddrStrategy(TupleUnion::new((
+        (1, statics::Map::new(socket_addr_v4(), V4ToSocket)),
+        (1, statics::Map::new(socket_addr_v6(), V6ToSocket)),
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ipv4_addr_avoids_reserved_ranges() {
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            let mut case = ipv4_addr().new_value(&mut runner).unwrap();
+            loop {
+                let addr = case.current();
+                assert!(!addr.is_loopback() && !addr.is_multicast(),
+                        "Generated reserved address {}", addr);
+                if !case.simplify() { break; }
+            }
+
+            assert_eq!(Ipv4Addr::new(0, 0, 0, 0), case.current());
+        }
+    }
+
+    #[test]
+    fn ipv4_addr_covers_unreserved_extremes() {
+        let tree = |index, skip_reserved| Ipv4AddrValueTree {
+            index: ::num::u32::BinarySearch::new(index),
+            skip_reserved,
+        }.current();
+
+        assert_eq!(Ipv4Addr::new(126, 255, 255, 255),
+                   tree((127 << 24) - 1, true));
+        assert_eq!(Ipv4Addr::new(128, 0, 0, 0), tree(127 << 24, true));
+        assert_eq!(Ipv4Addr::new(223, 255, 255, 255),
+                   tree((223 << 24) - 1, true));
+        assert_eq!(Ipv4Addr::new(240, 0, 0, 0), tree(223 << 24, true));
+        assert_eq!(Ipv4Addr::new(255, 255, 255, 255),
+                   tree((UNRESERVED_IPV4_COUNT - 1) as u32, true));
+        assert_eq!(Ipv4Addr::new(127, 0, 0, 1), tree(0x7F000001, false));
+    }
+
+    #[test]
+    fn ip_addr_generates_both_families() {
+        let mut runner = TestRunner::default();
+        let mut v4 = 0;
+        for _ in 0..1000 {
+            if ip_addr().new_value(&mut runner).unwrap().current().is_ipv4() {
+                v4 += 1;
+            }
+        }
+
+        assert!(v4 > 400 && v4 < 600, "Got {} IPv4 addresses", v4);
+    }
+
+    #[test]
+    fn socket_addr_shrinks_to_unspecified() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = socket_addr().new_value(&mut runner).unwrap();
+            while case.simplify() { }
+            assert_eq!("0.0.0.0:0".parse::<SocketAddr>().unwrap(),
+                       case.current());
+        }
+    }
+
+    #[test]
+    fn test_sanity() {
+        check_strategy_sanity(ipv4_addr(), None);
+        check_strategy_sanity(ipv6_addr(), None);
+        check_strategy_sanity(ip_addr(), None);
+        check_strategy_sanity(socket_addr(), None);
+    }
+}
@@ -11,6 +11,17 @@
 //! fields).
 //!
 //! All strategies in this module shrink by binary searching towards 0.
+//! `ShrinkTo` can be used to binary search towards another value instead.
+
+use std::fmt;
+
+use strategy::ValueTree;
+
+/// Convert the magnitude of a value into a `current_complexity()` estimate,
+/// saturating at `u64::MAX`.
+fn magnitude_complexity(magnitude: u128) -> u64 {
+    if magnitude > u128::from(!0u64) { !0 } else { magnitude as u64 }
+}
 
 macro_rules! int_any {
     () => {
@@ -31,6 +42,57 @@ macro_rules! int_any {
     }
 }
 
+macro_rules! int_non_zero {
+    ($nz:ident) => {
+        /// Type of the `NON_ZERO` constant.
+        #[derive(Clone, Copy, Debug)]
+        pub struct NonZeroAny(());
+        /// Generates `std::num::NonZero*` integers, uniformly distributed over
+        /// the whole range except zero.
+        ///
+        /// Values shrink towards 1 or -1, never through zero.
+        pub const NON_ZERO: NonZeroAny = NonZeroAny(());
+
+        impl Strategy for NonZeroAny {
+            type Value = NonZeroBinarySearch;
+
+            fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+                loop {
+                    let start = runner.rng().gen();
+                    if 0 != start {
+                        return Ok(NonZeroBinarySearch(
+                            BinarySearch::new_clamped(1, start, 0)));
+                    }
+                }
+            }
+        }
+
+        /// `ValueTree` corresponding to `NON_ZERO`.
+        #[derive(Clone, Copy, Debug)]
+        pub struct NonZeroBinarySearch(BinarySearch);
+
+        impl ValueTree for NonZeroBinarySearch {
+            type Value = ::std::num::$nz;
+
+            fn current(&self) -> ::std::num::$nz {
+                ::std::num::$nz::new(self.0.current())
+                    .expect("NonZeroBinarySearch reached zero")
+            }
+
+            fn simplify(&mut self) -> bool {
+                self.0.simplify()
+            }
+
+            fn complicate(&mut self) -> bool {
+                self.0.complicate()
+            }
+            fn current_complexity(&self) -> u64 {
+                self.0.current_complexity()
+            }
+        }
+    }
+}
+
 macro_rules! numeric_api {
     ($typ:ident, $epsilon:expr) => {
         impl Strategy for Range<$typ> {
@@ -74,7 +136,7 @@ macro_rules! numeric_api {
 }
 
 macro_rules! signed_integer_bin_search {
-    ($typ:ident) => {
+    ($typ:ident, $nz:ident) => {
         #[allow(missing_docs)]
         pub mod $typ {
             use std::ops::{Range, RangeFrom, RangeTo};
@@ -86,6 +148,7 @@ macro_rules! signed_integer_bin_search {
             use test_runner::TestRunner;
 
             int_any!();
+            int_non_zero!($nz);
 
             /// Shrinks an integer towards 0, using binary search to find
             /// boundary points.
@@ -171,6 +234,11 @@ macro_rules! signed_integer_bin_search {
 
                     self.reposition()
                 }
+
+                fn current_complexity(&self) -> u64 {
+                    ::num::magnitude_complexity(
+                        (self.curr as i128).wrapping_abs() as u128)
+                }
             }
 
             numeric_api!($typ, 1);
@@ -179,7 +247,7 @@ macro_rules! signed_integer_bin_search {
 }
 
 macro_rules! unsigned_integer_bin_search {
-    ($typ:ident) => {
+    ($typ:ident, $nz:ident) => {
         #[allow(missing_docs)]
         pub mod $typ {
             use std::ops::{Range, RangeFrom, RangeTo};
@@ -191,6 +259,7 @@ macro_rules! unsigned_integer_bin_search {
             use test_runner::TestRunner;
 
             int_any!();
+            int_non_zero!($nz);
 
             /// Shrinks an integer towards 0, using binary search to find
             /// boundary points.
@@ -258,6 +327,10 @@ macro_rules! unsigned_integer_bin_search {
                     self.lo = self.curr + 1;
                     self.reposition()
                 }
+
+                fn current_complexity(&self) -> u64 {
+                    ::num::magnitude_complexity(self.curr as u128)
+                }
             }
 
             numeric_api!($typ, 1);
@@ -265,21 +338,21 @@ macro_rules! unsigned_integer_bin_search {
     }
 }
 
-signed_integer_bin_search!(i8);
-signed_integer_bin_search!(i16);
-signed_integer_bin_search!(i32);
-signed_integer_bin_search!(i64);
-signed_integer_bin_search!(isize);
-unsigned_integer_bin_search!(u8);
-unsigned_integer_bin_search!(u16);
-unsigned_integer_bin_search!(u32);
-unsigned_integer_bin_search!(u64);
-unsigned_integer_bin_search!(usize);
+signed_integer_bin_search!(i8, NonZeroI8);
+signed_integer_bin_search!(i16, NonZeroI16);
+signed_integer_bin_search!(i32, NonZeroI32);
+signed_integer_bin_search!(i64, NonZeroI64);
+signed_integer_bin_search!(isize, NonZeroIsize);
+unsigned_integer_bin_search!(u8, NonZeroU8);
+unsigned_integer_bin_search!(u16, NonZeroU16);
+unsigned_integer_bin_search!(u32, NonZeroU32);
+unsigned_integer_bin_search!(u64, NonZeroU64);
+unsigned_integer_bin_search!(usize, NonZeroUsize);
 
 #[cfg(feature = "unstable")]
-signed_integer_bin_search!(i128);
+signed_integer_bin_search!(i128, NonZeroI128);
 #[cfg(feature = "unstable")]
-unsigned_integer_bin_search!(u128);
+unsigned_integer_bin_search!(u128, NonZeroU128);
 
 bitflags! {
     pub(crate) struct FloatTypes: u32 {
@@ -717,6 +790,15 @@ macro_rules! float_bin_search {
                         false
                     }
                 }
+
+                fn current_complexity(&self) -> u64 {
+                    let magnitude = self.curr.abs();
+                    if magnitude.is_nan() || magnitude >= 1.8e19 {
+                        !0
+                    } else {
+                        magnitude as u64
+                    }
+                }
             }
 
             numeric_api!($typ, 0.0);
@@ -727,6 +809,138 @@ macro_rules! float_bin_search {
 float_bin_search!(f32);
 float_bin_search!(f64);
 
+/// Integer types which `ShrinkTo` can shrink towards an arbitrary target.
+pub trait ShrinkTarget : Copy + Eq + fmt::Debug {
+    /// Return the value halfway from `self` to `other`, rounded towards
+    /// `self`.
+    fn halfway_to(self, other: Self) -> Self;
+    /// Return the value one step from `self` towards `other`, which must
+    /// differ from `self`.
+    fn step_to(self, other: Self) -> Self;
+    /// Return the absolute difference between `self` and `other`.
+    fn distance_to(self, other: Self) -> u128;
+}
+
+macro_rules! shrink_target {
+    ($($typ:ident => $unsigned:ident),*) => { $(
+        impl ShrinkTarget for $typ {
+            fn halfway_to(self, other: $typ) -> $typ {
+                // The difference always fits in the unsigned type of the
+                // same width, even if it would overflow `$typ`.
+                let (from, to) = (self as $unsigned, other as $unsigned);
+                if self <= other {
+                    from.wrapping_add(to.wrapping_sub(from) / 2) as $typ
+                } else {
+                    from.wrapping_sub(from.wrapping_sub(to) / 2) as $typ
+                }
+            }
+
+            fn step_to(self, other: $typ) -> $typ {
+                if self < other { self + 1 } else { self - 1 }
+            }
+
+            fn distance_to(self, other: $typ) -> u128 {
+                let (from, to) = (self as $unsigned, other as $unsigned);
+                if self <= other {
+                    to.wrapping_sub(from) as u128
+                } else {
+                    from.wrapping_sub(to) as u128
+                }
+            }
+        }
+    )* }
+}
+
+shrink_target!(i8 => u8, i16 => u16, i32 => u32, i64 => u64,
+               isize => usize, u8 => u8, u16 => u16, u32 => u32,
+               u64 => u64, usize => usize);
+#[cfg(feature = "unstable")]
+shrink_target!(i128 => u128, u128 => u128);
+
+/// Shrinks an integer towards an arbitrary target value, using binary search
+/// to find boundary points.
+///
+/// This is useful when a failure is known to lie near some value, such as
+/// `1_000_000`: the search then finds the failing value closest to the
+/// target instead of spending its steps walking down towards 0.
+///
+/// ```
+/// use proptest::num::{self, ShrinkTo};
+/// use proptest::test_runner::{TestError, TestRunner};
+///
+/// let case = ShrinkTo::new(num::i32::BinarySearch::new(1_000_500),
+///                          1_000_000);
+/// let result = TestRunner::default().run_one(case, |&x| {
+///     assert!(x < 1_000_123);
+///     Ok(())
+/// });
+///
+/// match result {
+///     Err(TestError::Fail(_, x)) => assert_eq!(1_000_123, x),
+///     _ => panic!("Unexpected result: {:?}", result),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ShrinkTo<T> {
+    target: T,
+    lo: T,
+    curr: T,
+    hi: T,
+}
+
+impl<T : ShrinkTarget> ShrinkTo<T> {
+    /// Creates a binary searcher starting at the current value of `inner`
+    /// and shrinking towards `target` instead of towards 0.
+    ///
+    /// Only the current value of `inner` is used; it does not shrink any
+    /// further itself.
+    pub fn new<V : ValueTree<Value = T>>(inner: V, target: T) -> Self {
+        let start = inner.current();
+        ShrinkTo { target, lo: target, curr: start, hi: start }
+    }
+
+    fn reposition(&mut self) -> bool {
+        let new_mid = self.lo.halfway_to(self.hi);
+
+        if new_mid == self.curr {
+            false
+        } else {
+            self.curr = new_mid;
+            true
+        }
+    }
+}
+
+impl<T : ShrinkTarget> ValueTree for ShrinkTo<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        self.curr
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.hi == self.lo { return false; }
+
+        self.hi = self.curr;
+        self.reposition()
+    }
+
+    fn complicate(&mut self) -> bool {
+        if self.hi == self.lo || self.curr == self.hi { return false; }
+
+        self.lo = self.curr.step_to(self.hi);
+        self.reposition()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        magnitude_complexity(self.curr.distance_to(self.target))
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.curr == self.lo
+    }
+}
+
 #[cfg(test)]
 mod test {
     use strategy::*;
@@ -734,6 +948,31 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn shrink_to_converges_on_failure_closest_to_target() {
+        fn minimal<T, F>(start: T, target: T, fail: F) -> T
+        where T : ShrinkTarget, F : Fn (T) -> bool {
+            let mut state = ShrinkTo::new(Just(start), target);
+            loop {
+                if fail(state.current()) {
+                    if state.is_terminal() { break; }
+                    assert!(state.simplify());
+                } else if !state.complicate() {
+                    break;
+                }
+            }
+            state.current()
+        }
+
+        assert_eq!(999_950, minimal(1_000_000i64, 999_000, |x| x >= 999_950));
+        assert_eq!(10, minimal(-50i32, 100, |x| x <= 10));
+        assert_eq!(42u8, minimal(42u8, 42, |_| true));
+        assert_eq!(::std::i64::MAX,
+                   minimal(::std::i64::MIN, ::std::i64::MAX, |_| true));
+        let max = ::std::u64::MAX;
+        assert_eq!(max - 3, minimal(0u64, max, |x| x <= max - 3));
+    }
+
     #[test]
     fn i8_binary_search_always_converges() {
         fn assert_converges<P : Fn (i32) -> bool>(start: i8, pass: P) {
@@ -875,6 +1114,27 @@ mod test {
         check_strategy_sanity(0i32..1i32, None);
     }
 
+    #[test]
+    fn non_zero_integers_shrink_to_one() {
+        let mut runner = TestRunner::default();
+        for _ in 0..100 {
+            let mut state = i32::NON_ZERO.new_value(&mut runner).unwrap();
+            let expected = if state.current().get() < 0 { -1 } else { 1 };
+            while state.simplify() { }
+            assert_eq!(expected, state.current().get());
+
+            let mut state = u8::NON_ZERO.new_value(&mut runner).unwrap();
+            while state.simplify() { }
+            assert_eq!(1, state.current().get());
+        }
+    }
+
+    #[test]
+    fn non_zero_integers_simplify_complicate_contract_upheld() {
+        check_strategy_sanity(i16::NON_ZERO, None);
+        check_strategy_sanity(u64::NON_ZERO, None);
+    }
+
     #[test]
     fn positive_float_simplifies_to_zero() {
         let mut runner = TestRunner::default();
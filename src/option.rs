@@ -47,6 +47,7 @@ impl<T : fmt::Debug> ValueTree for NoneStrategy<T> {
     fn current(&self) -> Option<T> { None }
     fn simplify(&mut self) -> bool { false }
     fn complicate(&mut self) -> bool { false }
+    fn is_terminal(&self) -> bool { true }
 }
 
 opaque_strategy_wrapper! {
@@ -56,8 +57,8 @@ opaque_strategy_wrapper! {
     /// Constructed by other functions in this module.
     #[derive(Clone)]
     pub struct OptionStrategy[<T>][where T : Strategy]
-        (TupleUnion<(W<NoneStrategy<ValueFor<T>>>,
-                     W<statics::Map<T, WrapSome>>)>)
+        (EagerTupleUnion<(W<NoneStrategy<ValueFor<T>>>,
+                          W<statics::Map<T, WrapSome>>)>)
         -> OptionValueTree<T::Value>;
     /// `ValueTree` type corresponding to `OptionStrategy`.
     #[derive(Clone, Debug)]
@@ -97,12 +98,30 @@ pub fn weighted<T : Strategy>(probability_of_some: f64, t: T)
                               -> OptionStrategy<T> {
     let (weight_some, weight_none) = float_to_weight(probability_of_some);
 
-    OptionStrategy(TupleUnion::new((
+    OptionStrategy(EagerTupleUnion((
         (weight_none, NoneStrategy(PhantomData)),
         (weight_some, statics::Map::new(t, WrapSome)),
     )))
 }
 
+/// Return a strategy producing nested `Option<Option<_>>` values wrapping
+/// values from the given delegate strategy.
+///
+/// The outer `Some` is chosen with a probability given by
+/// `probability_of_some`. When it is, the inner `Some` is chosen with a
+/// probability given by `probability_of_inner_some`. That is, the
+/// probabilities of `None`, `Some(None)` and `Some(Some(_))` are `1 - p`,
+/// `p * (1 - q)` and `p * q`, respectively, which always sum to 1. Both
+/// probabilities must be between 0.0 and 1.0, both exclusive.
+///
+/// `Some(Some(_))` values shrink by simplifying the inner value, then to
+/// `Some(None)`, then to `None`.
+pub fn of_weighted<T : Strategy>(t: T, probability_of_some: f64,
+                                 probability_of_inner_some: f64)
+                                 -> OptionStrategy<OptionStrategy<T>> {
+    weighted(probability_of_some, weighted(probability_of_inner_some, t))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -133,8 +152,44 @@ mod test {
         assert!(count > 50 && count < 150);
     }
 
+    #[test]
+    fn of_weighted_composes_probabilities() {
+        let input = of_weighted(Just(42i32), 0.8, 0.25);
+        let mut counts = [0; 3];
+        let mut runner = TestRunner::default();
+        for _ in 0..4000 {
+            counts[match input.new_value(&mut runner).unwrap().current() {
+                None => 0,
+                Some(None) => 1,
+                Some(Some(_)) => 2,
+            }] += 1;
+        }
+
+        // Expected 800, 2400 and 800, respectively.
+        assert!(counts[0] > 600 && counts[0] < 1000, "{:?}", counts);
+        assert!(counts[1] > 2100 && counts[1] < 2700, "{:?}", counts);
+        assert!(counts[2] > 600 && counts[2] < 1000, "{:?}", counts);
+    }
+
+    #[test]
+    fn of_weighted_shrinks_through_inner_none() {
+        let input = of_weighted(0i32..1000, 0.99, 0.99);
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let mut saw_inner_none = None == case.current() ||
+                Some(None) == case.current();
+            while case.simplify() {
+                saw_inner_none |= Some(None) == case.current();
+            }
+            assert!(saw_inner_none);
+            assert_eq!(None, case.current());
+        }
+    }
+
     #[test]
     fn test_sanity() {
         check_strategy_sanity(of(0i32..1000i32), None);
+        check_strategy_sanity(of_weighted(0i32..1000i32, 0.5, 0.5), None);
     }
 }
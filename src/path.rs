@@ -0,0 +1,277 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `std::path::PathBuf`s.
+//!
+//! Paths shrink by first removing components and then by simplifying the
+//! characters of the components that remain.
+
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use bool;
+use char::{self, CharStrategy, CharValueTree};
+use collection::{self, SizeRange, VecStrategy, VecValueTree};
+use option::{self, OptionStrategy, OptionValueTree};
+use strategy::*;
+use tuple::TupleValueTree;
+use test_runner::*;
+
+/// Characters used for the components of `unix_path()` and
+/// `windows_path()`. These are the POSIX portable filename characters.
+const PORTABLE_CHARS: &[(char, char)] = &[
+    ('-', '.'), ('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z'),
+];
+
+/// The number of components generated by `unix_path()` and `windows_path()`.
+const OS_PATH_COMPONENTS: ::std::ops::Range<usize> = 1..8;
+
+/// Create the strategy used to generate a single path component string.
+fn component<S : Strategy>(chars: S) -> VecStrategy<S>
+where S::Value : ValueTree<Value = char> {
+    collection::vec(chars, 1..16)
+}
+
+fn join(components: Vec<Vec<char>>, separator: char) -> String {
+    let mut joined = String::new();
+    for (ix, component) in components.into_iter().enumerate() {
+        if ix > 0 {
+            joined.push(separator);
+        }
+        joined.extend(component);
+    }
+    joined
+}
+
+mapfn! {
+    [] fn CharsToPath[](components: Vec<Vec<char>>) -> PathBuf {
+        components.into_iter()
+            .map(|component| component.into_iter().collect::<String>())
+            .collect()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create relative `PathBuf`s.
+    ///
+    /// Created by the `path_buf()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct PathBufStrategy[<S>][where S : Strategy,
+                                   S::Value : ValueTree<Value = char>]
+        (statics::Map<VecStrategy<VecStrategy<S>>, CharsToPath>)
+        -> PathBufValueTree<S::Value>;
+    /// `ValueTree` corresponding to `PathBufStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct PathBufValueTree[<T>][where T : ValueTree<Value = char>]
+        (statics::Map<VecValueTree<VecValueTree<T>>, CharsToPath>)
+        -> PathBuf;
+}
+
+/// Create a strategy to generate relative `PathBuf`s with a number of
+/// components given by `components`, each made of between 1 and 15
+/// characters drawn from `component_chars`.
+///
+/// Components are joined with the host's path separator. `component_chars`
+/// should therefore not produce path separators (such as `/`), or the
+/// resulting paths may have more components than requested, or be absolute.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use proptest::prelude::*;
+///
+/// let paths = prop::path::path_buf(
+///     1..4, prop::char::ranges(Cow::Borrowed(&[('a', 'z')])));
+/// # let _ = paths;
+/// ```
+pub fn path_buf<S : Strategy>(components: impl Into<SizeRange>,
+                              component_chars: S)
+                              -> PathBufStrategy<S>
+where S::Value : ValueTree<Value = char> {
+    PathBufStrategy(statics::Map::new(
+        collection::vec(component(component_chars), components),
+        CharsToPath))
+}
+
+mapfn! {
+    [] fn JoinUnixPath[](parts: (bool, Vec<Vec<char>>)) -> PathBuf {
+        let (absolute, components) = parts;
+        let joined = join(components, '/');
+        if absolute {
+            format!("/{}", joined).into()
+        } else {
+            joined.into()
+        }
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create Unix-style `PathBuf`s.
+    ///
+    /// Created by the `unix_path()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct UnixPathStrategy[][]
+        (statics::Map<(bool::Any,
+                       VecStrategy<VecStrategy<CharStrategy<'static>>>),
+                      JoinUnixPath>)
+        -> UnixPathValueTree;
+    /// `ValueTree` corresponding to `UnixPathStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct UnixPathValueTree[][]
+        (statics::Map<TupleValueTree<(
+            bool::BoolValueTree,
+            VecValueTree<VecValueTree<CharValueTree>>)>,
+                      JoinUnixPath>)
+        -> PathBuf;
+}
+
+/// Create a strategy to generate Unix-style paths, which are absolute about
+/// half the time. Absolute paths shrink to relative ones.
+///
+/// Components are separated by `/` and only use the POSIX portable filename
+/// characters (`A-Z`, `a-z`, `0-9`, `.`, `_` and `-`). The paths are built as
+/// strings, so they use Unix syntax regardless of the host platform.
+pub fn unix_path() -> UnixPathStrategy {
+    UnixPathStrategy(statics::Map::new(
+        (bool::ANY, collection::vec(
+            component(char::ranges(Cow::Borrowed(PORTABLE_CHARS))),
+            OS_PATH_COMPONENTS)),
+        JoinUnixPath))
+}
+
+mapfn! {
+    [] fn JoinWindowsPath[](parts: (Option<char>, Vec<Vec<char>>))
+                            -> PathBuf {
+        let (drive, components) = parts;
+        let joined = join(components, '\\');
+        if let Some(drive) = drive {
+            format!("{}:\\{}", drive, joined).into()
+        } else {
+            joined.into()
+        }
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create Windows-style `PathBuf`s.
+    ///
+    /// Created by the `windows_path()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct WindowsPathStrategy[][]
+        (statics::Map<(OptionStrategy<CharStrategy<'static>>,
+                       VecStrategy<VecStrategy<CharStrategy<'static>>>),
+                      JoinWindowsPath>)
+        -> WindowsPathValueTree;
+    /// `ValueTree` corresponding to `WindowsPathStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct WindowsPathValueTree[][]
+        (statics::Map<TupleValueTree<(
+            OptionValueTree<CharValueTree>,
+            VecValueTree<VecValueTree<CharValueTree>>)>,
+                      JoinWindowsPath>)
+        -> PathBuf;
+}
+
+/// Create a strategy to generate Windows-style paths, which are absolute and
+/// start with a drive letter (e.g., `C:\`) about half the time. Absolute
+/// paths shrink to relative ones.
+///
+/// Components are separated by `\` and only use the POSIX portable filename
+/// characters (`A-Z`, `a-z`, `0-9`, `.`, `_` and `-`). The paths are built as
+/// strings, so they use Windows syntax regardless of the host platform.
+pub fn windows_path() -> WindowsPathStrategy {
+    WindowsPathStrategy(statics::Map::new(
+        (option::of(char::range('A', 'Z')), collection::vec(
+            component(char::ranges(Cow::Borrowed(PORTABLE_CHARS))),
+            OS_PATH_COMPONENTS)),
+        JoinWindowsPath))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn path_buf_has_requested_components() {
+        let input = path_buf(2..5, char::range('a', 'z'));
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let path = input.new_value(&mut runner).unwrap().current();
+            assert!(path.is_relative());
+            let count = path.components().count();
+            assert!(count >= 2 && count < 5, "Bad path: {:?}", path);
+        }
+    }
+
+    #[test]
+    fn path_buf_shrinks_components_first() {
+        let input = path_buf(1..10, char::range('a', 'z'));
+        for _ in 0..64 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |path| {
+                prop_assert!(path.components().count() < 3);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, path)) =>
+                    assert_eq!(PathBuf::from("a/a/a"), path),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn unix_path_syntax() {
+        let mut runner = TestRunner::default();
+        let mut saw_absolute = false;
+        for _ in 0..256 {
+            let path = unix_path().new_value(&mut runner).unwrap().current();
+            let path = path.to_str().unwrap();
+            saw_absolute |= path.starts_with('/');
+            assert!(!path.contains("//"), "Bad path: {:?}", path);
+            assert!(path.trim_start_matches('/').split('/').all(
+                |c| !c.is_empty() && c.chars().all(
+                    |ch| ch.is_ascii_alphanumeric() || "._-".contains(ch))),
+                    "Bad path: {:?}", path);
+        }
+
+        assert!(saw_absolute);
+    }
+
+    #[test]
+    fn windows_path_syntax() {
+        let mut runner = TestRunner::default();
+        let mut saw_drive = false;
+        for _ in 0..256 {
+            let path = windows_path().new_value(&mut runner).unwrap().current();
+            let path = path.to_str().unwrap();
+            let rest = if path.len() > 3 && &path[1..3] == ":\\" {
+                saw_drive = true;
+                assert!(path.as_bytes()[0].is_ascii_uppercase());
+                &path[3..]
+            } else {
+                path
+            };
+            assert!(!rest.contains('/'), "Bad path: {:?}", path);
+            assert!(rest.split('\\').all(|c| !c.is_empty()),
+                    "Bad path: {:?}", path);
+        }
+
+        assert!(saw_drive);
+    }
+
+    #[test]
+    fn test_sanity() {
+        check_strategy_sanity(path_buf(0..5, char::range('a', 'z')), None);
+        check_strategy_sanity(unix_path(), None);
+        check_strategy_sanity(windows_path(), None);
+    }
+}
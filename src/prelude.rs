@@ -20,7 +20,9 @@
 //! `rand` crate directly will not provide insulation from the upcoming
 //! revision to the `rand` crate.
 
-pub use strategy::{BoxedStrategy, Just, SBoxedStrategy, Strategy};
+pub use arbitrary::{any, any_with, Arbitrary};
+pub use strategy::{BoxedStrategy, Const, Just, LazyJust, SBoxedStrategy,
+                   Strategy};
 pub use test_runner::Config as ProptestConfig;
 pub use test_runner::TestCaseError;
 
@@ -32,6 +34,7 @@ pub use rand::{Rng, XorShiftRng};
 pub mod prop {
     pub use test_runner;
     pub use strategy;
+    pub use arbitrary;
     pub use bool;
     pub use num;
     pub use bits;
@@ -42,5 +45,13 @@ pub mod prop {
     pub use string;
     pub use option;
     pub use result;
+    pub use bound;
     pub use sample;
+    pub use net;
+    pub use time;
+    pub use path;
+    pub use unicode;
+    pub use ffi;
+    pub use corpus;
+    pub use smart_ptr;
 }
@@ -9,10 +9,11 @@
 
 //! Strategies for combining delegate strategies into `std::Result`s.
 //!
-//! That is, the strategies here are for producing `Ok` _and_ `Err` cases. To
-//! simply adapt a strategy producing `T` into `Result<T, something>` which is
-//! always `Ok`, you can do something like `base_strategy.prop_map(Ok)` to
-//! simply wrap the generated values.
+//! That is, the strategies here are mostly for producing `Ok` _and_ `Err`
+//! cases. To simply adapt a strategy producing `T` into `Result<T, something>`
+//! which is always `Ok`, you can use `just_ok()` (or `just_err()` for the
+//! reverse), which is equivalent to `base_strategy.prop_map(Ok)` but produces
+//! a nameable type.
 //!
 //! Note that there are two nearly identical APIs for doing this, termed "maybe
 //! ok" and "maybe err". The difference between the two is in how they shrink;
@@ -79,7 +80,7 @@ opaque_strategy_wrapper! {
     /// Shrinks to `Err`.
     #[derive(Clone)]
     pub struct MaybeOk[<T, E>][where T : Strategy, E : Strategy]
-        (TupleUnion<(W<MapErr<T, E>>, W<MapOk<T, E>>)>)
+        (EagerTupleUnion<(W<MapErr<T, E>>, W<MapOk<T, E>>)>)
         -> MaybeOkValueTree<T::Value, E::Value>;
     /// `ValueTree` type corresponding to `MaybeOk`.
     #[derive(Clone, Debug)]
@@ -97,7 +98,7 @@ opaque_strategy_wrapper! {
     /// Shrinks to `Ok`.
     #[derive(Clone)]
     pub struct MaybeErr[<T, E>][where T : Strategy, E : Strategy]
-        (TupleUnion<(W<MapOk<T, E>>, W<MapErr<T, E>>)>)
+        (EagerTupleUnion<(W<MapOk<T, E>>, W<MapErr<T, E>>)>)
         -> MaybeErrValueTree<T::Value, E::Value>;
     /// `ValueTree` type corresponding to `MaybeErr`.
     #[derive(Clone, Debug)]
@@ -143,7 +144,7 @@ pub fn maybe_ok_weighted<T : Strategy, E : Strategy>(
 {
     let (ok_weight, err_weight) = float_to_weight(probability_of_ok);
 
-    MaybeOk(TupleUnion::new((
+    MaybeOk(EagerTupleUnion((
         (err_weight, statics::Map::new(e, WrapErr(PhantomData, PhantomData))),
         (ok_weight, statics::Map::new(t, WrapOk(PhantomData, PhantomData))),
     )))
@@ -171,12 +172,69 @@ pub fn maybe_err_weighted<T : Strategy, E : Strategy>(
 {
     let (err_weight, ok_weight) = float_to_weight(probability_of_err);
 
-    MaybeErr(TupleUnion::new((
+    MaybeErr(EagerTupleUnion((
         (ok_weight, statics::Map::new(t, WrapOk(PhantomData, PhantomData))),
         (err_weight, statics::Map::new(e, WrapErr(PhantomData, PhantomData))),
     )))
 }
 
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Result`s which are always `Ok`.
+    ///
+    /// Created by the `just_ok()` function in the same module.
+    #[derive(Clone)]
+    pub struct JustOk[<T, E>][where T : Strategy, E : fmt::Debug]
+        (statics::Map<T, WrapOk<ValueFor<T>, E>>)
+        -> JustOkValueTree<T::Value, E>;
+    /// `ValueTree` type corresponding to `JustOk`.
+    #[derive(Clone, Debug)]
+    pub struct JustOkValueTree[<T, E>][where T : ValueTree, E : fmt::Debug]
+        (statics::Map<T, WrapOk<T::Value, E>>)
+        -> Result<T::Value, E>;
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Result`s which are always `Err`.
+    ///
+    /// Created by the `just_err()` function in the same module.
+    #[derive(Clone)]
+    pub struct JustErr[<T, E>][where T : fmt::Debug, E : Strategy]
+        (statics::Map<E, WrapErr<T, ValueFor<E>>>)
+        -> JustErrValueTree<T, E::Value>;
+    /// `ValueTree` type corresponding to `JustErr`.
+    #[derive(Clone, Debug)]
+    pub struct JustErrValueTree[<T, E>][where T : fmt::Debug, E : ValueTree]
+        (statics::Map<E, WrapErr<T, E::Value>>)
+        -> Result<T, E::Value>;
+}
+
+impl<T : Strategy + fmt::Debug, E : fmt::Debug> fmt::Debug for JustOk<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JustOk({:?})", self.0)
+    }
+}
+impl<T : fmt::Debug, E : Strategy + fmt::Debug> fmt::Debug for JustErr<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JustErr({:?})", self.0)
+    }
+}
+
+/// Create a strategy for `Result`s which are always `Ok`, with values taken
+/// from `t`.
+///
+/// The error type `E` is usually inferred from context.
+pub fn just_ok<T : Strategy, E : fmt::Debug>(t: T) -> JustOk<T, E> {
+    JustOk(statics::Map::new(t, WrapOk(PhantomData, PhantomData)))
+}
+
+/// Create a strategy for `Result`s which are always `Err`, with values taken
+/// from `e`.
+///
+/// The success type `T` is usually inferred from context.
+pub fn just_err<T : fmt::Debug, E : Strategy>(e: E) -> JustErr<T, E> {
+    JustErr(statics::Map::new(e, WrapErr(PhantomData, PhantomData)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -251,9 +309,17 @@ mod test {
         }
     }
 
+    #[test]
+    fn just_ok_and_just_err_never_switch() {
+        assert_eq!(1000, count_ok_of_1000(just_ok(Just(()))));
+        assert_eq!(0, count_ok_of_1000(just_err(Just(()))));
+    }
+
     #[test]
     fn test_sanity() {
         check_strategy_sanity(maybe_ok(0i32..100i32, 0i32..100i32), None);
         check_strategy_sanity(maybe_err(0i32..100i32, 0i32..100i32), None);
+        check_strategy_sanity(just_ok::<_, ()>(0i32..100i32), None);
+        check_strategy_sanity(just_err::<(), _>(0i32..100i32), None);
     }
 }
@@ -19,6 +19,8 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use bit_set::BitSet;
+use rand;
+use rand::distributions::IndependentSample;
 
 use bits::{self, BitSetValueTree, SampledBitSetStrategy};
 use num;
@@ -100,6 +102,10 @@ impl<T : fmt::Debug + Clone + 'static> ValueTree for SubsequenceValueTree<T> {
     fn complicate(&mut self) -> bool {
         self.inner.complicate()
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.inner.current_complexity()
+    }
 }
 
 
@@ -146,6 +152,74 @@ where A : 'static + Into<Cow<'static, [T]>>, T : Clone + fmt::Debug + 'static {
         0..cow.len(), SelectMapFn(Arc::new(cow))))
 }
 
+/// Strategy to produce one value from a fixed collection of weighted
+/// options.
+///
+/// Created by the `weighted_select()` function in the same module.
+#[derive(Clone, Debug)]
+pub struct WeightedSelect<T : Clone + 'static> {
+    values: Arc<Cow<'static, [T]>>,
+    weights: Vec<u32>,
+}
+
+impl<T : Clone + fmt::Debug + 'static> Strategy for WeightedSelect<T> {
+    type Value = SelectValueTree<T>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let sum = self.weights.iter().sum();
+        let pick = rand::distributions::Range::new(0, sum)
+            .ind_sample(runner.rng());
+        let ix = self.weights.iter().scan(0, |state, &w| {
+            *state += w;
+            Some(*state)
+        }).filter(|&v| v <= pick).count();
+
+        Ok(SelectValueTree(statics::Map::new(
+            num::usize::BinarySearch::new(ix),
+            SelectMapFn(Arc::clone(&self.values)))))
+    }
+}
+
+/// Create a strategy which selects one value from `values`, where each value
+/// is chosen with probability proportional to the weight it is paired with.
+///
+/// Like `select()`, values shrink towards the first element of `values`,
+/// regardless of the weights.
+///
+/// ## Panics
+///
+/// Panics if `values` is empty, if any weight is 0, or if the sum of the
+/// weights overflows a `u32`.
+pub fn weighted_select<T>(values: Vec<(u32, T)>) -> WeightedSelect<T>
+where T : Clone + fmt::Debug + 'static {
+    assert!(!values.is_empty(), "No values passed to weighted_select");
+    assert!(!values.iter().any(|&(w, _)| 0 == w),
+            "weighted_select value has a weight of 0");
+    assert!(values.iter().map(|&(w, _)| u64::from(w)).sum::<u64>() <=
+            u64::from(!0u32), "weighted_select weights overflow u32");
+
+    let (weights, values): (Vec<u32>, Vec<T>) = values.into_iter().unzip();
+    WeightedSelect {
+        values: Arc::new(Cow::Owned(values)),
+        weights,
+    }
+}
+
+/// Strategy to produce permutations of a fixed collection.
+///
+/// Created by the `shuffled()` function in the same module.
+pub type Shuffled<T> = Shuffle<Just<Vec<T>>>;
+
+/// Create a strategy which generates random permutations of `items`.
+///
+/// Every generated value contains all of `items`. Values shrink by gradually
+/// restoring the original order of `items`.
+///
+/// This is shorthand for `Just(items).prop_shuffle()`.
+pub fn shuffled<T : Clone + fmt::Debug>(items: Vec<T>) -> Shuffled<T> {
+    Just(items).prop_shuffle()
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
@@ -220,6 +294,61 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_weighted_select() {
+        let input = weighted_select(vec![(1, 'a'), (3, 'b'), (1, 'c')]);
+        let mut counts = [0; 3];
+
+        let mut runner = TestRunner::default();
+        for _ in 0..4096 {
+            let value = input.new_value(&mut runner).unwrap().current();
+            counts[(value as u8 - b'a') as usize] += 1;
+        }
+
+        assert!(counts[0] > 0 && counts[2] > 0, "{:?}", counts);
+        assert!(counts[1] > counts[0] * 2, "{:?}", counts);
+        assert!(counts[1] > counts[2] * 2, "{:?}", counts);
+    }
+
+    #[test]
+    fn weighted_select_shrinks_to_first_value() {
+        let input = weighted_select(vec![(1, 0), (1, 1), (1000, 2)]);
+
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            while case.simplify() { }
+            assert_eq!(0, case.current());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn weighted_select_rejects_zero_weight() {
+        weighted_select(vec![(1, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn shuffled_permutes_and_shrinks_to_original_order() {
+        let items: Vec<u32> = (0..8).collect();
+        let input = shuffled(items.clone());
+        let mut saw_other_order = false;
+
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let mut value = case.current();
+            saw_other_order |= value != items;
+            value.sort();
+            assert_eq!(items, value);
+
+            while case.simplify() { }
+            assert_eq!(items, case.current());
+        }
+
+        assert!(saw_other_order);
+    }
+
     #[test]
     fn test_sample_sanity() {
         check_strategy_sanity(subsequence(vec![0, 1, 2, 3, 4], 1..3), None);
@@ -228,5 +357,7 @@ mod test {
     #[test]
     fn test_select_sanity() {
         check_strategy_sanity(select(vec![0, 1, 2, 3, 4]), None);
+        check_strategy_sanity(
+            weighted_select(vec![(1, 0), (2, 1), (3, 2)]), None);
     }
 }
@@ -0,0 +1,248 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `semver::Version`s and `semver::VersionReq`s.
+//!
+//! This module is only available with the `semver` feature.
+//!
+//! Version numbers are kept below 100 so that generated versions and
+//! requirements often share components, which is where comparison and
+//! resolution logic tends to go wrong.
+
+use std::ops::Range;
+
+use semver::{Identifier, Version, VersionReq};
+
+use collection::{self, VecStrategy, VecValueTree};
+use num;
+use sample::{self, Select, SelectValueTree};
+use strategy::*;
+use test_runner::*;
+use tuple::TupleValueTree;
+
+/// Bound on generated major, minor and patch numbers.
+const MAX_COMPONENT: u64 = 100;
+
+/// Strategy to create `Version`s.
+///
+/// Created by the `version()` function in the same module.
+#[derive(Debug)]
+pub struct VersionStrategy {
+    number: Range<u64>,
+    pre: SBoxedStrategy<Vec<Identifier>>,
+}
+
+/// `ValueTree` corresponding to `VersionStrategy`.
+pub struct VersionValueTree {
+    major: num::u64::BinarySearch,
+    minor: num::u64::BinarySearch,
+    patch: num::u64::BinarySearch,
+    pre: Box<ValueTree<Value = Vec<Identifier>>>,
+    /// Index, in shrinking order, of the component currently being
+    /// simplified.
+    shrinker: usize,
+    /// The component which was last simplified, if it may be complicated.
+    prev_shrinker: Option<usize>,
+}
+
+/// Number of components of a `VersionValueTree`.
+const COMPONENTS: usize = 4;
+
+impl VersionValueTree {
+    /// Simplify (or, if `simplify` is false, complicate) the component at
+    /// index `ix` in shrinking order.
+    fn shrink_component(&mut self, ix: usize, simplify: bool) -> bool {
+        fn shrink<T : ValueTree + ?Sized>(t: &mut T, simplify: bool) -> bool {
+            if simplify { t.simplify() } else { t.complicate() }
+        }
+
+        match ix {
+            0 => shrink(&mut *self.pre, simplify),
+            1 => shrink(&mut self.patch, simplify),
+            2 => shrink(&mut self.minor, simplify),
+            3 => shrink(&mut self.major, simplify),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Strategy for VersionStrategy {
+    type Value = VersionValueTree;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(VersionValueTree {
+            major: self.number.new_value(runner)?,
+            minor: self.number.new_value(runner)?,
+            patch: self.number.new_value(runner)?,
+            pre: self.pre.new_value(runner)?,
+            shrinker: 0,
+            prev_shrinker: None,
+        })
+    }
+}
+
+impl ValueTree for VersionValueTree {
+    type Value = Version;
+
+    fn current(&self) -> Version {
+        Version {
+            major: self.major.current(),
+            minor: self.minor.current(),
+            patch: self.patch.current(),
+            pre: self.pre.current(),
+            build: vec![],
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        while self.shrinker < COMPONENTS {
+            let shrinker = self.shrinker;
+            if self.shrink_component(shrinker, true) {
+                self.prev_shrinker = Some(shrinker);
+                return true;
+            }
+            self.shrinker += 1;
+        }
+
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        if let Some(shrinker) = self.prev_shrinker {
+            if self.shrink_component(shrinker, false) {
+                // Move back to this component so `simplify()` resumes with
+                // it, even if it was previously exhausted.
+                self.shrinker = shrinker;
+                return true;
+            }
+            self.prev_shrinker = None;
+        }
+
+        false
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.major.current_complexity()
+            .saturating_add(self.minor.current_complexity())
+            .saturating_add(self.patch.current_complexity())
+            .saturating_add(self.pre.current_complexity())
+    }
+}
+
+/// Create a strategy to generate `Version`s with major, minor and patch
+/// numbers below 100 and up to two pre-release identifiers, such as `3.14.0`
+/// or `1.0.2-beta.7`. No build metadata is generated.
+///
+/// Versions shrink by first removing the pre-release identifiers, then
+/// reducing the patch number to 0, then the minor number, then the major
+/// number, so the simplest version is `0.0.0`.
+pub fn version() -> VersionStrategy {
+    VersionStrategy {
+        number: 0..MAX_COMPONENT,
+        pre: collection::vec(prop_oneof![
+            (0..MAX_COMPONENT).prop_map(Identifier::Numeric),
+            "[a-z][a-z0-9-]{0,7}".prop_map(Identifier::AlphaNumeric),
+        ], 0..3).sboxed(),
+    }
+}
+
+/// Operators which may prefix a comparator in a generated `VersionReq`.
+const OPERATORS: &[&str] = &["=", ">", ">=", "<", "<=", "~", "^"];
+
+type Comparator = (&'static str, u64, u64, u64);
+
+mapfn! {
+    [] fn ComparatorsToReq[](comparators: Vec<Comparator>) -> VersionReq {
+        let req = comparators.iter()
+            .map(|&(op, major, minor, patch)| {
+                format!("{}{}.{}.{}", op, major, minor, patch)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        VersionReq::parse(&req).expect("generated invalid version requirement")
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `VersionReq`s.
+    ///
+    /// Created by the `version_req()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct VersionReqStrategy[][](
+        statics::Map<VecStrategy<(Select<&'static str>, Range<u64>,
+                                  Range<u64>, Range<u64>)>,
+                     ComparatorsToReq>)
+        -> VersionReqValueTree;
+    /// `ValueTree` corresponding to `VersionReqStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct VersionReqValueTree[][](
+        statics::Map<VecValueTree<TupleValueTree<(
+            SelectValueTree<&'static str>, num::u64::BinarySearch,
+            num::u64::BinarySearch, num::u64::BinarySearch)>>,
+                     ComparatorsToReq>)
+        -> VersionReq;
+}
+
+/// Create a strategy to generate `VersionReq`s of one or two comma-separated
+/// comparators, such as `^1.2.0` or `>=1.2.0, <2.0.0`.
+///
+/// Each comparator uses one of the operators `=`, `>`, `>=`, `<`, `<=`, `~`
+/// and `^` with a version whose numbers are below 100. Requirements shrink
+/// towards the single comparator `=0.0.0`.
+pub fn version_req() -> VersionReqStrategy {
+    let number = 0..MAX_COMPONENT;
+    VersionReqStrategy(statics::Map::new(
+        collection::vec((sample::select(OPERATORS), number.clone(),
+                         number.clone(), number), 1..3),
+        ComparatorsToReq))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn versions_shrink_pre_then_patch_then_minor_then_major() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = version().new_value(&mut runner).unwrap();
+            let initial = case.current();
+            assert!(initial.major < MAX_COMPONENT &&
+                    initial.minor < MAX_COMPONENT &&
+                    initial.patch < MAX_COMPONENT, "Bad version: {}", initial);
+            assert!(initial.build.is_empty());
+
+            while case.simplify() {
+                let v = case.current();
+                if v.patch != initial.patch {
+                    assert!(v.pre.is_empty(), "{} from {}", v, initial);
+                }
+                if v.minor != initial.minor {
+                    assert_eq!(0, v.patch, "{} from {}", v, initial);
+                }
+                if v.major != initial.major {
+                    assert_eq!(0, v.minor, "{} from {}", v, initial);
+                }
+            }
+
+            assert_eq!(Version::new(0, 0, 0), case.current());
+        }
+    }
+
+    #[test]
+    fn version_reqs_parse_and_shrink_to_exact_zero() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = version_req().new_value(&mut runner).unwrap();
+            while case.simplify() { }
+            assert_eq!(VersionReq::exact(&Version::new(0, 0, 0)),
+                       case.current());
+        }
+    }
+}
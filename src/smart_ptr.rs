@@ -0,0 +1,164 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating values wrapped in the reference-counted smart
+//! pointers `std::sync::Arc` and `std::rc::Rc`.
+//!
+//! These are equivalent to `inner.prop_map(Arc::new)` and
+//! `inner.prop_map(Rc::new)`, but name their types and need no annotations
+//! for `Arc::new` to be inferred.
+//!
+//! The wrapped type does not need to implement `Clone`: shrinking simplifies
+//! the value of the inner strategy and wraps each simplified value in a new
+//! pointer, rather than cloning the value out of the old one. Values are
+//! never shared between pointers, so every generated pointer has a strong
+//! count of 1.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use strategy::*;
+use test_runner::*;
+
+struct ToArc<T>(PhantomData<T>);
+impl<T> Clone for ToArc<T> {
+    fn clone(&self) -> Self { *self }
+}
+impl<T> Copy for ToArc<T> { }
+impl<T> fmt::Debug for ToArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToArc")
+    }
+}
+impl<T : fmt::Debug> statics::MapFn<T> for ToArc<T> {
+    type Output = Arc<T>;
+    fn apply(&self, value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+}
+
+struct ToRc<T>(PhantomData<T>);
+impl<T> Clone for ToRc<T> {
+    fn clone(&self) -> Self { *self }
+}
+impl<T> Copy for ToRc<T> { }
+impl<T> fmt::Debug for ToRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ToRc")
+    }
+}
+impl<T : fmt::Debug> statics::MapFn<T> for ToRc<T> {
+    type Output = Rc<T>;
+    fn apply(&self, value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Arc`s wrapping values generated by another
+    /// strategy.
+    ///
+    /// Created by the `arc()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct ArcStrategy[<S>][where S : Strategy](
+        statics::Map<S, ToArc<ValueFor<S>>>)
+        -> ArcValueTree<S::Value>;
+    /// `ValueTree` type corresponding to `ArcStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct ArcValueTree[<T>][where T : ValueTree](
+        statics::Map<T, ToArc<T::Value>>)
+        -> Arc<T::Value>;
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `Rc`s wrapping values generated by another
+    /// strategy.
+    ///
+    /// Created by the `rc()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct RcStrategy[<S>][where S : Strategy](
+        statics::Map<S, ToRc<ValueFor<S>>>)
+        -> RcValueTree<S::Value>;
+    /// `ValueTree` type corresponding to `RcStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct RcValueTree[<T>][where T : ValueTree](
+        statics::Map<T, ToRc<T::Value>>)
+        -> Rc<T::Value>;
+}
+
+/// Return a strategy producing `Arc`s wrapping values from the given delegate
+/// strategy.
+///
+/// Values shrink as `inner` does; the wrapped type need not be `Clone`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use proptest::smart_ptr::arc;
+/// use proptest::strategy::{Strategy, ValueTree};
+/// use proptest::test_runner::TestRunner;
+///
+/// let mut runner = TestRunner::default();
+/// let value: Arc<u32> = arc(0u32..10).new_value(&mut runner)
+///     .unwrap().current();
+/// assert!(*value < 10);
+/// ```
+pub fn arc<S : Strategy>(inner: S) -> ArcStrategy<S> {
+    ArcStrategy(statics::Map::new(inner, ToArc(PhantomData)))
+}
+
+/// Return a strategy producing `Rc`s wrapping values from the given delegate
+/// strategy.
+///
+/// Values shrink as `inner` does; the wrapped type need not be `Clone`.
+pub fn rc<S : Strategy>(inner: S) -> RcStrategy<S> {
+    RcStrategy(statics::Map::new(inner, ToRc(PhantomData)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A type which is deliberately not `Clone`.
+    #[derive(Debug, PartialEq)]
+    struct Opaque(u32);
+
+    mapfn! {
+        [] fn ToOpaque[](v: u32) -> Opaque { Opaque(v) }
+    }
+
+    #[test]
+    fn arc_shrinks_without_cloning() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = arc(statics::Map::new(0u32..100, ToOpaque))
+                .new_value(&mut runner).unwrap();
+            assert!(case.current().0 < 100);
+            while case.simplify() { }
+            let value = case.current();
+            assert_eq!(Opaque(0), *value);
+            assert_eq!(1, Arc::strong_count(&value));
+        }
+    }
+
+    #[test]
+    fn rc_shrinks_without_cloning() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = rc(statics::Map::new(0u32..100, ToOpaque))
+                .new_value(&mut runner).unwrap();
+            assert!(case.current().0 < 100);
+            while case.simplify() { }
+            let value = case.current();
+            assert_eq!(Opaque(0), *value);
+            assert_eq!(1, Rc::strong_count(&value));
+        }
+    }
+}
@@ -106,6 +106,10 @@ ValueTree for Filter<S, F> {
             false
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
 }
 
 #[cfg(test)]
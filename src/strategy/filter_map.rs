@@ -0,0 +1,184 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::sync::Arc;
+
+use strategy::traits::*;
+use test_runner::*;
+
+/// `Strategy` and `ValueTree` filter-map adaptor.
+///
+/// See `Strategy::prop_filter_map()`.
+pub struct FilterMap<S, F> {
+    pub(super) source: S,
+    pub(super) whence: Reason,
+    pub(super) fun: Arc<F>,
+}
+
+impl<S, F> FilterMap<S, F> {
+    pub (super) fn new(source: S, whence: Reason, fun: F) -> Self {
+        Self { source, whence, fun: Arc::new(fun) }
+    }
+}
+
+impl<S : fmt::Debug, F> fmt::Debug for FilterMap<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilterMap")
+            .field("source", &self.source)
+            .field("whence", &self.whence)
+            .field("fun", &"<function>")
+            .finish()
+    }
+}
+
+impl<S : Clone, F> Clone for FilterMap<S, F> {
+    fn clone(&self) -> Self {
+        FilterMap {
+            source: self.source.clone(),
+            whence: self.whence.clone(),
+            fun: Arc::clone(&self.fun),
+        }
+    }
+}
+
+impl<S : Strategy, O : fmt::Debug,
+     F : Fn (ValueFor<S>) -> Option<O>>
+Strategy for FilterMap<S, F> {
+    type Value = FilterMap<S::Value, F>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        loop {
+            let val = self.source.new_value(runner)?;
+            if (self.fun)(val.current()).is_none() {
+                runner.reject_local(self.whence.clone())?;
+            } else {
+                return Ok(FilterMap {
+                    source: val,
+                    whence: self.whence.clone(),
+                    fun: Arc::clone(&self.fun),
+                })
+            }
+        }
+    }
+}
+
+impl<S : ValueTree, O : fmt::Debug, F : Fn (S::Value) -> Option<O>>
+FilterMap<S, F> {
+    fn ensure_acceptable(&mut self) {
+        while (self.fun)(self.source.current()).is_none() {
+            if !self.source.complicate() {
+                panic!("Unable to complicate filter-mapped strategy \
+                        back into acceptable value");
+            }
+        }
+    }
+}
+
+impl<S : ValueTree, O : fmt::Debug, F : Fn (S::Value) -> Option<O>>
+ValueTree for FilterMap<S, F> {
+    type Value = O;
+
+    fn current(&self) -> O {
+        (self.fun)(self.source.current())
+            .expect("FilterMap has unacceptable current value")
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.source.simplify() {
+            self.ensure_acceptable();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn complicate(&mut self) -> bool {
+        if self.source.complicate() {
+            self.ensure_acceptable();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_map() {
+        let input = (0..256).prop_filter_map(
+            "%3", |v| if 0 == v % 3 { Some(v / 3) } else { None });
+
+        for _ in 0..256 {
+            let mut runner = TestRunner::default();
+            let mut case = input.new_value(&mut runner).unwrap();
+
+            assert!(case.current() < 86);
+            while case.simplify() {
+                assert!(case.current() < 86);
+            }
+            assert!(case.current() < 86);
+        }
+    }
+
+    #[test]
+    fn filter_map_shrinks_to_accepted_values() {
+        let input = (0u32..1000).prop_filter_map(
+            "not prime", |v| if v > 1 && (2..v).all(|d| 0 != v % d) {
+                Some(v)
+            } else {
+                None
+            });
+
+        for _ in 0..64 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |&v| {
+                prop_assert!(v < 100);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                Err(TestError::Fail(_, v)) => {
+                    assert!(v >= 100);
+                    assert!((2..v).all(|d| 0 != v % d), "Not prime: {}", v);
+                },
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn filter_map_gives_up_on_too_many_rejects() {
+        let input = (0..256).prop_filter_map("never", |_| None::<i32>);
+        let mut runner = TestRunner::default();
+        assert!(input.new_value(&mut runner).is_err());
+    }
+
+    #[test]
+    fn test_filter_map_sanity() {
+        check_strategy_sanity(
+            (0..256).prop_filter_map(
+                "!%5", |v| if 0 != v % 5 { Some(v * 2) } else { None }),
+            Some(CheckStrategySanityOptions {
+                // Due to internal rejection sampling, `simplify()` can
+                // converge back to what `complicate()` would do.
+                strict_complicate_after_simplify: false,
+                .. CheckStrategySanityOptions::default()
+            }));
+    }
+}
@@ -186,6 +186,11 @@ where S::Value : Strategy {
             false
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.meta.current_complexity()
+            .saturating_add(self.current.current_complexity())
+    }
 }
 
 /// Similar to `Flatten`, but does not shrink the input strategy.
@@ -245,10 +250,104 @@ Strategy for IndFlattenMap<S, F> {
     }
 }
 
+/// `Strategy` and `ValueTree` adaptor which derives a pair of strategies from
+/// each value of the source.
+///
+/// This is the source of the `Flatten` used by
+/// `Strategy::prop_flat_map_shared()`.
+pub struct MapShared<S, F1, F2> {
+    source: S,
+    fun1: Arc<F1>,
+    fun2: Arc<F2>,
+}
+
+/// `Strategy` returned by `Strategy::prop_flat_map_shared()`.
+pub type SharedFlatMap<S, F1, F2> = Flatten<MapShared<S, F1, F2>>;
+
+impl<S, F1, F2> MapShared<S, F1, F2> {
+    pub(super) fn new(source: S, fun1: F1, fun2: F2) -> Self {
+        MapShared { source, fun1: Arc::new(fun1), fun2: Arc::new(fun2) }
+    }
+}
+
+impl<S : fmt::Debug, F1, F2> fmt::Debug for MapShared<S, F1, F2> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapShared")
+            .field("source", &self.source)
+            .field("fun1", &"<function>")
+            .field("fun2", &"<function>")
+            .finish()
+    }
+}
+
+impl<S : Clone, F1, F2> Clone for MapShared<S, F1, F2> {
+    fn clone(&self) -> Self {
+        MapShared {
+            source: self.source.clone(),
+            fun1: Arc::clone(&self.fun1),
+            fun2: Arc::clone(&self.fun2),
+        }
+    }
+}
+
+impl<S : Strategy, R1 : Strategy, R2 : Strategy,
+     F1 : Fn (&ValueFor<S>) -> R1, F2 : Fn (&ValueFor<S>) -> R2>
+Strategy for MapShared<S, F1, F2> {
+    type Value = MapShared<S::Value, F1, F2>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.source.new_value(runner).map(|v| MapShared {
+            source: v,
+            fun1: Arc::clone(&self.fun1),
+            fun2: Arc::clone(&self.fun2),
+        })
+    }
+}
+
+impl<S : ValueTree, R1 : Strategy, R2 : Strategy,
+     F1 : Fn (&S::Value) -> R1, F2 : Fn (&S::Value) -> R2>
+ValueTree for MapShared<S, F1, F2> {
+    type Value = (R1, R2);
+
+    fn current(&self) -> (R1, R2) {
+        let base = self.source.current();
+        ((self.fun1)(&base), (self.fun2)(&base))
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.source.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.source.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn flat_map_shared_derives_both_from_one_base() {
+        let input = (1u32..1000).prop_flat_map_shared(
+            |&base| 0..base, |&base| Just(base));
+
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            loop {
+                let (below, base) = case.current();
+                assert!(below < base);
+                if !case.simplify() { break; }
+            }
+            assert_eq!((0, 1), case.current());
+        }
+    }
+
     #[test]
     fn test_flat_map() {
         // Pick random integer A, then random integer B which is ±5 of A and
@@ -289,6 +388,37 @@ mod test {
             None);
     }
 
+    #[test]
+    fn flat_map_keeps_dependent_value_valid() {
+        // Generate a vec and then an index into it. Shrinking the vec must
+        // never leave the index out of bounds, since the index strategy is
+        // regenerated from the shrunken vec.
+        let input = ::collection::vec(0u8..255, 1..100)
+            .prop_flat_map(|v| {
+                let len = v.len();
+                (Just(v), 0..len)
+            });
+
+        for _ in 0..100 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |&(ref v, ix)| {
+                prop_assert!(ix < v.len());
+                prop_assert!(v[ix] < 128);
+                Ok(())
+            });
+
+            match result {
+                Ok(_) => { },
+                Err(TestError::Fail(_, (v, ix))) => {
+                    assert!(ix < v.len());
+                    assert!(v[ix] >= 128);
+                },
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+    }
+
     #[test]
     fn flat_map_respects_regen_limit() {
         use std::sync::atomic::{AtomicBool, Ordering};
@@ -318,6 +448,28 @@ mod test {
         });
     }
 
+    #[test]
+    fn ind_flat_map2_shrinks_both_sides_independently() {
+        let input = (100u32..1000).prop_ind_flat_map2(|a| 0..a);
+
+        for _ in 0..256 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let result = runner.run_one(case, |&(_, b)| {
+                prop_assert!(b <= 10);
+                Ok(())
+            });
+
+            match result {
+                Ok(true) => (),
+                // `a` shrinks to its minimum even though `b` was derived from
+                // a larger `a`, since the two no longer depend on each other.
+                Err(TestError::Fail(_, v)) => assert_eq!((100, 11), v),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
     #[test]
     fn test_ind_flat_map_sanity() {
         check_strategy_sanity(
@@ -140,6 +140,10 @@ impl<T : ValueTree> ValueTree for Fuse<T> {
             false
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.inner.current_complexity()
+    }
 }
 
 #[cfg(test)]
@@ -8,6 +8,7 @@
 // except according to those terms.
 
 use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
 
 use rand::XorShiftRng;
@@ -18,6 +19,11 @@ use test_runner::*;
 /// `Strategy` and `ValueTree` map adaptor.
 ///
 /// See `Strategy::prop_map()`.
+///
+/// The `Debug` output of a `Map` includes the type name of the mapping
+/// function, e.g. `my_crate::double` or
+/// `my_crate::tests::arb_point::{{closure}}`, so that nested maps in failure
+/// reports and compiler errors can be told apart.
 pub struct Map<S, F> {
     pub(super) source: S,
     pub(super) fun: Arc<F>,
@@ -27,7 +33,7 @@ impl<S : fmt::Debug, F> fmt::Debug for Map<S, F> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Map")
             .field("source", &self.source)
-            .field("fun", &"<function>")
+            .field("fun", &::std::any::type_name::<F>())
             .finish()
     }
 }
@@ -67,6 +73,74 @@ ValueTree for Map<S, F> {
     fn complicate(&mut self) -> bool {
         self.source.complicate()
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
+}
+
+/// `Strategy` and `ValueTree` adaptor converting values with `Into`.
+///
+/// See `Strategy::prop_map_into()`.
+pub struct MapInto<S, O> {
+    pub(super) source: S,
+    pub(super) output: PhantomData<O>,
+}
+
+impl<S : fmt::Debug, O> fmt::Debug for MapInto<S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapInto")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<S : Clone, O> Clone for MapInto<S, O> {
+    fn clone(&self) -> Self {
+        MapInto {
+            source: self.source.clone(),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<S : Strategy, O : fmt::Debug> Strategy for MapInto<S, O>
+where ValueFor<S> : Into<O> {
+    type Value = MapInto<S::Value, O>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.source.new_value(runner).map(
+            |v| MapInto { source: v, output: PhantomData })
+    }
+}
+
+impl<S : ValueTree, O : fmt::Debug> ValueTree for MapInto<S, O>
+where S::Value : Into<O> {
+    type Value = O;
+
+    fn current(&self) -> O {
+        self.source.current().into()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.source.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.source.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
 }
 
 /// `Strategy` perturbation adaptor.
@@ -156,6 +230,14 @@ ValueTree for PerturbValueTree<S, F> {
     fn complicate(&mut self) -> bool {
         self.source.complicate()
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
 }
 
 #[cfg(test)]
@@ -166,6 +248,27 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_map_into() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Id(u8);
+        impl From<u8> for Id {
+            fn from(v: u8) -> Self { Id(v) }
+        }
+
+        let input = (1u8..10).prop_map_into::<Id>();
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let mut case = input.clone().new_value(&mut runner).unwrap();
+            assert!(case.current().0 >= 1 && case.current().0 < 10);
+            while case.simplify() { }
+            assert_eq!(Id(1), case.current());
+        }
+
+        let widened = (0u8..10).prop_map_into::<u64>();
+        let _: u64 = widened.new_value(&mut runner).unwrap().current();
+    }
+
     #[test]
     fn test_map() {
         TestRunner::default()
@@ -175,6 +278,15 @@ mod test {
             }).unwrap();
     }
 
+    #[test]
+    fn map_debug_shows_source() {
+        fn double(v: i32) -> i32 { v * 2 }
+
+        let debug = format!("{:?}", (0..10).prop_map(double));
+        assert!(debug.starts_with("Map { source: 0..10, fun: "), "{}", debug);
+        assert!(debug.contains("double"), "{}", debug);
+    }
+
     #[test]
     fn perturb_uses_same_rng_every_time() {
         let mut runner = TestRunner::default();
@@ -186,6 +298,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn perturb_keeps_perturbation_while_shrinking() {
+        let mut runner = TestRunner::default();
+        let input = (0u32..1000).prop_perturb(
+            |v, mut rng| (v, rng.next_u32()));
+
+        for _ in 0..64 {
+            let mut value = input.new_value(&mut runner).unwrap();
+            let (_, perturbation) = value.current();
+            while value.simplify() {
+                assert_eq!(perturbation, value.current().1);
+            }
+            assert_eq!((0, perturbation), value.current());
+        }
+    }
+
     #[test]
     fn perturb_uses_varying_random_seeds() {
         let mut runner = TestRunner::default();
@@ -12,19 +12,23 @@
 mod traits;
 mod map;
 mod filter;
+mod filter_map;
 mod flatten;
 mod unions;
 mod recursive;
 mod shuffle;
 mod fuse;
+mod zip;
 
 pub use self::traits::*;
 pub use self::map::*;
 pub use self::filter::*;
+pub use self::filter_map::*;
 pub use self::flatten::*;
 pub use self::unions::*;
 pub use self::recursive::*;
 pub use self::shuffle::*;
 pub use self::fuse::*;
+pub use self::zip::*;
 
 pub mod statics;
@@ -11,6 +11,7 @@ use std::fmt;
 use std::sync::Arc;
 
 use strategy::traits::*;
+use strategy::unions::{float_to_weight, TupleUnion};
 use test_runner::*;
 
 /// Return type from `Strategy::prop_recursive()`.
@@ -80,6 +81,11 @@ Strategy for Recursive<BoxedStrategy<T>, F> {
         // also clamp all probabilities to 0.9 to ensure that we can't end up
         // with levels which are always pure branches, which further
         // underestimates size.
+        //
+        // Each level is a union whose first option is the whole tree below
+        // it. Shrinking a branch can thus collapse it into a value of the
+        // lower level (ultimately, into a value from `base`), and can never
+        // produce a structure which the layers could not have generated.
 
         let mut branch_probabilities = Vec::new();
         let mut k2 = u64::from(self.expected_branch_size) * 2;
@@ -92,13 +98,12 @@ Strategy for Recursive<BoxedStrategy<T>, F> {
         while let Some(branch_probability) = branch_probabilities.pop() {
             let recursive_choice = Arc::new((self.recurse)(Arc::clone(&strat)));
             let non_recursive_choice = strat;
-            strat = Arc::new(
-                ::bool::weighted(branch_probability.min(0.9))
-                    .prop_ind_flat_map(move |branch| if branch {
-                        Arc::clone(&recursive_choice)
-                    } else {
-                        Arc::clone(&non_recursive_choice)
-                    }).boxed());
+            let (weight_branch, weight_leaf) =
+                float_to_weight(branch_probability.min(0.9));
+            strat = Arc::new(TupleUnion::new((
+                (weight_leaf, non_recursive_choice),
+                (weight_branch, recursive_choice),
+            )).boxed());
         }
 
         strat.new_value(runner)
@@ -160,4 +165,52 @@ mod test {
         assert!(max_depth >= 3, "Only got max depth {}", max_depth);
         assert!(max_count > 48, "Only got max count {}", max_count);
     }
+
+    #[test]
+    fn shrinking_respects_depth_and_reaches_base() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum Tree {
+            Leaf(u32),
+            Branch(Vec<Tree>),
+        }
+
+        impl Tree {
+            fn depth(&self) -> u32 {
+                match *self {
+                    Tree::Leaf(_) => 0,
+                    Tree::Branch(ref children) =>
+                        1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+                }
+            }
+        }
+
+        let strat = (0u32..100).prop_map(Tree::Leaf).prop_recursive(
+            3, 32, 4,
+            |element| ::collection::vec(element, 1..4)
+                .prop_map(Tree::Branch).boxed());
+
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = strat.new_value(&mut runner).unwrap();
+            loop {
+                let depth = case.current().depth();
+                assert!(depth <= 3, "Shrunk to depth {}", depth);
+                if !case.simplify() { break; }
+            }
+
+            assert_eq!(Tree::Leaf(0), case.current());
+        }
+    }
+
+    #[test]
+    fn zero_depth_only_uses_base() {
+        let strat = Just(0u32).prop_recursive(
+            0, 64, 16,
+            |element| element.prop_map(|v| v + 1).boxed());
+
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            assert_eq!(0, strat.new_value(&mut runner).unwrap().current());
+        }
+    }
 }
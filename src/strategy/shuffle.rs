@@ -195,6 +195,11 @@ where V::Value : Shuffleable {
             self.dist.get_mut().as_mut().unwrap().complicate()
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        let dist = self.dist.get().map(|d| d.current() as u64).unwrap_or(0);
+        self.inner.current_complexity().saturating_add(dist)
+    }
 }
 
 #[cfg(test)]
@@ -36,6 +36,14 @@ pub trait FilterFn<T> {
 }
 
 /// Static version of `strategy::Filter`.
+///
+/// Values for which the `FilterFn` returns `false` are rejected while
+/// generating and skipped while shrinking, exactly as with `prop_filter()`.
+/// Strategies such as `collection::hash_set()` use this to name their types.
+///
+/// `prop_filter()` itself does not use this type since closures need not be
+/// `Clone`; it is nonetheless statically dispatched, sharing the closure
+/// through an `Arc` rather than boxing it.
 #[derive(Clone)]
 pub struct Filter<S, F> {
     source: S,
@@ -119,6 +127,14 @@ impl<S : ValueTree, F : FilterFn<S::Value>> ValueTree for Filter<S, F> {
             false
         }
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
 }
 
 /// Essentially `Fn (T) -> Output`.
@@ -179,6 +195,14 @@ ValueTree for Map<S, F> {
     fn complicate(&mut self) -> bool {
         self.source.complicate()
     }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
 }
 
 #[cfg(test)]
@@ -9,6 +9,7 @@
 
 use std::cmp;
 use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
 
 use rand::XorShiftRng;
@@ -69,6 +70,17 @@ pub trait Strategy : fmt::Debug {
         Map { source: self, fun: Arc::new(fun) }
     }
 
+    /// Returns a strategy which produces values of type `O` by converting
+    /// the values of `self` with `Into`.
+    ///
+    /// This is equivalent to `self.prop_map(Into::into)`, but the resulting
+    /// type can be named and needs no closure. Shrinking takes place in terms
+    /// of the source value.
+    fn prop_map_into<O : fmt::Debug>(self) -> MapInto<Self, O>
+    where Self : Sized, ValueFor<Self> : Into<O> {
+        MapInto { source: self, output: PhantomData }
+    }
+
     /// Returns a strategy which produces values transformed by the function
     /// `fun`, which is additionally given a random number generator.
     ///
@@ -207,6 +219,25 @@ pub trait Strategy : fmt::Debug {
         Flatten::new(Map { source: self, fun: Arc::new(fun) })
     }
 
+    /// Derives two strategies from each value produced by this strategy and
+    /// produces pairs of values from them.
+    ///
+    /// This behaves like `self.prop_flat_map(|v| (fun1(&v), fun2(&v)))`.
+    /// What is shared is the base value: both functions are passed a
+    /// reference to the same value generated by `self`, so the two derived
+    /// strategies always agree on it. Shrinking the base value regenerates
+    /// both, exactly as with `prop_flat_map()`.
+    ///
+    /// The resulting type stores both functions, each behind an `Arc` which
+    /// the strategy shares with the value trees it generates.
+    fn prop_flat_map_shared<S1 : Strategy, S2 : Strategy,
+                            F1 : Fn (&ValueFor<Self>) -> S1,
+                            F2 : Fn (&ValueFor<Self>) -> S2>
+        (self, fun1: F1, fun2: F2) -> SharedFlatMap<Self, F1, F2>
+    where Self : Sized {
+        Flatten::new(MapShared::new(self, fun1, fun2))
+    }
+
     /// Maps values produced by this strategy into new strategies and picks
     /// values from those strategies while considering the new strategies to be
     /// independent.
@@ -265,6 +296,26 @@ pub trait Strategy : fmt::Debug {
         Filter::new(self, whence.into(), fun)
     }
 
+    /// Returns a strategy which produces values transformed by the fallible
+    /// function `fun`, rejecting any value for which `fun` returns `None`.
+    ///
+    /// This is useful for mapping into types whose constructors can fail, such
+    /// as `NonZeroU32::new`, without first having to filter out the inputs
+    /// the constructor rejects. Rejections are counted exactly as they are for
+    /// `prop_filter()`, and all the caveats of that method apply here too.
+    ///
+    /// When shrinking, values for which `fun` returns `None` are skipped over
+    /// by complicating the source value until it is accepted again, so `fun`
+    /// is never asked to produce a value it has rejected.
+    ///
+    /// `whence` is used to record where and why the rejection occurred.
+    fn prop_filter_map<R : Into<Reason>, O : fmt::Debug,
+                       F : Fn (ValueFor<Self>) -> Option<O>>
+        (self, whence: R, fun: F) -> FilterMap<Self, F>
+    where Self : Sized {
+        FilterMap::new(self, whence.into(), fun)
+    }
+
     /// Returns a strategy which picks uniformly from `self` and `other`.
     ///
     /// When shrinking, if a value from `other` was originally chosen but that
@@ -284,6 +335,125 @@ pub trait Strategy : fmt::Debug {
         Union::new(vec![self, other])
     }
 
+    /// Returns a strategy which picks from `self` and `other` with relative
+    /// weights `self_weight` and `other_weight`.
+    ///
+    /// This is otherwise the same as `prop_union()`; e.g., `a.prop_union(b)`
+    /// is equivalent to `a.prop_weighted_union(1, b, 1)`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if either weight is 0 or if the sum of the weights overflows a
+    /// `u32`.
+    fn prop_weighted_union(self, self_weight: u32,
+                           other: Self, other_weight: u32) -> Union<Self>
+    where Self : Sized {
+        Union::new_weighted(vec![(self_weight, self), (other_weight, other)])
+    }
+
+    /// Returns a strategy which picks uniformly from `self` and `other`,
+    /// which may be of different types as long as they produce the same type
+    /// of value.
+    ///
+    /// This is a method-chaining alternative to `prop_oneof!`. Unlike
+    /// `prop_union()`, both strategies are boxed, and calling `prop_or()` on
+    /// the result adds to the same union rather than nesting it, so
+    /// `a.prop_or(b).prop_or(c)` picks each of the three with equal
+    /// probability. Shrinking moves to earlier strategies as for
+    /// `prop_union()`.
+    ///
+    /// ```
+    /// use proptest::prelude::*;
+    ///
+    /// let digit = prop::char::range('0', '9');
+    /// let letter = Just('x');
+    /// let punct = prop::sample::select(vec![',', '.', '!']);
+    /// let symbol = digit.prop_or(letter).prop_or(punct);
+    /// # let _ = symbol;
+    /// ```
+    fn prop_or<S : Strategy + 'static>(self, other: S)
+                                       -> Union<BoxedStrategy<ValueFor<Self>>>
+    where Self : Sized + 'static,
+          S::Value : ValueTree<Value = ValueFor<Self>> {
+        Union::new(vec![self.boxed(), other.boxed()])
+    }
+
+    /// Returns a strategy which picks from `self` and `other` with relative
+    /// weights `self_weight` and `other_weight`.
+    ///
+    /// This is otherwise the same as `prop_or()`, except that calling
+    /// `prop_or_weighted()` on the result nests it within a new union, so
+    /// `self_weight` applies to all of its strategies together.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if either weight is 0 or if the sum of the weights overflows a
+    /// `u32`.
+    fn prop_or_weighted<S : Strategy + 'static>(
+        self, self_weight: u32, other: S, other_weight: u32)
+        -> Union<BoxedStrategy<ValueFor<Self>>>
+    where Self : Sized + 'static,
+          S::Value : ValueTree<Value = ValueFor<Self>> {
+        Union::new_weighted(vec![(self_weight, self.boxed()),
+                                 (other_weight, other.boxed())])
+    }
+
+    /// Returns a strategy which produces pairs of values from `self` and
+    /// `other`.
+    ///
+    /// This is equivalent to the tuple strategy `(self, other)`, but allows
+    /// strategies to be combined in method-chaining style, such as
+    /// `a.prop_zip(b).prop_zip(c).prop_map(|((a, b), c)| ...)`.
+    ///
+    /// Shrinking behaves as for tuples: whichever of the two values is
+    /// estimated to be more complex is simplified first.
+    fn prop_zip<S : Strategy>(self, other: S) -> Zip<Self, S>
+    where Self : Sized {
+        Zip::new(self, other)
+    }
+
+    /// Returns a strategy which produces values of `fun` applied to values
+    /// from `self` and `other`.
+    ///
+    /// This is equivalent to `self.prop_zip(other).prop_map(|(a, b)| fun(a,
+    /// b))`, but needs no destructuring of the intermediate pair. Shrinking
+    /// takes place in terms of the two source values, as for `prop_zip()`.
+    ///
+    /// ```
+    /// use proptest::prelude::*;
+    ///
+    /// let areas = (1u32..10).prop_zip_with(1u32..10, |w, h| w * h);
+    /// # let _ = areas;
+    /// ```
+    fn prop_zip_with<S : Strategy, O : fmt::Debug,
+                     F : Fn (ValueFor<Self>, ValueFor<S>) -> O>
+        (self, other: S, fun: F) -> ZipWith<Self, S, F>
+    where Self : Sized {
+        ZipWith::new(self, other, fun)
+    }
+
+    /// Returns a strategy which produces pairs of values from `self` and
+    /// `other`, and which can be extended into a flat tuple by calling
+    /// `prop_tuple()` again.
+    ///
+    /// Unlike with `prop_zip()`, `a.prop_tuple(b).prop_tuple(c)` produces
+    /// `(A, B, C)` rather than `((A, B), C)`, as the tuple strategy
+    /// `(a, b, c)` does. This works up to 10 elements, the largest supported
+    /// tuple strategy. Shrinking behaves as for tuples.
+    ///
+    /// ```
+    /// use proptest::prelude::*;
+    ///
+    /// let rgba = (0u8..).prop_tuple(0u8..).prop_tuple(0u8..)
+    ///     .prop_tuple(Just(255u8))
+    ///     .prop_map(|(r, g, b, a)| [r, g, b, a]);
+    /// # let _ = rgba;
+    /// ```
+    fn prop_tuple<S : Strategy>(self, other: S) -> TupleChain<(Self, S)>
+    where Self : Sized {
+        TupleChain::new(self, other)
+    }
+
     /// Generate a recursive structure with `self` items as leaves.
     ///
     /// `recurse` is applied to various strategies that produce the same type
@@ -409,9 +579,12 @@ pub trait Strategy : fmt::Debug {
     ///
     /// See also `sboxed()` if this `Strategy` is `Send` and `Sync` and you
     /// want to preserve that information.
+    ///
+    /// Boxing a strategy which is already a `BoxedStrategy` returns it
+    /// unchanged rather than adding another layer of boxes.
     fn boxed(self) -> BoxedStrategy<ValueFor<Self>>
     where Self : Sized + 'static {
-        Box::new(BoxedStrategyWrapper(self))
+        BoxedStrategy(Box::new(BoxedStrategyWrapper(self)))
     }
 
     /// Erases the type of this `Strategy` so it can be passed around as a
@@ -421,7 +594,7 @@ pub trait Strategy : fmt::Debug {
     /// on the output.
     fn sboxed(self) -> SBoxedStrategy<ValueFor<Self>>
     where Self : Sized + Send + Sync + 'static {
-        Box::new(BoxedStrategyWrapper(self))
+        SBoxedStrategy(Box::new(BoxedStrategyWrapper(self)))
     }
 
     /// Wraps this strategy to prevent values from being subject to shrinking.
@@ -521,6 +694,26 @@ pub trait ValueTree {
     /// called even immediately after it had been called previously and
     /// returned `false`.
     fn complicate(&mut self) -> bool;
+    /// Returns a rough estimate of how complex the current value is.
+    ///
+    /// Smaller numbers mean simpler values, with 0 being as simple as the
+    /// tree can describe. The scale is arbitrary and only meaningful
+    /// when comparing states of the same tree, for example to decide which of
+    /// several candidate shrinks made the most progress. It has no influence
+    /// on how `simplify()` and `complicate()` behave.
+    ///
+    /// Composite trees typically sum the estimates of their parts. The
+    /// default implementation returns 0, i.e., no information.
+    fn current_complexity(&self) -> u64 { 0 }
+    /// Returns whether this tree has reached a state from which it cannot
+    /// simplify any further, i.e., whether `simplify()` would return `false`.
+    ///
+    /// Unlike `simplify()`, this never changes the state of the tree, so
+    /// callers driving the shrinking process can use it to stop without
+    /// making a wasted call. An implementation returning `true` must be
+    /// certain of it; the default implementation conservatively returns
+    /// `false`.
+    fn is_terminal(&self) -> bool { false }
 }
 
 impl<T : ValueTree + ?Sized> ValueTree for Box<T> {
@@ -528,21 +721,62 @@ impl<T : ValueTree + ?Sized> ValueTree for Box<T> {
     fn current(&self) -> Self::Value { (**self).current() }
     fn simplify(&mut self) -> bool { (**self).simplify() }
     fn complicate(&mut self) -> bool { (**self).complicate() }
+    fn current_complexity(&self) -> u64 { (**self).current_complexity() }
+    fn is_terminal(&self) -> bool { (**self).is_terminal() }
 }
 
-/// Shorthand for a boxed `Strategy` trait object as produced by
-/// `Strategy::boxed()`.
-pub type BoxedStrategy<T> = Box<Strategy<Value = Box<ValueTree<Value = T>>>>;
-/// Shorthand for a boxed `Strategy` trait object which is also `Sync` and
-/// `Send`, as produced by `Strategy::sboxed()`.
-pub type SBoxedStrategy<T> = Box<Strategy<Value = Box<ValueTree<Value = T>>> +
-                                 Sync + Send>;
+/// A boxed `Strategy` trait object as produced by `Strategy::boxed()`.
+///
+/// Each generated `ValueTree` is boxed exactly once, no matter how many times
+/// the strategy is re-boxed.
+#[derive(Debug)]
+pub struct BoxedStrategy<T>(Box<Strategy<Value = BoxedValueTree<T>>>);
+
+/// A boxed `Strategy` trait object which is also `Sync` and `Send`, as
+/// produced by `Strategy::sboxed()`.
+///
+/// Like `BoxedStrategy`, re-boxing this strategy does not add further
+/// layers of boxes.
+#[derive(Debug)]
+pub struct SBoxedStrategy<T>(
+    Box<Strategy<Value = BoxedValueTree<T>> + Sync + Send>);
+
+/// The `ValueTree` produced by `BoxedStrategy` and `SBoxedStrategy`.
+type BoxedValueTree<T> = Box<ValueTree<Value = T>>;
+
+impl<T : fmt::Debug> Strategy for BoxedStrategy<T> {
+    type Value = BoxedValueTree<T>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.0.new_value(runner)
+    }
+
+    fn boxed(self) -> BoxedStrategy<T> where Self : 'static {
+        self
+    }
+}
+
+impl<T : fmt::Debug> Strategy for SBoxedStrategy<T> {
+    type Value = BoxedValueTree<T>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.0.new_value(runner)
+    }
+
+    fn boxed(self) -> BoxedStrategy<T> where Self : 'static {
+        BoxedStrategy(self.0)
+    }
+
+    fn sboxed(self) -> SBoxedStrategy<T> where Self : 'static {
+        self
+    }
+}
 
 #[derive(Debug)]
 struct BoxedStrategyWrapper<T>(T);
 impl<T : Strategy> Strategy for BoxedStrategyWrapper<T>
 where T::Value : 'static {
-    type Value = Box<ValueTree<Value = ValueFor<T>>>;
+    type Value = BoxedValueTree<ValueFor<T>>;
 
     fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
         Ok(Box::new(self.0.new_value(runner)?))
@@ -577,6 +811,98 @@ impl<T : Clone + fmt::Debug> ValueTree for Just<T> {
 
     fn simplify(&mut self) -> bool { false }
     fn complicate(&mut self) -> bool { false }
+    fn is_terminal(&self) -> bool { true }
+}
+
+/// A `Strategy` which always produces a single `Copy` value and never
+/// simplifies.
+///
+/// This is like `Just`, but since the value is `Copy`, it is returned by
+/// simple copy rather than through `Clone`.
+#[derive(Clone, Copy, Debug)]
+pub struct Const<T : Copy + fmt::Debug>(
+    /// The value produced by this strategy.
+    pub T);
+
+impl<T : Copy + fmt::Debug> Strategy for Const<T> {
+    type Value = Self;
+
+    fn new_value(&self, _: &mut TestRunner) -> NewTree<Self> {
+        Ok(*self)
+    }
+}
+
+impl<T : Copy + fmt::Debug> ValueTree for Const<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        self.0
+    }
+
+    fn simplify(&mut self) -> bool { false }
+    fn complicate(&mut self) -> bool { false }
+    fn is_terminal(&self) -> bool { true }
+}
+
+/// A `Strategy` which always produces a single value, computed afresh by a
+/// function every time it is needed, and never simplifies.
+///
+/// This is useful in place of `Just` for values which cannot (or should not)
+/// be cloned.
+///
+/// ```
+/// use proptest::prelude::*;
+///
+/// let buffer = LazyJust::new(|| vec![0u8; 4096]);
+/// # let _ = buffer;
+/// ```
+pub struct LazyJust<T, F : Fn () -> T> {
+    function: F,
+    _marker: PhantomData<fn () -> T>,
+}
+
+impl<T, F : Fn () -> T> LazyJust<T, F> {
+    /// Create a strategy whose single value is produced by calling
+    /// `function`.
+    pub fn new(function: F) -> Self {
+        LazyJust { function, _marker: PhantomData }
+    }
+}
+
+impl<T, F : Clone + Fn () -> T> Clone for LazyJust<T, F> {
+    fn clone(&self) -> Self {
+        LazyJust::new(self.function.clone())
+    }
+}
+
+impl<T, F : Copy + Fn () -> T> Copy for LazyJust<T, F> { }
+
+impl<T, F : Fn () -> T> fmt::Debug for LazyJust<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LazyJust")
+            .field("function", &"<function>")
+            .finish()
+    }
+}
+
+impl<T : fmt::Debug, F : Clone + Fn () -> T> Strategy for LazyJust<T, F> {
+    type Value = Self;
+
+    fn new_value(&self, _: &mut TestRunner) -> NewTree<Self> {
+        Ok(self.clone())
+    }
+}
+
+impl<T : fmt::Debug, F : Fn () -> T> ValueTree for LazyJust<T, F> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        (self.function)()
+    }
+
+    fn simplify(&mut self) -> bool { false }
+    fn complicate(&mut self) -> bool { false }
+    fn is_terminal(&self) -> bool { true }
 }
 
 /// Wraps a `Strategy` or `ValueTree` to suppress shrinking of generated
@@ -603,6 +929,8 @@ impl<T : ValueTree> ValueTree for NoShrink<T> {
 
     fn simplify(&mut self) -> bool { false }
     fn complicate(&mut self) -> bool { false }
+    fn current_complexity(&self) -> u64 { self.0.current_complexity() }
+    fn is_terminal(&self) -> bool { true }
 }
 
 /// Options passed to `check_strategy_sanity()`.
@@ -800,3 +1128,110 @@ where S::Value : Clone + fmt::Debug, ValueFor<S> : cmp::PartialEq {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn const_produces_only_its_value() {
+        let mut runner = TestRunner::default();
+        let mut case = Const(42u32).new_value(&mut runner).unwrap();
+        assert_eq!(42, case.current());
+        assert!(!case.simplify());
+        assert!(!case.complicate());
+        assert_eq!(42, case.current());
+    }
+
+    #[test]
+    fn lazy_just_calls_function_for_each_value() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Debug, PartialEq)]
+        struct NotClone(u32);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let input = LazyJust::new(|| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            NotClone(42)
+        });
+
+        let mut runner = TestRunner::default();
+        let mut case = input.new_value(&mut runner).unwrap();
+        assert_eq!(NotClone(42), case.current());
+        assert_eq!(NotClone(42), case.current());
+        assert_eq!(2, CALLS.load(Ordering::SeqCst));
+        assert!(!case.simplify());
+        assert!(!case.complicate());
+    }
+
+    #[test]
+    fn no_shrink_reports_original_value() {
+        let input = (0u32..1000).no_shrink();
+
+        for _ in 0..256 {
+            let mut runner = TestRunner::default();
+            let case = input.new_value(&mut runner).unwrap();
+            let original = case.current();
+            let result = runner.run_one(case, |&v| {
+                prop_assert!(v < 100);
+                Ok(())
+            });
+
+            match result {
+                Ok(_) => assert!(original < 100),
+                Err(TestError::Fail(_, v)) => assert_eq!(original, v),
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn no_shrink_neither_simplifies_nor_complicates() {
+        let mut runner = TestRunner::default();
+        let mut case = (0u32..1000).no_shrink().new_value(&mut runner).unwrap();
+        let original = case.current();
+        assert!(!case.simplify());
+        assert!(!case.complicate());
+        assert_eq!(original, case.current());
+    }
+
+    #[test]
+    fn reboxing_does_not_add_layers() {
+        fn inner_ptr<T>(s: &BoxedStrategy<T>) -> *const u8 {
+            &*s.0 as *const _ as *const u8
+        }
+
+        let boxed = (0u32..1000).boxed();
+        let ptr = inner_ptr(&boxed);
+        let boxed = boxed.boxed();
+        assert_eq!(ptr, inner_ptr(&boxed));
+
+        let sboxed = (0u32..1000).sboxed();
+        let ptr = &*sboxed.0 as *const _ as *const u8;
+        let sboxed = sboxed.sboxed();
+        assert_eq!(ptr, &*sboxed.0 as *const _ as *const u8);
+        let boxed = sboxed.boxed();
+        assert_eq!(ptr, inner_ptr(&boxed));
+
+        let mut runner = TestRunner::default();
+        assert!(boxed.new_value(&mut runner).unwrap().current() < 1000);
+    }
+
+    #[test]
+    fn is_terminal_is_true_only_for_trees_which_cannot_simplify() {
+        let mut runner = TestRunner::default();
+
+        assert!(Just(5).is_terminal());
+        assert!(Const(5).is_terminal());
+        assert!(LazyJust::new(|| 5).is_terminal());
+        assert!((0..100).no_shrink().new_value(&mut runner).unwrap()
+                .is_terminal());
+        assert!(Just(5).prop_map(|v| v * 2).boxed()
+                .new_value(&mut runner).unwrap().is_terminal());
+
+        // Trees which do not know conservatively report `false`.
+        assert!(!(0..100).prop_map(|v| v * 2).boxed()
+                .new_value(&mut runner).unwrap().is_terminal());
+    }
+}
@@ -8,6 +8,9 @@
 // except according to those terms.
 
 use std::cmp::{max, min};
+use std::fmt;
+use std::iter::FromIterator;
+use std::sync::Arc;
 use std::u32;
 
 use rand;
@@ -54,12 +57,22 @@ impl<T : Strategy> Union<T> {
     /// weight of 2 will be chosen twice as frequently as one with a weight of
     /// 1\.
     ///
+    /// `options` may be any iterable, which allows building a union from
+    /// strategies only known at run time, such as a `Vec` of
+    /// `BoxedStrategy`s.
+    ///
+    /// Shrinking moves to earlier options as with `new()`, regardless of their
+    /// weights; `prop_recursive()` and `prop_oneof!` rely on this to shrink
+    /// towards their first option. To have lower-weight alternatives tried
+    /// first, sort `options` by ascending weight before passing them in.
+    ///
     /// ## Panics
     ///
     /// Panics if `options` is empty or any element has a weight of 0.
     ///
     /// Panics if the sum of the weights overflows a `u32`.
-    pub fn new_weighted(options: Vec<W<T>>) -> Self {
+    pub fn new_weighted<I : IntoIterator<Item = W<T>>>(options: I) -> Self {
+        let options: Vec<W<T>> = options.into_iter().collect();
         assert!(!options.is_empty());
         assert!(!options.iter().any(|&(w, _)| 0 == w),
                 "Union option has a weight of 0");
@@ -75,6 +88,62 @@ impl<T : Strategy> Union<T> {
     }
 }
 
+impl<T : fmt::Debug> Union<BoxedStrategy<T>> {
+    /// Box `other` and add it as an additional alternate strategy with weight
+    /// 1.
+    ///
+    /// This takes precedence over `Strategy::prop_or()`, so that chained
+    /// calls like `a.prop_or(b).prop_or(c)` build a single union.
+    pub fn prop_or<S : Strategy + 'static>(self, other: S) -> Self
+    where S::Value : ValueTree<Value = T> {
+        self.or(other.boxed())
+    }
+}
+
+/// Collects strategies into a `Union` which selects uniformly from them, as
+/// with `Union::new()`.
+///
+/// Unlike `Union::new()`, this accepts an empty iterator, since the number of
+/// options is typically only known at run time. A union without options
+/// rejects every value it is asked to generate.
+///
+/// ```
+/// use std::iter::FromIterator;
+/// use proptest::prelude::*;
+/// use proptest::strategy::Union;
+///
+/// let plugins = vec!["alpha", "beta", "gamma"];
+/// let names = Union::from_iter(
+///     plugins.into_iter().map(|name| Just(name.to_owned()).boxed()));
+/// # let _ = names;
+/// ```
+impl<T : Strategy> FromIterator<T> for Union<T> {
+    fn from_iter<I : IntoIterator<Item = T>>(options: I) -> Self {
+        Union { options: options.into_iter().map(|v| (1, v)).collect() }
+    }
+}
+
+/// Collects weighted strategies into a `Union`, as with
+/// `Union::new_weighted()`.
+///
+/// Unlike `Union::new_weighted()`, this accepts an empty iterator, in which
+/// case the union rejects every value it is asked to generate.
+///
+/// ## Panics
+///
+/// Panics if any element has a weight of 0 or if the sum of the weights
+/// overflows a `u32`.
+impl<T : Strategy> FromIterator<W<T>> for Union<T> {
+    fn from_iter<I : IntoIterator<Item = W<T>>>(options: I) -> Self {
+        let options: Vec<W<T>> = options.into_iter().collect();
+        if options.is_empty() {
+            Union { options }
+        } else {
+            Union::new_weighted(options)
+        }
+    }
+}
+
 fn pick_weighted<I : Iterator<Item = u32>>(runner: &mut TestRunner,
                                            weights1: I, weights2: I) -> usize {
     let sum = weights1.sum();
@@ -92,6 +161,14 @@ impl<T : Strategy> Strategy for Union<T> {
     fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
         fn extract_weight<V>(&(w, _): &W<V>) -> u32 { w }
 
+        if self.options.is_empty() {
+            // Nothing can ever be generated, so reject like a filter which
+            // nothing passes until the runner gives up.
+            loop {
+                runner.reject_local("Union has no options")?;
+            }
+        }
+
         let pick = pick_weighted(
             runner,
             self.options.iter().map(extract_weight::<T>),
@@ -121,6 +198,7 @@ pub struct UnionValueTree<T : ValueTree> {
 }
 
 macro_rules! access_vec {
+    (@init $this:expr, $ix:expr) => { true };
     ([$($muta:tt)*] $dst:ident = $this:expr, $ix:expr, $body:block) => {{
         let $dst = &$($muta)* $this.options[$ix];
         $body
@@ -140,14 +218,22 @@ macro_rules! union_value_tree_body {
         fn simplify(&mut self) -> bool {
             if $access!([mut] opt = self, self.pick, { opt.simplify() }) {
                 self.prev_pick = None;
-                true
-            } else if self.pick > self.min_pick {
-                self.prev_pick = Some(self.pick);
-                self.pick -= 1;
-                true
-            } else {
-                false
+                return true;
             }
+
+            // Move to the closest earlier option which can be generated.
+            for pick in (self.min_pick..self.pick).rev() {
+                if $access!(@init self, pick) {
+                    self.prev_pick = Some(self.pick);
+                    self.pick = pick;
+                    return true;
+                }
+            }
+
+            // Nothing left to try, so a later `complicate()` must not undo an
+            // earlier, accepted move to a lower pick.
+            self.prev_pick = None;
+            false
         }
 
         fn complicate(&mut self) -> bool {
@@ -160,6 +246,14 @@ macro_rules! union_value_tree_body {
                 $access!([mut] opt = self, self.pick, { opt.complicate() })
             }
         }
+
+        fn current_complexity(&self) -> u64 {
+            // Earlier options are considered simpler, so the index of the
+            // current option counts towards the complexity.
+            $access!([] opt = self, self.pick, {
+                (self.pick as u64).saturating_add(opt.current_complexity())
+            })
+        }
     }
 }
 
@@ -167,21 +261,172 @@ impl<T : ValueTree> ValueTree for UnionValueTree<T> {
     union_value_tree_body!(T::Value, access_vec);
 }
 
+/// A `ValueTree` which is only generated from its strategy when first
+/// needed.
+///
+/// `TupleUnionValueTree` uses this for every option other than the one
+/// initially picked, since the earlier options are only generated if
+/// shrinking moves to them, and the later ones never are. `U` is the tuple of
+/// options of the `TupleUnion` and `S` the strategy of this option.
+pub struct LazyValueTree<U, S : Strategy> {
+    state: LazyValueTreeState<U, S>,
+}
+
+enum LazyValueTreeState<U, S : Strategy> {
+    Initialized(S::Value),
+    Uninitialized {
+        options: Arc<U>,
+        option: fn (&U) -> &S,
+        generation: DeferredGeneration,
+    },
+    /// Generating the value tree was rejected.
+    Failed,
+}
+
+// Implemented manually since deriving would require `U : Clone` and
+// `S : Clone` but not `S::Value : Clone`.
+impl<U, S : Strategy> Clone for LazyValueTree<U, S> where S::Value : Clone {
+    fn clone(&self) -> Self {
+        LazyValueTree {
+            state: match self.state {
+                LazyValueTreeState::Initialized(ref value_tree) =>
+                    LazyValueTreeState::Initialized(value_tree.clone()),
+                LazyValueTreeState::Uninitialized {
+                    ref options, option, ref generation
+                } => LazyValueTreeState::Uninitialized {
+                    options: Arc::clone(options),
+                    option,
+                    generation: generation.clone(),
+                },
+                LazyValueTreeState::Failed => LazyValueTreeState::Failed,
+            },
+        }
+    }
+}
+
+impl<U, S : Strategy> fmt::Debug for LazyValueTree<U, S>
+where S::Value : fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.state {
+            LazyValueTreeState::Initialized(ref value_tree) =>
+                f.debug_tuple("LazyValueTree").field(value_tree).finish(),
+            LazyValueTreeState::Uninitialized {
+                ref options, option, ..
+            } => f.debug_struct("LazyValueTree")
+                .field("strategy", option(options)).finish(),
+            LazyValueTreeState::Failed => write!(f, "LazyValueTree(Failed)"),
+        }
+    }
+}
+
+impl<U, S : Strategy> LazyValueTree<U, S> {
+    /// Create a `LazyValueTree` which will generate its value tree from the
+    /// strategy `option` picks out of `options`.
+    pub(crate) fn new(options: Arc<U>, option: fn (&U) -> &S,
+                      generation: DeferredGeneration) -> Self {
+        LazyValueTree {
+            state: LazyValueTreeState::Uninitialized {
+                options, option, generation,
+            },
+        }
+    }
+
+    /// Create a `LazyValueTree` wrapping an already generated value tree.
+    pub(crate) fn new_initialized(value_tree: S::Value) -> Self {
+        LazyValueTree {
+            state: LazyValueTreeState::Initialized(value_tree),
+        }
+    }
+
+    /// Generate the value tree if that has not been attempted yet.
+    pub(crate) fn maybe_init(&mut self) {
+        let state = match self.state {
+            LazyValueTreeState::Uninitialized {
+                ref options, option, ref generation
+            } => match generation.new_value(option(options)) {
+                Ok(value_tree) => LazyValueTreeState::Initialized(value_tree),
+                Err(_) => LazyValueTreeState::Failed,
+            },
+            _ => return,
+        };
+        self.state = state;
+    }
+
+    /// Whether the value tree has been successfully generated.
+    pub(crate) fn is_initialized(&self) -> bool {
+        match self.state {
+            LazyValueTreeState::Initialized(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Return the value tree, if it has been generated.
+    pub(crate) fn as_inner(&self) -> Option<&S::Value> {
+        match self.state {
+            LazyValueTreeState::Initialized(ref value_tree) =>
+                Some(value_tree),
+            _ => None,
+        }
+    }
+
+    /// Return the value tree, if it has been generated.
+    pub(crate) fn as_inner_mut(&mut self) -> Option<&mut S::Value> {
+        match self.state {
+            LazyValueTreeState::Initialized(ref mut value_tree) =>
+                Some(value_tree),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! lazy_inner {
+    ([] $lazy:expr) => { $lazy.as_inner() };
+    ([mut] $lazy:expr) => { $lazy.as_inner_mut() };
+}
+
 macro_rules! def_access_tuple {
     ($b:tt $name:ident, $($n:tt)*) => {
         macro_rules! $name {
+            (@init $b this:expr, $b ix:expr) => {
+                match $b ix {
+                    0 => {
+                        $b this.options.0.maybe_init();
+                        $b this.options.0.is_initialized()
+                    },
+                    $(
+                        $n => {
+                            if let Some(ref mut lazy) = $b this.options.$n {
+                                lazy.maybe_init();
+                                lazy.is_initialized()
+                            } else {
+                                false
+                            }
+                        },
+                    )*
+                    _ => false,
+                }
+            };
             ([$b($b muta:tt)*] $b dst:ident = $b this:expr,
              $b ix:expr, $b body:block) => {
                 match $b ix {
                     0 => {
-                        let $b dst = &$b($b muta)* $b this.options.0;
-                        $b body
+                        let inner = lazy_inner!(
+                            [$b($b muta)*] $b this.options.0);
+                        if let Some($b dst) = inner {
+                            $b body
+                        } else {
+                            panic!("TupleUnion tried to access \
+                                    uninitialised slot 0")
+                        }
                     },
                     $(
                         $n => {
-                            if let Some(ref $b($b muta)* $b dst) =
-                                $b this.options.$n
-                            {
+                            let inner = match $b this.options.$n {
+                                Some(ref $b($b muta)* lazy) =>
+                                    lazy_inner!([$b($b muta)*] lazy),
+                                None => None,
+                            };
+                            if let Some($b dst) = inner {
                                 $b body
                             } else {
                                 panic!("TupleUnion tried to access \
@@ -212,8 +457,19 @@ def_access_tuple!($ access_tupleA, 1 2 3 4 5 6 7 8 9);
 /// This allows better performance than vanilla `Union` since one does not need
 /// to resort to boxing and dynamic dispatch to handle heterogeneous
 /// strategies.
-#[derive(Clone, Copy, Debug)]
-pub struct TupleUnion<T>(T);
+///
+/// Only the value tree of the option picked is generated up front. Those of
+/// earlier options are generated if and when shrinking moves to them, and
+/// those of later options never are.
+#[derive(Debug)]
+pub struct TupleUnion<T>(Arc<T>);
+
+// Implemented manually since deriving would require `T : Clone`.
+impl<T> Clone for TupleUnion<T> {
+    fn clone(&self) -> Self {
+        TupleUnion(Arc::clone(&self.0))
+    }
+}
 
 impl<T> TupleUnion<T> {
     /// Wrap `tuple` in a `TupleUnion`.
@@ -227,17 +483,22 @@ impl<T> TupleUnion<T> {
     /// Using this constructor directly is discouraged; prefer to use
     /// `prop_oneof!` since it is generally clearer.
     pub fn new(tuple: T) -> Self {
-        TupleUnion(tuple)
+        TupleUnion(Arc::new(tuple))
     }
 }
 
 macro_rules! tuple_union {
     ($($gen:ident $ix:tt)*) => {
+        tuple_union!(@impl (W<A>, $(W<$gen>),*), $($gen $ix)*);
+    };
+
+    (@impl $options:ty, $($gen:ident $ix:tt)*) => {
         impl<A : Strategy, $($gen: Strategy),*> Strategy
-        for TupleUnion<(W<A>, $(W<$gen>),*)>
+        for TupleUnion<$options>
         where $($gen::Value : ValueTree<Value = ValueFor<A>>),* {
             type Value = TupleUnionValueTree<
-                (A::Value, $(Option<$gen::Value>),*)>;
+                (LazyValueTree<$options, A>,
+                 $(Option<LazyValueTree<$options, $gen>>),*)>;
 
             fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
                 let weights = [((self.0).0).0, $(((self.0).$ix).0),*];
@@ -246,9 +507,23 @@ macro_rules! tuple_union {
 
                 Ok(TupleUnionValueTree {
                     options: (
-                        ((self.0).0).1.new_value(runner)?,
-                        $(if $ix <= pick {
-                            Some(((self.0).$ix).1.new_value(runner)?)
+                        if 0 == pick {
+                            LazyValueTree::new_initialized(
+                                ((self.0).0).1.new_value(runner)?)
+                        } else {
+                            LazyValueTree::new(
+                                Arc::clone(&self.0),
+                                |options| &(options.0).1,
+                                runner.defer())
+                        },
+                        $(if $ix == pick {
+                            Some(LazyValueTree::new_initialized(
+                                ((self.0).$ix).1.new_value(runner)?))
+                        } else if $ix < pick {
+                            Some(LazyValueTree::new(
+                                Arc::clone(&self.0),
+                                |options| &(options.$ix).1,
+                                runner.defer()))
                         } else {
                             None
                         }),*),
@@ -258,7 +533,7 @@ macro_rules! tuple_union {
                 })
             }
         }
-    }
+    };
 }
 
 tuple_union!(B 1);
@@ -282,11 +557,17 @@ pub struct TupleUnionValueTree<T> {
 
 macro_rules! value_tree_tuple {
     ($access:ident, $($gen:ident)*) => {
-        impl<A : ValueTree, $($gen: ValueTree<Value = A::Value>),*> ValueTree
-        for TupleUnionValueTree<(A, $(Option<$gen>),*)> {
-            union_value_tree_body!(A::Value, $access);
+        value_tree_tuple!(@impl $access, (W<A>, $(W<$gen>),*), $($gen)*);
+    };
+
+    (@impl $access:ident, $options:ty, $($gen:ident)*) => {
+        impl<A : Strategy, $($gen: Strategy),*> ValueTree
+        for TupleUnionValueTree<(LazyValueTree<$options, A>,
+                                 $(Option<LazyValueTree<$options, $gen>>),*)>
+        where $($gen::Value : ValueTree<Value = ValueFor<A>>),* {
+            union_value_tree_body!(ValueFor<A>, $access);
         }
-    }
+    };
 }
 
 value_tree_tuple!(access_tuple2, B);
@@ -299,6 +580,62 @@ value_tree_tuple!(access_tuple8, B C D E F G H);
 value_tree_tuple!(access_tuple9, B C D E F G H I);
 value_tree_tuple!(access_tupleA, B C D E F G H I J);
 
+/// A union of two options which, unlike `TupleUnion`, generates the value
+/// trees of the option picked and of all earlier ones up front.
+///
+/// This lets `OptionStrategy`, `MaybeOk` and `MaybeErr` keep value tree types
+/// which only name the value trees of their delegates.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EagerTupleUnion<T>(pub(crate) T);
+
+impl<A : Strategy, B : Strategy> Strategy for EagerTupleUnion<(W<A>, W<B>)>
+where B::Value : ValueTree<Value = ValueFor<A>> {
+    type Value = TupleUnionValueTree<(A::Value, Option<B::Value>)>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let weights = [((self.0).0).0, ((self.0).1).0];
+        let pick = pick_weighted(runner, weights.iter().cloned(),
+                                 weights.iter().cloned());
+
+        Ok(TupleUnionValueTree {
+            options: (
+                ((self.0).0).1.new_value(runner)?,
+                if 1 == pick {
+                    Some(((self.0).1).1.new_value(runner)?)
+                } else {
+                    None
+                }),
+            pick: pick,
+            min_pick: 0,
+            prev_pick: None,
+        })
+    }
+}
+
+macro_rules! access_eager_tuple2 {
+    (@init $this:expr, $ix:expr) => { true };
+    ([$($muta:tt)*] $dst:ident = $this:expr, $ix:expr, $body:block) => {
+        match $ix {
+            0 => {
+                let $dst = &$($muta)* $this.options.0;
+                $body
+            },
+            1 => if let Some(ref $($muta)* $dst) = $this.options.1 {
+                $body
+            } else {
+                panic!("TupleUnion tried to access uninitialised slot 1")
+            },
+            _ => panic!("TupleUnion tried to access out-of-range slot {}",
+                        $ix),
+        }
+    }
+}
+
+impl<A : ValueTree, B : ValueTree<Value = A::Value>> ValueTree
+for TupleUnionValueTree<(A, Option<B>)> {
+    union_value_tree_body!(A::Value, access_eager_tuple2);
+}
+
 const WEIGHT_BASE: u32 = 0x8000_0000;
 
 /// Convert a floating-point weight in the range (0.0,1.0) to a pair of weights
@@ -330,6 +667,37 @@ pub fn float_to_weight(f: f64) -> (u32, u32) {
 mod test {
     use super::*;
 
+    #[test]
+    fn union_from_iter_picks_every_option_and_shrinks_to_earlier() {
+        fn options() -> Vec<BoxedStrategy<u32>> {
+            (0..4u32).map(|i| (i * 10..i * 10 + 10).boxed()).collect()
+        }
+
+        let uniform = Union::from_iter(options());
+        let weighted: Union<_> = options().into_iter()
+            .map(|s| (2, s)).collect();
+        let mut runner = TestRunner::default();
+
+        for input in &[uniform, weighted] {
+            let mut seen = [false; 4];
+            for _ in 0..256 {
+                let mut case = input.new_value(&mut runner).unwrap();
+                seen[(case.current() / 10) as usize] = true;
+                while case.simplify() { }
+                assert_eq!(0, case.current());
+            }
+            assert!(seen.iter().all(|&s| s), "{:?}", seen);
+        }
+    }
+
+    #[test]
+    fn empty_union_from_iter_rejects() {
+        let input = Union::from_iter(Vec::<BoxedStrategy<u32>>::new());
+        let mut runner = TestRunner::default();
+        assert!(input.new_value(&mut runner).is_err());
+        assert!(runner.stats().local_rejects > 0);
+    }
+
     #[test]
     fn test_union() {
         let input = (10u32..20u32).prop_union(30u32..40u32);
@@ -385,6 +753,79 @@ mod test {
         assert!(counts[1] > counts[2] * 3/2);
     }
 
+    #[test]
+    fn test_prop_weighted_union() {
+        let input = Just(0usize).prop_weighted_union(1, Just(1usize), 3);
+
+        let mut counts = [0, 0];
+        let mut runner = TestRunner::default();
+        for _ in 0..4096 {
+            counts[input.new_value(&mut runner).unwrap().current()] += 1;
+        }
+
+        assert!(counts[0] > 0);
+        assert!(counts[1] > counts[0] * 2, "Bad counts: {:?}", counts);
+    }
+
+    #[test]
+    fn prop_or_chains_into_one_uniform_union() {
+        let input = Just(0usize).prop_or(1usize..2).prop_or(Just(2usize));
+
+        let mut counts = [0, 0, 0];
+        let mut runner = TestRunner::default();
+        for _ in 0..3072 {
+            counts[input.new_value(&mut runner).unwrap().current()] += 1;
+        }
+
+        assert!(counts.iter().all(|&n| n > 850 && n < 1200),
+                "Bad counts: {:?}", counts);
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            while case.simplify() { }
+            assert_eq!(0, case.current());
+        }
+    }
+
+    #[test]
+    fn test_prop_or_weighted() {
+        let input = Just(0usize).prop_or_weighted(1, 1usize..2, 3);
+
+        let mut counts = [0, 0];
+        let mut runner = TestRunner::default();
+        for _ in 0..4096 {
+            counts[input.new_value(&mut runner).unwrap().current()] += 1;
+        }
+
+        assert!(counts[0] > 0);
+        assert!(counts[1] > counts[0] * 2, "Bad counts: {:?}", counts);
+    }
+
+    #[test]
+    fn union_of_runtime_boxed_strategies() {
+        let ranges = vec![(0, 10), (100, 110), (1000, 1010)];
+        let input = Union::new_weighted(ranges.into_iter().map(
+            |(lo, hi)| (1, (lo..hi).boxed())));
+
+        let mut seen = [false; 3];
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let value = case.current();
+            seen[match value {
+                0..=9 => 0,
+                100..=109 => 1,
+                1000..=1009 => 2,
+                _ => panic!("Unexpected value {}", value),
+            }] = true;
+
+            while case.simplify() { }
+            assert_eq!(0, case.current());
+        }
+
+        assert_eq!([true; 3], seen);
+    }
+
     #[test]
     fn test_union_sanity() {
         check_strategy_sanity(Union::new_weighted(vec![
@@ -486,6 +927,46 @@ mod test {
         test!(r, r, r, r, r, r, r, r, r); // 10
     }
 
+    #[test]
+    fn tuple_union_only_generates_earlier_options_when_shrinking() {
+        let never = Just(0u32).prop_filter("never", |_| false);
+        let input = TupleUnion::new(((1, never), (1, Just(1u32))));
+        let mut runner = TestRunner::default();
+
+        let mut generated = 0;
+        for _ in 0..64 {
+            // Picking the first option fails, but picking the second does not
+            // try to generate the first.
+            if let Ok(mut case) = input.new_value(&mut runner) {
+                generated += 1;
+                assert_eq!(1, case.current());
+                // Since the first option cannot be generated, shrinking
+                // stays with the second, and the attempt counts towards the
+                // runner's local rejections.
+                let local_rejects = runner.stats().local_rejects;
+                assert!(!case.simplify());
+                assert_eq!(1, case.current());
+                assert!(runner.stats().local_rejects > local_rejects);
+            }
+        }
+
+        assert!(generated > 0);
+    }
+
+    #[test]
+    fn test_union_complexity() {
+        let input = TupleUnion::new(((1, Just(0u32)), (1, 10u32..20)));
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let case = input.new_value(&mut runner).unwrap();
+            match case.current() {
+                0 => assert_eq!(0, case.current_complexity()),
+                v => assert_eq!(1 + u64::from(v), case.current_complexity()),
+            }
+        }
+    }
+
     #[test]
     fn test_tuple_union_sanity() {
         check_strategy_sanity(
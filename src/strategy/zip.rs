@@ -0,0 +1,254 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::sync::Arc;
+
+use strategy::traits::*;
+use test_runner::*;
+use tuple::TupleValueTree;
+
+opaque_strategy_wrapper! {
+    /// `Strategy` which combines two strategies into one producing pairs.
+    ///
+    /// See `Strategy::prop_zip()`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Zip[<A, B>][where A : Strategy, B : Strategy]
+        ((A, B)) -> ZipValueTree<A::Value, B::Value>;
+    /// `ValueTree` corresponding to `Zip`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct ZipValueTree[<A, B>][where A : ValueTree, B : ValueTree]
+        (TupleValueTree<(A, B)>) -> (A::Value, B::Value);
+}
+
+impl<A : Strategy, B : Strategy> Zip<A, B> {
+    pub(super) fn new(a: A, b: B) -> Self {
+        Zip((a, b))
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// `Strategy` which produces flat tuples of values from the tuple of
+    /// strategies it wraps.
+    ///
+    /// See `Strategy::prop_tuple()`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TupleChain[<T>][where T : Strategy]
+        (T) -> TupleChainValueTree<T::Value>;
+    /// `ValueTree` corresponding to `TupleChain`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TupleChainValueTree[<T>][where T : ValueTree]
+        (T) -> T::Value;
+}
+
+impl<A : Strategy, B : Strategy> TupleChain<(A, B)> {
+    pub(super) fn new(a: A, b: B) -> Self {
+        TupleChain((a, b))
+    }
+}
+
+macro_rules! tuple_chain {
+    ($($typ:ident),*) => {
+        impl<$($typ : Strategy),*> TupleChain<($($typ,)*)> {
+            /// Returns a strategy which produces the same tuples with a value
+            /// from `next` appended.
+            ///
+            /// See `Strategy::prop_tuple()`.
+            #[allow(non_snake_case)]
+            pub fn prop_tuple<Z : Strategy>(self, next: Z)
+                                            -> TupleChain<($($typ,)* Z,)> {
+                let ($($typ,)*) = self.0;
+                TupleChain(($($typ,)* next,))
+            }
+        }
+    }
+}
+
+tuple_chain!(A, B);
+tuple_chain!(A, B, C);
+tuple_chain!(A, B, C, D);
+tuple_chain!(A, B, C, D, E);
+tuple_chain!(A, B, C, D, E, F);
+tuple_chain!(A, B, C, D, E, F, G);
+tuple_chain!(A, B, C, D, E, F, G, H);
+tuple_chain!(A, B, C, D, E, F, G, H, I);
+
+/// `Strategy` which combines the values of two strategies with a function.
+///
+/// See `Strategy::prop_zip_with()`.
+pub struct ZipWith<A : Strategy, B : Strategy, F> {
+    source: Zip<A, B>,
+    fun: Arc<F>,
+}
+
+impl<A : Strategy, B : Strategy, F> ZipWith<A, B, F> {
+    pub(super) fn new(a: A, b: B, fun: F) -> Self {
+        ZipWith { source: Zip::new(a, b), fun: Arc::new(fun) }
+    }
+}
+
+impl<A : Strategy + fmt::Debug, B : Strategy + fmt::Debug, F> fmt::Debug
+for ZipWith<A, B, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ZipWith")
+            .field("a", &(self.source.0).0)
+            .field("b", &(self.source.0).1)
+            .field("fun", &"<function>")
+            .finish()
+    }
+}
+
+impl<A : Strategy + Clone, B : Strategy + Clone, F> Clone
+for ZipWith<A, B, F> {
+    fn clone(&self) -> Self {
+        ZipWith {
+            source: self.source.clone(),
+            fun: Arc::clone(&self.fun),
+        }
+    }
+}
+
+impl<A : Strategy, B : Strategy, O : fmt::Debug,
+     F : Fn (ValueFor<A>, ValueFor<B>) -> O>
+Strategy for ZipWith<A, B, F> {
+    type Value = ZipWithValueTree<A::Value, B::Value, F>;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.source.new_value(runner).map(|source| ZipWithValueTree {
+            source, fun: Arc::clone(&self.fun),
+        })
+    }
+}
+
+/// `ValueTree` corresponding to `ZipWith`.
+pub struct ZipWithValueTree<A : ValueTree, B : ValueTree, F> {
+    source: ZipValueTree<A, B>,
+    fun: Arc<F>,
+}
+
+impl<A : ValueTree + fmt::Debug, B : ValueTree + fmt::Debug, F> fmt::Debug
+for ZipWithValueTree<A, B, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ZipWithValueTree")
+            .field("source", &self.source)
+            .field("fun", &"<function>")
+            .finish()
+    }
+}
+
+impl<A : ValueTree + Clone, B : ValueTree + Clone, F> Clone
+for ZipWithValueTree<A, B, F> {
+    fn clone(&self) -> Self {
+        ZipWithValueTree {
+            source: self.source.clone(),
+            fun: Arc::clone(&self.fun),
+        }
+    }
+}
+
+impl<A : ValueTree, B : ValueTree, O : fmt::Debug,
+     F : Fn (A::Value, B::Value) -> O>
+ValueTree for ZipWithValueTree<A, B, F> {
+    type Value = O;
+
+    fn current(&self) -> O {
+        let (a, b) = self.source.current();
+        (self.fun)(a, b)
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.source.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.source.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.source.current_complexity()
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.source.is_terminal()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zip_shrinks_both_elements() {
+        let input = (0u32..100).prop_zip(0u32..100);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            while case.simplify() { }
+            assert_eq!((0, 0), case.current());
+        }
+    }
+
+    #[test]
+    fn zip_chains() {
+        let input = (0u32..10).prop_zip(10u32..20).prop_zip(20u32..30)
+            .prop_map(|((a, b), c)| a + b + c);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let v = input.new_value(&mut runner).unwrap().current();
+            assert!(v >= 30 && v <= 57);
+        }
+    }
+
+    #[test]
+    fn zip_with_combines_and_shrinks_both_values() {
+        let input = (0u32..100).prop_zip_with(100u32..200, |a, b| (b - a, a));
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let (diff, a) = case.current();
+            assert!(a < 100 && diff > 0 && diff < 200 - a);
+            while case.simplify() { }
+            assert_eq!((100, 0), case.current());
+        }
+    }
+
+    #[test]
+    fn tuple_chains_flatten_and_shrink_all_elements() {
+        let input = (0u32..10).prop_tuple(10u32..20).prop_tuple(20u32..30)
+            .prop_tuple(Just(true));
+        assert_eq!("TupleChain((0..10, 10..20, 20..30, Just(true)))",
+                   format!("{:?}", input));
+
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let (a, b, c, d) = case.current();
+            assert!(a < 10 && b >= 10 && b < 20 && c >= 20 && c < 30 && d);
+            while case.simplify() { }
+            assert_eq!((0, 10, 20, true), case.current());
+        }
+
+        let ten = Just(0).prop_tuple(Just(1)).prop_tuple(Just(2))
+            .prop_tuple(Just(3)).prop_tuple(Just(4)).prop_tuple(Just(5))
+            .prop_tuple(Just(6)).prop_tuple(Just(7)).prop_tuple(Just(8))
+            .prop_tuple(Just(9));
+        assert_eq!((0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
+                   ten.new_value(&mut runner).unwrap().current());
+    }
+
+    #[test]
+    fn test_sanity() {
+        check_strategy_sanity((0i32..100).prop_zip(Just(5)), None);
+        check_strategy_sanity(
+            (0i32..100).prop_zip_with(Just(5), |a, b| a * b), None);
+    }
+}
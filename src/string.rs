@@ -8,19 +8,23 @@
 // except according to those terms.
 
 //! Strategies for generating strings and byte strings from regular
-//! expressions.
+//! expressions, from strategies for their characters, or from fixed
+//! alphabets.
 
 use std::borrow::Cow;
 use std::fmt;
 use std::u32;
 
 use regex_syntax as rs;
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
 
 use bool;
 use char;
-use collection;
+use collection::{self, SizeRange, VecStrategy, VecValueTree};
 use bits;
 use num;
+use sample::{self, Select, SelectValueTree};
 use strategy::*;
 use test_runner::*;
 
@@ -44,6 +48,17 @@ quick_error! {
     }
 }
 
+quick_error! {
+    /// Errors which may occur when creating a `StringParam` from an alphabet.
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum AlphabetError {
+        /// The alphabet contains no characters.
+        Empty {
+            description("Alphabet is empty")
+        }
+    }
+}
+
 opaque_strategy_wrapper! {
     /// Strategy which generates values (i.e., `String` or `Vec<u8>`) matching
     /// a regular expression.
@@ -70,6 +85,11 @@ impl Strategy for str {
 ///
 /// If you don't need error handling and aren't limited by setup time, it is
 /// also possible to directly use a `&str` as a strategy with the same effect.
+///
+/// Line and text anchors (`^`, `$`, `\A` and `\z`) are ignored, which is
+/// correct when they occur at the start or end of the expression. Expressions
+/// like `a^b` which can never match are not detected and still generate
+/// strings. Word boundary tests are not supported.
 pub fn string_regex(regex: &str)
                     -> Result<RegexGeneratorStrategy<String>, Error> {
     string_regex_parsed(&rs::Expr::parse(regex)?)
@@ -213,11 +233,11 @@ pub fn bytes_regex_parsed(expr: &rs::Expr)
             Ok(Union::new(subs).sboxed())
         },
 
+        // Anchors generate nothing; see `string_regex()`.
         StartLine |
         EndLine |
         StartText |
-        EndText => Err(Error::UnsupportedRegex(
-            "line/text anchors not supported for string generation")),
+        EndText => Ok(Just(vec![]).sboxed()),
 
         WordBoundary |
         NotWordBoundary |
@@ -227,6 +247,206 @@ pub fn bytes_regex_parsed(expr: &rs::Expr)
     }.map(RegexGeneratorStrategy)
 }
 
+mapfn! {
+    [] fn CharsToString[](chars: Vec<char>) -> String {
+        chars.into_iter().collect()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `String`s whose characters are generated by
+    /// another strategy.
+    ///
+    /// Created by the `string_with()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct StringWith[<S>][where S : Strategy,
+                               S::Value : ValueTree<Value = char>]
+        (statics::Map<VecStrategy<S>, CharsToString>)
+        -> StringWithValueTree<S::Value>;
+    /// `ValueTree` corresponding to `StringWith`.
+    #[derive(Clone, Debug)]
+    pub struct StringWithValueTree[<T>][where T : ValueTree<Value = char>]
+        (statics::Map<VecValueTree<T>, CharsToString>) -> String;
+}
+
+/// Creates a strategy which generates `String`s of `length` characters, each
+/// of which is generated by `chars`.
+///
+/// The length is measured in `char`s, not bytes. Values shrink by first
+/// removing characters and then by simplifying the characters that remain.
+///
+/// ```
+/// use proptest::char;
+/// use proptest::string::string_with;
+///
+/// let words = string_with(1..10, char::range('a', 'z'));
+/// # let _ = words;
+/// ```
+pub fn string_with<S>(length: impl Into<SizeRange>, chars: S) -> StringWith<S>
+where S : Strategy, S::Value : ValueTree<Value = char> {
+    StringWith(statics::Map::new(collection::vec(chars, length),
+                                 CharsToString))
+}
+
+/// A Unicode normalization form, as defined by
+/// [UAX #15](https://unicode.org/reports/tr15/).
+///
+/// This is only available with the `unicode-normalization` feature.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// Canonical decomposition followed by canonical composition.
+    Nfc,
+    /// Canonical decomposition.
+    Nfd,
+    /// Compatibility decomposition followed by canonical composition.
+    Nfkc,
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug)]
+struct Normalize(NormalizationForm);
+
+#[cfg(feature = "unicode-normalization")]
+impl statics::MapFn<String> for Normalize {
+    type Output = String;
+    fn apply(&self, s: String) -> String {
+        match self.0 {
+            NormalizationForm::Nfc => s.nfc().collect(),
+            NormalizationForm::Nfd => s.nfd().collect(),
+            NormalizationForm::Nfkc => s.nfkc().collect(),
+            NormalizationForm::Nfkd => s.nfkd().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+opaque_strategy_wrapper! {
+    /// Strategy which generates `String`s in a particular Unicode
+    /// normalization form.
+    ///
+    /// Created by the `unicode_normalized()` function in the same module.
+    #[derive(Clone, Debug)]
+    pub struct UnicodeNormalized[][](
+        statics::Map<StringWith<char::CharStrategy<'static>>, Normalize>)
+        -> UnicodeNormalizedValueTree;
+    /// `ValueTree` corresponding to `UnicodeNormalized`.
+    #[derive(Clone, Debug)]
+    pub struct UnicodeNormalizedValueTree[][](
+        statics::Map<StringWithValueTree<char::CharValueTree>, Normalize>)
+        -> String;
+}
+
+/// Creates a strategy which generates `String`s of arbitrary characters,
+/// normalized to `form`.
+///
+/// `length` is the number of characters generated before normalization;
+/// since normalization may compose or decompose characters, the final string
+/// can be shorter or longer than this. As with `string_with()`, values shrink
+/// by first removing characters and then by simplifying those that remain,
+/// and every shrunk value is normalized as well.
+///
+/// This is only available with the `unicode-normalization` feature.
+///
+/// ```
+/// use proptest::string::{unicode_normalized, NormalizationForm};
+///
+/// let nfc = unicode_normalized(NormalizationForm::Nfc, 0..16);
+/// # let _ = nfc;
+/// ```
+#[cfg(feature = "unicode-normalization")]
+pub fn unicode_normalized(form: NormalizationForm,
+                          length: impl Into<SizeRange>)
+                          -> UnicodeNormalized {
+    UnicodeNormalized(statics::Map::new(string_with(length, char::any()),
+                                        Normalize(form)))
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy which generates `String`s whose characters are drawn
+    /// uniformly from a fixed alphabet.
+    ///
+    /// Created by `StringParam::from_alphabet()` and by the `alphanumeric()`,
+    /// `alphabetic()`, `digits()` and `hex_string()` functions in the same
+    /// module.
+    #[derive(Clone, Debug)]
+    pub struct StringParam[][](StringWith<Select<char>>)
+        -> StringParamValueTree;
+    /// `ValueTree` corresponding to `StringParam`.
+    #[derive(Clone, Debug)]
+    pub struct StringParamValueTree[][](
+        StringWithValueTree<SelectValueTree<char>>) -> String;
+}
+
+impl StringParam {
+    /// Creates a strategy which generates `String`s of `length` characters,
+    /// each chosen uniformly from the characters of `alphabet`.
+    ///
+    /// Duplicate characters in `alphabet` are ignored, so they do not make
+    /// any character more likely. Values shrink by first removing characters
+    /// and then by moving the remaining ones towards the first character of
+    /// `alphabet`.
+    ///
+    /// This is simpler and faster than an equivalent regex such as
+    /// `"[abc]{1,8}"`.
+    ///
+    /// Returns `Err` if `alphabet` is empty.
+    ///
+    /// ```
+    /// use proptest::string::StringParam;
+    ///
+    /// let dna = StringParam::from_alphabet("ACGT", 1..100).unwrap();
+    /// # let _ = dna;
+    /// ```
+    pub fn from_alphabet(alphabet: &str, length: impl Into<SizeRange>)
+                         -> Result<Self, AlphabetError> {
+        let mut chars = Vec::<char>::new();
+        for ch in alphabet.chars() {
+            if !chars.contains(&ch) {
+                chars.push(ch);
+            }
+        }
+
+        if chars.is_empty() {
+            Err(AlphabetError::Empty)
+        } else {
+            Ok(StringParam(string_with(length, sample::select(chars))))
+        }
+    }
+}
+
+/// Creates a strategy which generates `String`s of `length` ASCII letters and
+/// digits, shrinking towards `a`.
+pub fn alphanumeric(length: impl Into<SizeRange>) -> StringParam {
+    StringParam::from_alphabet(
+        "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789",
+        length).expect("alphabet is not empty")
+}
+
+/// Creates a strategy which generates `String`s of `length` ASCII letters,
+/// shrinking towards `a`.
+pub fn alphabetic(length: impl Into<SizeRange>) -> StringParam {
+    StringParam::from_alphabet(
+        "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ",
+        length).expect("alphabet is not empty")
+}
+
+/// Creates a strategy which generates `String`s of `length` ASCII decimal
+/// digits, shrinking towards `0`.
+pub fn digits(length: impl Into<SizeRange>) -> StringParam {
+    StringParam::from_alphabet("0123456789", length)
+        .expect("alphabet is not empty")
+}
+
+/// Creates a strategy which generates `String`s of `length` lowercase
+/// hexadecimal digits, shrinking towards `0`.
+pub fn hex_string(length: impl Into<SizeRange>) -> StringParam {
+    StringParam::from_alphabet("0123456789abcdef", length)
+        .expect("alphabet is not empty")
+}
+
 fn flip_case_to_bytes(flip: bool, ch: char) -> Vec<u8> {
     if flip && ch.is_uppercase() {
         ch.to_lowercase().collect::<String>().into_bytes()
@@ -295,6 +515,13 @@ mod test {
                  generated {}", max_distinct, generated.len());
     }
 
+    #[test]
+    fn test_anchors() {
+        do_test("^foo$", 1, 1, 8);
+        do_test("\\Afoo|bar\\z", 2, 2, 64);
+        do_test("(?m)^[ab]$", 2, 2, 64);
+    }
+
     #[test]
     fn test_literal() {
         do_test("foo", 1, 1, 8);
@@ -360,6 +587,99 @@ mod test {
         do_test("(?s).", 200, 65536, 256);
     }
 
+    #[test]
+    fn string_with_obeys_length_and_chars() {
+        let input = string_with(2..5, char::range('a', 'c'));
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            loop {
+                let s = case.current();
+                assert!(s.chars().count() >= 2 && s.chars().count() < 5,
+                        "Bad length: {:?}", s);
+                assert!(s.chars().all(|c| c >= 'a' && c <= 'c'),
+                        "Bad chars: {:?}", s);
+                if !case.simplify() { break; }
+            }
+            assert_eq!("aa", case.current());
+        }
+    }
+
+    #[test]
+    fn string_with_sanity() {
+        check_strategy_sanity(string_with(0..8, char::any()), None);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn unicode_normalized_values_stay_normalized_while_shrinking() {
+        use unicode_normalization::{is_nfc, is_nfd, is_nfkc, is_nfkd};
+
+        let forms: [(NormalizationForm, fn(&str) -> bool); 4] = [
+            (NormalizationForm::Nfc, is_nfc),
+            (NormalizationForm::Nfd, is_nfd),
+            (NormalizationForm::Nfkc, is_nfkc),
+            (NormalizationForm::Nfkd, is_nfkd),
+        ];
+        let mut runner = TestRunner::default();
+
+        for &(form, is_normalized) in &forms {
+            let input = unicode_normalized(form, 1..8);
+            for _ in 0..64 {
+                let mut case = input.new_value(&mut runner).unwrap();
+                loop {
+                    let s = case.current();
+                    assert!(is_normalized(&s), "{:?} not {:?}", s, form);
+                    if !case.simplify() { break; }
+                }
+                assert_eq!(1, case.current().chars().count());
+            }
+        }
+    }
+
+    #[test]
+    fn from_alphabet_dedups_and_shrinks_to_first_char() {
+        let input = StringParam::from_alphabet("xyzzyx", 2..5).unwrap();
+        let mut runner = TestRunner::default();
+        let mut counts = [0; 3];
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            for ch in case.current().chars() {
+                counts[(ch as usize) - ('x' as usize)] += 1;
+            }
+            loop {
+                let s = case.current();
+                assert!(s.chars().count() >= 2 && s.chars().count() < 5,
+                        "Bad length: {:?}", s);
+                if !case.simplify() { break; }
+            }
+            assert_eq!("xx", case.current());
+        }
+
+        assert!(counts.iter().all(|&n| n > 150 && n < 350),
+                "Bad counts: {:?}", counts);
+        assert_eq!(Some(AlphabetError::Empty),
+                   StringParam::from_alphabet("", 0..4).err());
+    }
+
+    #[test]
+    fn alphabet_functions_use_their_alphabets() {
+        let mut runner = TestRunner::default();
+        for _ in 0..64 {
+            let s = alphanumeric(8).new_value(&mut runner).unwrap().current();
+            assert!(s.chars().all(|c| c.is_ascii_alphanumeric()), "{}", s);
+            let s = alphabetic(8).new_value(&mut runner).unwrap().current();
+            assert!(s.chars().all(|c| c.is_ascii_alphabetic()), "{}", s);
+            let s = digits(8).new_value(&mut runner).unwrap().current();
+            assert!(s.chars().all(|c| c.is_ascii_digit()), "{}", s);
+            let s = hex_string(8).new_value(&mut runner).unwrap().current();
+            assert!(s.chars().all(|c| c.is_digit(16) && !c.is_uppercase()),
+                    "{}", s);
+        }
+    }
+
     fn assert_send_and_sync<T : Send + Sync>(_: T) { }
 
     #[test]
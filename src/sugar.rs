@@ -7,8 +7,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cmp;
 use std::fmt;
 
+use std::path::Path;
+
+use strategy::{Strategy, ValueFor};
+use test_runner::{panic_guard, Config, TestCaseError, TestCaseResult,
+                  TestRunner};
+
 /// Easily define `proptest` tests.
 ///
 /// Within `proptest!`, define one or more functions without return type
@@ -21,6 +28,11 @@ use std::fmt;
 /// strategies. Note that the inputs are borrowed from the test runner, so if
 /// they are not `Copy`, you will need to use `ref` with each parameter name.
 ///
+/// The module path and name of the test function are mixed into the seed of
+/// its random number generator, so different tests generate different
+/// inputs. With `Config::seed_from_test_name`, the seed is derived from them
+/// alone, so each test also generates the same inputs on every run.
+///
 /// Example:
 ///
 /// ```
@@ -70,45 +82,135 @@ use std::fmt;
 /// #
 /// # fn main() { test_addition(); }
 /// ```
+///
+/// The configuration may also be given as a list of `field = value` pairs,
+/// which override the corresponding fields of the default configuration.
+/// Such a list may additionally be put in a `#[proptest_config(...)]`
+/// attribute on an individual function, where it overrides the fields of the
+/// configuration of the block for that function only; it must be one of the
+/// first four attributes of the function. Misspelt field names are compile
+/// errors.
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   #![proptest_config(cases = 99, max_shrink_iters = 500)]
+///   # /*
+///   #[test]
+///   # */
+///   fn test_addition(a in 0..10, b in 0..10) {
+///     prop_assert!(a + b <= 18);
+///   }
+///
+///   # /*
+///   #[test]
+///   # */
+///   #[proptest_config(cases = 1000)]
+///   fn test_subtraction(a in 0..10, b in 0..10) {
+///     prop_assert!(a - b >= -9);
+///   }
+/// }
+/// #
+/// # fn main() { test_addition(); test_subtraction(); }
+/// ```
 #[macro_export]
 macro_rules! proptest {
+    (#![proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest! {
+            #![proptest_config($crate::test_runner::Config {
+                $($field: $value,)+
+                .. $crate::test_runner::Config::default()
+            })]
+            $($rest)*
+        }
+    };
+
     (#![proptest_config($config:expr)]
      $(
-        $(#[$meta:meta])*
+        $(#[$($attr:tt)*])*
         fn $test_name:ident($($parm:pat in $strategy:expr),+) $body:block
     )*) => {
         $(
-            $(#[$meta])*
-            fn $test_name() {
-                let mut runner = $crate::test_runner::TestRunner::new(
-                    $config.clone());
-                runner.set_source_file(::std::path::Path::new(file!()));
-                let names = proptest_helper!(@_WRAPSTR ($($parm),*));
-                match runner.run(
-                    &$crate::strategy::Strategy::prop_map(
-                        proptest_helper!(@_WRAP ($($strategy)*)),
-                        |values| $crate::sugar::NamedArguments(names, values)),
-                    |&$crate::sugar::NamedArguments(
-                        _, proptest_helper!(@_WRAPPAT ($($parm),*)))|
-                    {
-                        $body;
-                        Ok(())
-                    })
+            proptest!(@_fn [$config] $(#[$($attr)*])*
+                      fn $test_name($($parm in $strategy),+) $body);
+        )*
+    };
+
+    (#![proptest_config($config:expr)] $($rest:tt)*) => {
+        compile_error!("expected `fn name(pattern in strategy, ...) { ... }` \
+                        in proptest!");
+    };
+
+    // A `#[proptest_config(...)]` attribute is looked for among the first
+    // four attributes of each function by separate arms, rather than by
+    // recursing over them, so that the expansion of a block only recurses a
+    // bounded number of times per function.
+    (@_fn [$config:expr]
+     #[proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest!(@_fn [$crate::test_runner::Config {
+            $($field: $value,)+
+            .. $config.clone()
+        }] $($rest)*);
+    };
+
+    (@_fn [$config:expr] #[$($a0:tt)*]
+     #[proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest!(@_fn [$config]
+                  #[proptest_config($($field = $value),+)]
+                  #[$($a0)*] $($rest)*);
+    };
+
+    (@_fn [$config:expr] #[$($a0:tt)*] #[$($a1:tt)*]
+     #[proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest!(@_fn [$config]
+                  #[proptest_config($($field = $value),+)]
+                  #[$($a0)*] #[$($a1)*] $($rest)*);
+    };
+
+    (@_fn [$config:expr] #[$($a0:tt)*] #[$($a1:tt)*] #[$($a2:tt)*]
+     #[proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest!(@_fn [$config]
+                  #[proptest_config($($field = $value),+)]
+                  #[$($a0)*] #[$($a1)*] #[$($a2)*] $($rest)*);
+    };
+
+    (@_fn [$config:expr]
+     $(#[$meta:meta])*
+     fn $test_name:ident($($parm:pat in $strategy:expr),+) $body:block) => {
+        $(#[$meta])*
+        fn $test_name() {
+            let mut runner = $crate::test_runner::TestRunner::new(
+                $config.clone());
+            runner.set_source_file(::std::path::Path::new(file!()));
+            runner.set_test_name(
+                concat!(module_path!(), "::", stringify!($test_name)));
+            let names = proptest_helper!(@_WRAPSTR ($($parm),*));
+            match runner.run(
+                &$crate::strategy::Strategy::prop_map(
+                    proptest_helper!(@_WRAP ($($strategy)*)),
+                    |values| $crate::sugar::NamedArguments(names, values)),
+                |&$crate::sugar::NamedArguments(
+                    _, proptest_helper!(@_WRAPPAT ($($parm),*)))|
                 {
-                    Ok(_) => (),
-                    Err(e) => panic!("{}\n{}", e, runner),
-                }
+                    $body;
+                    Ok(())
+                })
+            {
+                Ok(_) => (),
+                Err(e) => panic!("{}\n{}", e, runner),
             }
-        )*
+        }
     };
 
-    ($(
-        $(#[$meta:meta])*
-        fn $test_name:ident($($parm:pat in $strategy:expr),+) $body:block
-    )*) => { proptest! {
+    ($($rest:tt)*) => { proptest! {
         #![proptest_config($crate::test_runner::Config::default())]
-        $($(#[$meta])*
-          fn $test_name($($parm in $strategy),+) $body)*
+        $($rest)*
     } };
 }
 
@@ -139,6 +241,58 @@ macro_rules! prop_assume {
     };
 }
 
+/// Like `prop_assume!`, but panics instead of rejecting the input once the
+/// assumption has been rejected too many times in a row.
+///
+/// An assumption which can never hold, for example because the strategy
+/// cannot generate any input satisfying it, otherwise only makes the test
+/// fail with a "Too many global rejects" error which does not say which
+/// assumption is at fault. `prop_assume_or_abort!` instead panics with its
+/// message once its call site has rejected more than
+/// `Config::max_assume_rejects` consecutive inputs in the current test. The
+/// count starts over whenever the assumption holds. It is kept by the
+/// `TestRunner` running the test, so rejections in test cases which run in a
+/// child process (as with `Config::fork`) are not counted.
+///
+/// This is invoked the same way as `prop_assume!`.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   # /*
+///   #[test]
+///   # */
+///   fn test_even(x in 0u32..100) {
+///     prop_assume_or_abort!(x % 2 == 0, "{} is odd", x);
+///     prop_assert_eq!(0, x / 2 * 2 - x);
+///   }
+/// }
+/// #
+/// # fn main() { test_even(); }
+/// ```
+#[macro_export]
+macro_rules! prop_assume_or_abort {
+    ($expr:expr) => {
+        prop_assume_or_abort!($expr, "{}", stringify!($expr))
+    };
+
+    ($expr:expr, $fmt:tt $(, $fmt_arg:expr),*) => {
+        let held = $expr;
+        if let ::std::option::Option::Some(rejects) =
+            $crate::test_runner::check_assumption(
+                held, (file!(), line!(), column!()))
+        {
+            panic!(concat!("{}:{}:{}: assumption rejected {} times in a row, \
+                            so it is likely impossible to satisfy: ", $fmt),
+                   file!(), line!(), column!(), rejects $(, $fmt_arg)*);
+        }
+        prop_assume!(held, $fmt $(, $fmt_arg)*);
+    };
+}
+
 /// Produce a strategy which picks one of the listed choices.
 ///
 /// This is conceptually equivalent to calling `prop_union` on the first two
@@ -151,13 +305,19 @@ macro_rules! prop_assume {
 /// strategies separated by commas; this will cause value generation to pick
 /// from the strategies uniformly. The other form is to provide a weight in the
 /// form of a `u32` before each strategy, separated from the strategy with
-/// `=>`.
+/// `=>`. The two forms may be mixed, in which case strategies without an
+/// explicit weight are given a weight of 1.
 ///
 /// Note that the exact type returned by the macro varies depending on how many
 /// inputs there are. In particular, if given exactly one option, it will
 /// return it unmodified. It is not recommended to depend on the particular
 /// type produced by this macro.
 ///
+/// There is no limit on the number of options. Up to 10 options are combined
+/// with a `TupleUnion`, which needs no boxing; beyond that, every option is
+/// boxed and combined with a `Union`. If the number of options is only known
+/// at run time, construct a `Union` directly with `Union::new_weighted()`.
+///
 /// ## Example
 ///
 /// ```rust,no_run
@@ -191,6 +351,25 @@ macro_rules! prop_assume {
 /// ```
 #[macro_export]
 macro_rules! prop_oneof {
+    // Internal rules which give a weight of 1 to every unweighted option when
+    // weighted and unweighted options are mixed.
+    (@_normalise [$($done:tt)*]) => {
+        prop_oneof![$($done)*]
+    };
+    (@_normalise [$($done:tt)*] $weight:expr => $item:expr,
+     $($rest:tt)*) => {
+        prop_oneof!(@_normalise [$($done)* $weight => $item,] $($rest)*)
+    };
+    (@_normalise [$($done:tt)*] $weight:expr => $item:expr) => {
+        prop_oneof!(@_normalise [$($done)* $weight => $item,])
+    };
+    (@_normalise [$($done:tt)*] $item:expr, $($rest:tt)*) => {
+        prop_oneof!(@_normalise [$($done)* 1 => $item,] $($rest)*)
+    };
+    (@_normalise [$($done:tt)*] $item:expr) => {
+        prop_oneof!(@_normalise [$($done)* 1 => $item,])
+    };
+
     ($($item:expr),+ $(,)*) => {
         prop_oneof![
             $(1 => $item),*
@@ -314,6 +493,10 @@ macro_rules! prop_oneof {
             $(($weight, $crate::strategy::Strategy::boxed($item))),*
         ])
     };
+
+    ($($mixed:tt)+) => {
+        prop_oneof!(@_normalise [] $($mixed)+)
+    };
 }
 
 /// Convenience to define functions which produce new strategies.
@@ -325,7 +508,8 @@ macro_rules! prop_oneof {
 /// used to generate the other inputs for the function. The second argument
 /// list has access to all arguments in the first. The return type indicates
 /// the type of value being generated; the final return type of the function is
-/// `BoxedStrategy<$type>`.
+/// `impl Strategy<Value = impl ValueTree<Value = $type>>`. Call `.boxed()` on
+/// the result if a nameable type is needed.
 ///
 /// ```rust,no_run
 /// # #![allow(dead_code)]
@@ -396,6 +580,30 @@ macro_rules! prop_oneof {
 /// The second form is sugar around making a strategy tuple, calling
 /// `prop_flat_map()`, then `prop_map()`.
 ///
+/// Alternatively, a single argument list may contain `let` bindings, which
+/// can refer to any values generated before them. Arguments after a `let` may
+/// then use the bound strategies, so a strategy can depend on an earlier value
+/// without a separate argument list or passing values through `Just`.
+///
+/// ```rust,no_run
+/// # #![allow(dead_code)]
+/// #[macro_use] extern crate proptest;
+///
+/// prop_compose! {
+///   fn ordered_pair()(a in 0i32..100, let b_strat = a+1..200, b in b_strat)
+///                   -> (i32, i32) {
+///     (a, b)
+///   }
+/// }
+/// #
+/// # fn main() { }
+/// ```
+///
+/// Each `let` after a generated value is sugar around `prop_flat_map()`, so
+/// any number of them may be chained. The values generated before a `let` are
+/// carried along with `Just`, so they must implement `Clone`. The bindings
+/// themselves are not visible in the body of the function.
+///
 /// To give the function a visibility or unsafe modifier, put it in brackets
 /// before the `fn` token.
 ///
@@ -413,6 +621,26 @@ macro_rules! prop_oneof {
 /// # fn main() { }
 /// ```
 ///
+/// The function may also be generic. Generic parameters and their bounds go
+/// between the function name and the first argument list, as usual.
+///
+/// ```rust,no_run
+/// # #![allow(dead_code)]
+/// #[macro_use] extern crate proptest;
+/// use std::fmt::Debug;
+/// use proptest::prelude::*;
+///
+/// prop_compose! {
+///   fn vec_and_element<T : Clone + Debug>(values: Vec<T>)
+///                     (index in 0..values.len(), values in Just(values))
+///                     -> (Vec<T>, T) {
+///     let element = values[index].clone();
+///     (values, element)
+///   }
+/// }
+/// # fn main() { }
+/// ```
+///
 /// ## Comparison with Hypothesis' `@composite`
 ///
 /// `prop_compose!` makes it easy to do a lot of things you can do with
@@ -422,27 +650,150 @@ macro_rules! prop_oneof {
 /// - You can't filter via this macro. For filtering, you need to make the
 /// strategy the "normal" way and use `prop_filter()`.
 ///
-/// - More than two layers of strategies or arbitrary logic between the two
-/// layers. If you need either of these, you can achieve them by calling
+/// - Arbitrary logic between the layers of strategies, beyond what fits in a
+/// `let` binding. If you need this, you can achieve it by calling
 /// `prop_flat_map()` by hand.
 #[macro_export]
 macro_rules! prop_compose {
-    ($(#[$meta:meta])*
-     $([$($vis:tt)*])* fn $name:ident $params:tt
+    // Internal rules which collect the tokens of a generic parameter list up
+    // to its closing `>`; the second bracket tracks how many `<`s inside the
+    // list are still open.
+    (@_generics $head:tt [$($generics:tt)*] [] > $($rest:tt)*) => {
+        prop_compose!(@_body $head [$($generics)*] $($rest)*);
+    };
+    (@_generics $head:tt [$($generics:tt)*] [] >> $($rest:tt)*) => {
+        compile_error!("Generic parameter list closed twice")
+    };
+    (@_generics $head:tt [$($generics:tt)*] [o] >> $($rest:tt)*) => {
+        prop_compose!(@_body $head [$($generics)* >] $($rest)*);
+    };
+    (@_generics $head:tt [$($generics:tt)*] [o o $($open:tt)*]
+     >> $($rest:tt)*) => {
+        prop_compose!(@_generics $head [$($generics)* >>] [$($open)*]
+                      $($rest)*);
+    };
+    (@_generics $head:tt [$($generics:tt)*] [o $($open:tt)*]
+     > $($rest:tt)*) => {
+        prop_compose!(@_generics $head [$($generics)* >] [$($open)*]
+                      $($rest)*);
+    };
+    (@_generics $head:tt [$($generics:tt)*] [$($open:tt)*]
+     < $($rest:tt)*) => {
+        prop_compose!(@_generics $head [$($generics)* <] [o $($open)*]
+                      $($rest)*);
+    };
+    (@_generics $head:tt [$($generics:tt)*] [$($open:tt)*]
+     $token:tt $($rest:tt)*) => {
+        prop_compose!(@_generics $head [$($generics)* $token] [$($open)*]
+                      $($rest)*);
+    };
+
+    // The single argument list form is handled by the `@_let` rules below
+    // rather than matching `pat in expr` here, since a `let` where a pattern
+    // is expected is a hard error rather than a mismatch.
+    (@_body [$($head:tt)*] [$($generics:tt)*] $params:tt
+     ($($items:tt)+)
+       -> $return_type:ty $body:block) =>
+    {
+        $($head)* <$($generics)*> $params
+                 -> impl $crate::strategy::Strategy<
+                     Value = impl $crate::strategy::ValueTree<
+                         Value = $return_type>> {
+            prop_compose!(@_let $body [] [] [] $($items)+ ,)
+        }
+    };
+
+    (@_body [$($head:tt)*] [$($generics:tt)*] $params:tt
      ($($var:pat in $strategy:expr),+ $(,)*)
+     ($($var2:pat in $strategy2:expr),+ $(,)*)
        -> $return_type:ty $body:block) =>
     {
-        $(#[$meta])*
-        $($($vis)*)* fn $name $params
-                 -> $crate::strategy::BoxedStrategy<$return_type> {
+        $($head)* <$($generics)*> $params
+                 -> impl $crate::strategy::Strategy<
+                     Value = impl $crate::strategy::ValueTree<
+                         Value = $return_type>> {
             let strat = proptest_helper!(@_WRAP ($($strategy)*));
-            let strat = $crate::strategy::Strategy::prop_map(
+            let strat = $crate::strategy::Strategy::prop_flat_map(
+                strat,
+                |proptest_helper!(@_WRAPPAT ($($var),*))|
+                proptest_helper!(@_WRAP ($($strategy2)*)));
+            $crate::strategy::Strategy::prop_map(
                 strat,
-                |proptest_helper!(@_WRAPPAT ($($var),*))| $body);
-            $crate::strategy::Strategy::boxed(strat)
+                |proptest_helper!(@_WRAPPAT ($($var2),*))| $body)
         }
     };
 
+    // Internal rules which split an argument list containing `let` bindings
+    // into segments, each being the `let` bindings before a run of `in`
+    // arguments, and the `in` arguments themselves.
+    (@_let $body:tt $segs:tt $lets:tt $ins:tt , $($rest:tt)*) => {
+        prop_compose!(@_let $body $segs $lets $ins $($rest)*)
+    };
+    (@_let $body:tt $segs:tt [$($lets:tt)*] []
+     let $var:pat = $value:expr, $($rest:tt)*) => {
+        prop_compose!(@_let $body $segs [$($lets)* ($var) = ($value)] []
+                      $($rest)*)
+    };
+    (@_let $body:tt [$($segs:tt)*] [$($lets:tt)*] [$($ins:tt)+]
+     let $var:pat = $value:expr, $($rest:tt)*) => {
+        prop_compose!(@_let $body [$($segs)* {[$($lets)*] [$($ins)+]}]
+                      [($var) = ($value)] [] $($rest)*)
+    };
+    (@_let $body:tt $segs:tt $lets:tt [$($ins:tt)*]
+     $var:pat in $strategy:expr, $($rest:tt)*) => {
+        prop_compose!(@_let $body $segs $lets [$($ins)* ($var) in ($strategy)]
+                      $($rest)*)
+    };
+    (@_let $body:tt [$($segs:tt)*] [$($lets:tt)*] [$($ins:tt)+]) => {
+        prop_compose!(@_chain_start $body $($segs)* {[$($lets)*] [$($ins)+]})
+    };
+
+    // Internal rules which turn the segments into a strategy. Each segment
+    // after the first is generated by `prop_flat_map()` from the values of
+    // all earlier segments, which are passed along with `Just`.
+    (@_chain_start $body:tt
+     {[$(($lvar:pat) = ($lvalue:expr))*]
+      [$(($var:pat) in ($strategy:expr))+]} $($rest:tt)*) => {{
+        $(let $lvar = $lvalue;)*
+        let strat = proptest_helper!(@_WRAP ($($strategy)*));
+        prop_compose!(@_chain strat [proptest_helper!(@_WRAPPAT ($($var),*))]
+                      $body $($rest)*)
+    }};
+    (@_chain $strat:ident [$($pat:tt)*] $body:tt) => {
+        $crate::strategy::Strategy::prop_map($strat, move |$($pat)*| $body)
+    };
+    (@_chain $strat:ident [$($pat:tt)*] $body:tt
+     {[$(($lvar:pat) = ($lvalue:expr))*]
+      [$(($var:pat) in ($strategy:expr))+]} $($rest:tt)*) => {{
+        let $strat = $crate::strategy::Strategy::prop_flat_map(
+            $strat, move |values| {
+                #[allow(unused_variables)]
+                let $($pat)* = ::std::clone::Clone::clone(&values);
+                $(let $lvar = $lvalue;)*
+                ($crate::strategy::Just(values),
+                 proptest_helper!(@_WRAP ($($strategy)*)))
+            });
+        prop_compose!(@_chain $strat
+                      [($($pat)*, proptest_helper!(@_WRAPPAT ($($var),*)))]
+                      $body $($rest)*)
+    }};
+
+    ($(#[$meta:meta])*
+     $([$($vis:tt)*])* fn $name:ident < $($rest:tt)*) =>
+    {
+        prop_compose!(@_generics [$(#[$meta])* $($($vis)*)* fn $name] [] []
+                      $($rest)*);
+    };
+
+    ($(#[$meta:meta])*
+     $([$($vis:tt)*])* fn $name:ident $params:tt
+     ($($items:tt)+)
+       -> $return_type:ty $body:block) =>
+    {
+        prop_compose!(@_body [$(#[$meta])* $($($vis)*)* fn $name] []
+                      $params ($($items)+) -> $return_type $body);
+    };
+
     ($(#[$meta:meta])*
      $([$($vis:tt)*])* fn $name:ident $params:tt
      ($($var:pat in $strategy:expr),+ $(,)*)
@@ -451,16 +802,17 @@ macro_rules! prop_compose {
     {
         $(#[$meta])*
         $($($vis)*)* fn $name $params
-                 -> $crate::strategy::BoxedStrategy<$return_type> {
+                 -> impl $crate::strategy::Strategy<
+                     Value = impl $crate::strategy::ValueTree<
+                         Value = $return_type>> {
             let strat = proptest_helper!(@_WRAP ($($strategy)*));
             let strat = $crate::strategy::Strategy::prop_flat_map(
                 strat,
                 |proptest_helper!(@_WRAPPAT ($($var),*))|
                 proptest_helper!(@_WRAP ($($strategy2)*)));
-            let strat = $crate::strategy::Strategy::prop_map(
+            $crate::strategy::Strategy::prop_map(
                 strat,
-                |proptest_helper!(@_WRAPPAT ($($var2),*))| $body);
-            $crate::strategy::Strategy::boxed(strat)
+                |proptest_helper!(@_WRAPPAT ($($var2),*))| $body)
         }
     };
 }
@@ -478,6 +830,20 @@ macro_rules! prop_compose {
 /// `prop_assert!` instead, the only output on a failing test case is the final
 /// panic including the minimal test case.
 ///
+/// If the condition is a single comparison with `==`, `!=`, `<=` or `>=`
+/// whose operands are each at most eight token trees long (e.g., `x`, `f(x)`,
+/// `v.len() + 1` or `a[i]`) and no custom message is given, each operand is
+/// evaluated once and the failure message also shows its value, e.g.,
+///
+/// ```text
+/// assertion failed: f(x) == g(y, z) at src/lib.rs:42
+///   f(x) = 42
+///   g(y, z) = -1
+/// ```
+///
+/// Values are shown with `Debug` where the operand type implements it. The
+/// values are only formatted when the assertion fails.
+///
 /// ## Example
 ///
 /// ```
@@ -511,10 +877,6 @@ macro_rules! prop_compose {
 /// ```
 #[macro_export]
 macro_rules! prop_assert {
-    ($cond:expr) => {
-        prop_assert!($cond, concat!("assertion failed: ", stringify!($cond)))
-    };
-
     ($cond:expr, $($fmt:tt)*) => {
         if !$cond {
             let message = format!($($fmt)*);
@@ -523,6 +885,10 @@ macro_rules! prop_assert {
                 $crate::test_runner::TestCaseError::fail(message));
         }
     };
+
+    ($($cond:tt)+) => {
+        proptest_helper!(@_ASSERT_LHS ($($cond)+) $($cond)+)
+    };
 }
 
 /// Similar to `assert_eq!` from std, but returns a test failure instead of
@@ -530,6 +896,9 @@ macro_rules! prop_assert {
 ///
 /// See `prop_assert!` for a more in-depth discussion.
 ///
+/// If the values' pretty-printed `Debug` representations span multiple lines,
+/// the failure message additionally shows a line-based diff of them.
+///
 /// ## Example
 ///
 /// ```
@@ -558,23 +927,327 @@ macro_rules! prop_assert_eq {
         let left = $left;
         let right = $right;
         prop_assert!(left == right, "assertion failed: `(left == right)` \
-                                     (left: `{:?}`, right: `{:?}`)",
-                     left, right);
+                                     (left: `{:?}`, right: `{:?}`){}",
+                     left, right,
+                     $crate::sugar::multiline_diff(&left, &right));
     }};
 
     ($left:expr, $right:expr, $fmt:tt $($args:tt)*) => {{
         let left = $left;
         let right = $right;
-        prop_assert!(left == right, concat!(
-            "assertion failed: `(left == right)` \
-             (left: `{:?}`, right: `{:?}`): ", $fmt),
-                     left, right $($args)*);
+        prop_assert!(left == right, "assertion failed: `(left == right)` \
+                                     (left: `{:?}`, right: `{:?}`): {}{}",
+                     left, right, format!($fmt $($args)*),
+                     $crate::sugar::multiline_diff(&left, &right));
     }};
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! proptest_helper {
+    // Find the comparison operator of a `prop_assert!` condition among its
+    // first eight token trees. This uses one arm per operand length rather
+    // than recursing once per token, so long conditions do not approach the
+    // recursion limit; operators further in are not looked for. `&&` and
+    // `||` bind more loosely than comparisons, so conditions containing them
+    // at the top level are asserted as a whole.
+    (@_ASSERT_LHS $whole:tt $l0:tt == $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt != $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt <= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt >= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt == $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt != $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt <= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt >= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt == $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt != $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt <= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt >= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt == $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt != $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt <= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt >= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt == $($r:tt)+) =>
+        {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3 $l4] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt != $($r:tt)+) =>
+        {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3 $l4] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt <= $($r:tt)+) =>
+        {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3 $l4] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt >= $($r:tt)+) =>
+        {
+        proptest_helper!(@_ASSERT_RHS $whole [$l0 $l1 $l2 $l3 $l4] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt && $($rest:tt)*)
+        => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt || $($rest:tt)*)
+        => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt ==
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt !=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt <=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt >=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt &&
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt ||
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt ==
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt !=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt <=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt >=
+        $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt &&
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt ||
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt == $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6 $l7] == $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt != $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6 $l7] != $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt <= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6 $l7] <= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt >= $($r:tt)+) => {
+        proptest_helper!(@_ASSERT_RHS $whole
+                         [$l0 $l1 $l2 $l3 $l4 $l5 $l6 $l7] >= $($r)+)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $l0:tt $l1:tt $l2:tt $l3:tt $l4:tt $l5:tt $l6:tt
+        $l7:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_LHS $whole:tt $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    // Likewise take a right operand of up to eight token trees, as long as it
+    // is not itself combined with `&&` or `||`.
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0 $r1])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0 $r1 $r2])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt && $($rest:tt)*)
+        => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt || $($rest:tt)*)
+        => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0 $r1 $r2 $r3])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt &&
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt ||
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt )
+        => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0 $r1 $r2 $r3 $r4])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt &&
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt ||
+        $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op [$r0 $r1 $r2 $r3 $r4 $r5])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op
+                         [$r0 $r1 $r2 $r3 $r4 $r5 $r6])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt $r7:tt ) => {
+        proptest_helper!(@_ASSERT_CMP $whole $l $op
+                         [$r0 $r1 $r2 $r3 $r4 $r5 $r6 $r7])
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt $r7:tt && $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $r0:tt $r1:tt $r2:tt $r3:tt $r4:tt
+        $r5:tt $r6:tt $r7:tt || $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_RHS $whole:tt $l:tt $op:tt $($rest:tt)*) => {
+        proptest_helper!(@_ASSERT_PLAIN $whole)
+    };
+    (@_ASSERT_CMP ($($whole:tt)*) [$($l:tt)*] $op:tt [$($r:tt)*]) => {
+        match (&($($l)*), &($($r)*)) {
+            (left, right) => if !(*left $op *right) {
+                #[allow(unused_imports)]
+                use $crate::sugar::{DebugIfPossible, ViaDebug, ViaNoDebug};
+                let message = format!(
+                    "assertion failed: {} at {}:{}\n  {} = {}\n  {} = {}",
+                    stringify!($($whole)*), file!(), line!(),
+                    stringify!($($l)*), (&DebugIfPossible(left)).describe(),
+                    stringify!($($r)*), (&DebugIfPossible(right)).describe());
+                return ::std::result::Result::Err(
+                    $crate::test_runner::TestCaseError::fail(message));
+            }
+        }
+    };
+    (@_ASSERT_PLAIN ($($whole:tt)*)) => {
+        prop_assert!($($whole)*,
+                     concat!("assertion failed: ", stringify!($($whole)*)))
+    };
     (@_WRAP ($a:tt)) => { $a };
     (@_WRAP ($a0:tt $a1:tt)) => { ($a0, $a1) };
     (@_WRAP ($a0:tt $a1:tt $a2:tt)) => { ($a0, $a1, $a2) };
@@ -748,19 +1421,97 @@ named_arguments_tuple!(0 AN AV 1 BN BV 2 CN CV 3 DN DV 4 EN EV
 named_arguments_tuple!(0 AN AV 1 BN BV 2 CN CV 3 DN DV 4 EN EV
                        5 FN FV 6 GN GV 7 HN HV 8 IN IV 9 JN JV);
 
-/// Similar to `assert_ne!` from std, but returns a test failure instead of
-/// panicking if the condition fails.
-///
-/// See `prop_assert!` for a more in-depth discussion.
+/// Produce a line-based diff of the pretty-printed `Debug` representations of
+/// `left` and `right`, for use in the failure message of `prop_assert_eq!`.
 ///
-/// ## Example
-///
-/// ```
-/// #[macro_use] extern crate proptest;
-///
-/// proptest! {
-///   # /*
-///   #[test]
+/// Returns an empty string if both representations fit on one line, since the
+/// values are then easy enough to compare directly.
+#[doc(hidden)]
+pub fn multiline_diff<L : fmt::Debug, R : fmt::Debug>(left: &L, right: &R)
+                                                      -> String {
+    // Number of unchanged lines to show on either side of the difference.
+    const CONTEXT: usize = 3;
+
+    let left = format!("{:#?}", left);
+    let right = format!("{:#?}", right);
+    let left = left.lines().collect::<Vec<_>>();
+    let right = right.lines().collect::<Vec<_>>();
+    if left.len() <= 1 && right.len() <= 1 {
+        return String::new();
+    }
+
+    let prefix = left.iter().zip(&right)
+        .take_while(|&(l, r)| l == r).count();
+    let suffix = left[prefix..].iter().rev().zip(right[prefix..].iter().rev())
+        .take_while(|&(l, r)| l == r).count();
+    let context_start = prefix.saturating_sub(CONTEXT);
+    let context_end = cmp::min(left.len(), left.len() - suffix + CONTEXT);
+
+    let mut diff = "\n\ndiff (- left, + right):\n".to_owned();
+    if context_start > 0 {
+        diff.push_str("  ...\n");
+    }
+    for line in &left[context_start..prefix] {
+        diff.push_str(&format!("  {}\n", line));
+    }
+    for line in &left[prefix..left.len() - suffix] {
+        diff.push_str(&format!("- {}\n", line));
+    }
+    for line in &right[prefix..right.len() - suffix] {
+        diff.push_str(&format!("+ {}\n", line));
+    }
+    for line in &left[left.len() - suffix..context_end] {
+        diff.push_str(&format!("  {}\n", line));
+    }
+    if context_end < left.len() {
+        diff.push_str("  ...\n");
+    }
+    diff
+}
+
+/// Wrapper used by `prop_assert!` to describe operand values with `Debug`
+/// where available.
+///
+/// `(&DebugIfPossible(x)).describe()` resolves to `ViaDebug` if the type of
+/// `x` implements `Debug` and to `ViaNoDebug` otherwise.
+#[doc(hidden)]
+pub struct DebugIfPossible<'a, T : ?Sized + 'a>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaDebug {
+    fn describe(&self) -> String;
+}
+
+impl<'a, T : fmt::Debug + ?Sized> ViaDebug for DebugIfPossible<'a, T> {
+    fn describe(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaNoDebug {
+    fn describe(&self) -> String;
+}
+
+impl<'a, 'b, T : ?Sized> ViaNoDebug for &'b DebugIfPossible<'a, T> {
+    fn describe(&self) -> String {
+        "<no Debug representation>".to_owned()
+    }
+}
+
+/// Similar to `assert_ne!` from std, but returns a test failure instead of
+/// panicking if the condition fails.
+///
+/// See `prop_assert!` for a more in-depth discussion.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   # /*
+///   #[test]
 ///   # */
 ///   fn test_addition(a in 0i32..100i32, b in 1i32..100i32) {
 ///     // Use with default message
@@ -792,6 +1543,372 @@ macro_rules! prop_assert_ne {
     }};
 }
 
+/// Asserts that two values are approximately equal, returning a test failure
+/// instead of panicking if they are not.
+///
+/// The assertion passes if the absolute difference between `left` and `right`
+/// is no greater than `epsilon`. The operands may be of any type which is
+/// `Sub<Output = T> + PartialOrd + Copy + Debug`, such as `f32` or `f64`. The
+/// assertion always fails if the difference is NaN.
+///
+/// See `prop_assert!` for a more in-depth discussion.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   # /*
+///   #[test]
+///   # */
+///   fn test_sqrt(a in 0.0f64..1.0e6) {
+///     // Use with default message
+///     prop_assert_approx_eq!(a, a.sqrt() * a.sqrt(), 1e-6);
+///     // Can also provide custom message added after the common message
+///     prop_assert_approx_eq!(a, a.sqrt() * a.sqrt(), 1e-6, "a = {}", a);
+///   }
+/// }
+/// #
+/// # fn main() { test_sqrt(); }
+/// ```
+#[macro_export]
+macro_rules! prop_assert_approx_eq {
+    ($left:expr, $right:expr, $epsilon:expr) => {{
+        let left = $left;
+        let right = $right;
+        let epsilon = $epsilon;
+        let diff = if left > right { left - right } else { right - left };
+        prop_assert!(diff <= epsilon,
+                     "assertion failed: `(left ≈ right)` \
+                      (left: `{:?}`, right: `{:?}`, \
+                      difference: `{:?}`, epsilon: `{:?}`)",
+                     left, right, diff, epsilon);
+    }};
+
+    ($left:expr, $right:expr, $epsilon:expr, $fmt:tt $($args:tt)*) => {{
+        let left = $left;
+        let right = $right;
+        let epsilon = $epsilon;
+        let diff = if left > right { left - right } else { right - left };
+        prop_assert!(diff <= epsilon, concat!(
+            "assertion failed: `(left ≈ right)` \
+             (left: `{:?}`, right: `{:?}`, \
+             difference: `{:?}`, epsilon: `{:?}`): ", $fmt),
+                     left, right, diff, epsilon $($args)*);
+    }};
+}
+
+/// Asserts that a value matches a pattern, returning a test failure instead
+/// of panicking if it does not.
+///
+/// On failure, the message includes the `Debug` representation of the value
+/// and the pattern which it failed to match. As with `match`, any bindings in
+/// the pattern take the value by move unless `ref` is used.
+///
+/// See `prop_assert!` for a more in-depth discussion.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   # /*
+///   #[test]
+///   # */
+///   fn test_parse(a in 0u32..1000) {
+///     // Use with default message
+///     prop_assert_matches!(a.to_string().parse::<u32>(), Ok(_));
+///     // Can also provide custom message added after the common message
+///     prop_assert_matches!(a.checked_add(1), Some(_), "a = {}", a);
+///   }
+/// }
+/// #
+/// # fn main() { test_parse(); }
+/// ```
+#[macro_export]
+macro_rules! prop_assert_matches {
+    ($value:expr, $($pat:pat)|+) => {
+        match $value {
+            $($pat)|+ => (),
+            ref value => prop_assert!(
+                false, "assertion failed: `{:?}` does not match `{}`",
+                value, stringify!($($pat)|+)),
+        }
+    };
+
+    ($value:expr, $($pat:pat)|+, $fmt:tt $($args:tt)*) => {
+        match $value {
+            $($pat)|+ => (),
+            ref value => prop_assert!(
+                false, concat!(
+                    "assertion failed: `{:?}` does not match `{}`: ", $fmt),
+                value, stringify!($($pat)|+) $($args)*),
+        }
+    };
+}
+
+/// Records a key-value pair of context for the current test case, which is
+/// shown below the failure message if the test case fails.
+///
+/// This is useful for intermediate values, operation logs and the like which
+/// explain a failure but are not part of the generated input. It is similar
+/// to `note()` in Hypothesis.
+///
+/// This is invoked as `prop_add_context!(key, value)`. The key is formatted
+/// with `Display` and the value with `Debug`. Context is collected afresh for
+/// each test case, so only that of the reported (minimal) failing case is
+/// shown. It is available programmatically from `Reason::context()`.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// proptest! {
+///   # /*
+///   #[test]
+///   # */
+///   fn test_sum(a in 0u32..1000, b in 0u32..1000) {
+///     let sum = a + b;
+///     prop_add_context!("sum", sum);
+///     prop_assert!(sum >= a);
+///   }
+/// }
+/// #
+/// # fn main() { test_sum(); }
+/// ```
+#[macro_export]
+macro_rules! prop_add_context {
+    ($key:expr, $value:expr) => {
+        $crate::test_runner::add_context(
+            format!("{}", $key), format!("{:?}", $value))
+    };
+}
+
+/// Runs a test on every combination of values from small, fixed domains.
+///
+/// This is an exhaustive alternative to `proptest!` for parameters with only a
+/// handful of interesting values. Instead of sampling randomly, the body is run
+/// once for each element of the cartesian product of the domains, so there is
+/// no shrinking.
+///
+/// This is invoked as `prop_matrix!(a in domain_a, b in domain_b, |a, b|
+/// body)`. Each domain is an expression evaluating to an `IntoIterator` whose
+/// items are `Clone + Debug`. The closure-like body receives one value from
+/// each domain, in order, and may use `prop_assert!` and `prop_assume!`
+/// like the body of a `proptest!` test; a combination rejected with
+/// `prop_assume!` is skipped.
+///
+/// If the body fails or panics for some combination, `prop_matrix!` panics
+/// with the reason and the failing combination, e.g., `combination: a = 2, b
+/// = false`.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// # /*
+/// #[test]
+/// # */
+/// fn xor_is_commutative() {
+///     prop_matrix!(a in vec![true, false], b in vec![true, false], |a, b| {
+///         prop_assert_eq!(a ^ b, b ^ a);
+///     });
+/// }
+/// #
+/// # fn main() { xor_is_commutative(); }
+/// ```
+#[macro_export]
+macro_rules! prop_matrix {
+    ($($name:ident in $domain:expr),+, |$($parm:pat),+| $body:expr) => {{
+        let ($($name,)+) = ($(
+            ::std::iter::IntoIterator::into_iter($domain)
+                .collect::<::std::vec::Vec<_>>(),)+);
+        $crate::sugar::run_matrix(
+            &[$($name.len()),+],
+            |indices| {
+                let mut indices = indices.iter();
+                let ($($parm,)+) = (
+                    $($name[*indices.next().unwrap()].clone(),)+);
+                $body;
+                Ok(())
+            },
+            |indices| {
+                let mut indices = indices.iter();
+                let values: &[::std::string::String] = &[$(
+                    format!(concat!(stringify!($name), " = {:?}"),
+                            $name[*indices.next().unwrap()])),+];
+                values.join(", ")
+            })
+    }};
+}
+
+/// Iterator over every combination of indices into domains of the given
+/// lengths, varying the last index fastest.
+#[derive(Clone, Debug)]
+struct CartesianIndices<'a> {
+    lengths: &'a [usize],
+    next: Option<Vec<usize>>,
+}
+
+impl<'a> CartesianIndices<'a> {
+    fn new(lengths: &'a [usize]) -> Self {
+        let next = if lengths.contains(&0) {
+            None
+        } else {
+            Some(vec![0; lengths.len()])
+        };
+        CartesianIndices { lengths, next }
+    }
+}
+
+impl<'a> Iterator for CartesianIndices<'a> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+        for (ix, &len) in next.iter_mut().zip(self.lengths).rev() {
+            *ix += 1;
+            if *ix < len {
+                self.next = Some(next);
+                break;
+            }
+            *ix = 0;
+        }
+        Some(current)
+    }
+}
+
+/// Runs `test` on each combination of indices into domains of the given
+/// `lengths`, panicking with the description from `describe` of the first
+/// combination that fails. Used by `prop_matrix!`.
+#[doc(hidden)]
+pub fn run_matrix<T, D>(lengths: &[usize], test: T, describe: D)
+where T : Fn (&[usize]) -> TestCaseResult,
+      D : Fn (&[usize]) -> String {
+    for indices in CartesianIndices::new(lengths) {
+        match panic_guard(&indices, &|indices: &Vec<usize>| test(indices)) {
+            Ok(()) | Err(TestCaseError::Reject(_)) => (),
+            Err(TestCaseError::Fail(why)) =>
+                panic!("Test failed: {}\ncombination: {}{}",
+                       why, describe(&indices), why.context_lines()),
+        }
+    }
+}
+
+/// Runs a test on a fixed table of inputs, and then on random inputs like
+/// `proptest!`.
+///
+/// This combines a table of regression or edge cases with a property test in
+/// a single function. It is invoked as `proptest_table![row, row, ...;
+/// |a: A, b: B, ...| body]`, where each row is a parenthesised list of one
+/// value per parameter. The closure-like body may use `prop_assert!` and
+/// `prop_assume!` like the body of a `proptest!` test, and receives the
+/// values by value, so their types must be `Clone`.
+///
+/// The body is first run on every row in order. If it fails or panics for
+/// some row, `proptest_table!` panics with the reason, the index of the row
+/// and its values, e.g., `row 1: a = 2, b = -2`. Otherwise, it is run on
+/// random values generated by `any::<A>()`, `any::<B>()`, etc. as by
+/// `proptest!`, so each parameter type must implement `Arbitrary`. The random
+/// values are seeded from the module path and line of the invocation.
+///
+/// As with `proptest!`, the rows may be preceded by
+/// `#![proptest_config(expr)]` or `#![proptest_config(field = value, ...)]`
+/// to override the default configuration for the random inputs.
+///
+/// ## Example
+///
+/// ```
+/// #[macro_use] extern crate proptest;
+///
+/// # /*
+/// #[test]
+/// # */
+/// fn wrapping_add_is_commutative() {
+///     proptest_table![(0, 0), (i32::max_value(), 1), (-1, i32::min_value());
+///                     |a: i32, b: i32| {
+///         prop_assert_eq!(a.wrapping_add(b), b.wrapping_add(a));
+///     }];
+/// }
+/// #
+/// # fn main() { wrapping_add_is_commutative(); }
+/// ```
+#[macro_export]
+macro_rules! proptest_table {
+    (#![proptest_config($($field:ident = $value:expr),+ $(,)*)]
+     $($rest:tt)*) => {
+        proptest_table![
+            #![proptest_config($crate::test_runner::Config {
+                $($field: $value,)+
+                .. $crate::test_runner::Config::default()
+            })]
+            $($rest)*
+        ]
+    };
+
+    (#![proptest_config($config:expr)]
+     $(($($value:expr),+)),+ $(,)*;
+     |$($parm:ident : $typ:ty),+| $body:expr) => {{
+        let names = ($(stringify!($parm),)+);
+        let rows: ::std::vec::Vec<($($typ,)+)> = vec![$(($($value,)+)),+];
+        $crate::sugar::run_table(
+            $config.clone(), concat!(module_path!(), ":", line!()), file!(),
+            rows.into_iter()
+                .map(|row| $crate::sugar::NamedArguments(names, row))
+                .collect(),
+            $crate::strategy::Strategy::prop_map(
+                ($($crate::arbitrary::any::<$typ>(),)+),
+                move |values| $crate::sugar::NamedArguments(names, values)),
+            |args: &$crate::sugar::NamedArguments<_, ($($typ,)+)>| {
+                let ($($parm,)+) = args.1.clone();
+                $body;
+                Ok(())
+            })
+    }};
+
+    (#![proptest_config($config:expr)] $($rest:tt)*) => {
+        compile_error!("expected `row, ...; |name: Type, ...| body` \
+                        in proptest_table!")
+    };
+
+    ($($rest:tt)*) => {
+        proptest_table![
+            #![proptest_config($crate::test_runner::Config::default())]
+            $($rest)*
+        ]
+    };
+}
+
+/// Runs `test` on each of `rows`, panicking with the first row that fails,
+/// and then on values from `strategy` with a `TestRunner` using `config` for
+/// the test called `test_name`. Used by `proptest_table!`.
+#[doc(hidden)]
+pub fn run_table<S, F>(config: Config, test_name: &str,
+                       source_file: &'static str,
+                       rows: Vec<ValueFor<S>>, strategy: S, test: F)
+where S : Strategy, F : Fn (&ValueFor<S>) -> TestCaseResult {
+    for (ix, row) in rows.iter().enumerate() {
+        match panic_guard(row, &test) {
+            Ok(()) | Err(TestCaseError::Reject(_)) => (),
+            Err(TestCaseError::Fail(why)) =>
+                panic!("Test failed: {}\nrow {}: {:?}{}",
+                       why, ix, row, why.context_lines()),
+        }
+    }
+
+    let mut runner = TestRunner::new(config);
+    runner.set_source_file(Path::new(source_file));
+    runner.set_test_name(test_name);
+    if let Err(e) = runner.run(&strategy, test) {
+        panic!("{}\n{}", e, runner);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ::strategy::Just;
@@ -822,6 +1939,197 @@ mod test {
         }
     }
 
+    prop_compose! {
+        fn generic_pair<T : Clone + ::std::fmt::Debug + 'static>(value: T)
+                       (a in Just(value.clone()), b in Just(value))
+                       -> (T, T) {
+            (a, b)
+        }
+    }
+
+    prop_compose! {
+        /// Generic, with nested angle brackets in the bounds.
+        [pub(crate)] fn generic_sum<
+            S : ::strategy::Strategy<Value = ::num::i32::BinarySearch>
+                + 'static>(s: S)
+            (centre in s)(a in Just(centre), b in centre - 1..centre + 1)
+            -> i32 {
+            a + b
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Point(i32, i32);
+
+    impl Point {
+        prop_compose! {
+            fn arbitrary_near(centre: i32)
+                             (x in centre - 1..centre + 1,
+                              y in centre - 1..centre + 1)
+                             -> Point {
+                Point(x, y)
+            }
+        }
+    }
+
+    prop_compose! {
+        fn generic_non_static<T : Clone + ::std::fmt::Debug>(value: T)
+                             (a in Just(value)) -> T {
+            a
+        }
+    }
+
+    #[test]
+    fn prop_compose_in_impl_returns_impl_strategy() {
+        use strategy::*;
+        use test_runner::*;
+
+        let mut runner = TestRunner::default();
+        let mut case = Point::arbitrary_near(5).new_value(&mut runner)
+            .unwrap();
+        let Point(x, y) = case.current();
+        assert!(x >= 4 && x < 6 && y >= 4 && y < 6);
+        while case.simplify() { }
+        assert_eq!(Point(4, 4), case.current());
+
+        // Boxing is still available when a nameable type is needed.
+        let _: BoxedStrategy<Point> = Point::arbitrary_near(0).boxed();
+        assert_eq!(3, generic_non_static(3).new_value(&mut runner)
+                   .unwrap().current());
+    }
+
+    #[test]
+    fn generic_prop_compose() {
+        use strategy::*;
+        use test_runner::*;
+
+        let mut runner = TestRunner::default();
+        let pair = generic_pair(vec![1u8, 2]).new_value(&mut runner)
+            .unwrap().current();
+        assert_eq!((vec![1u8, 2], vec![1u8, 2]), pair);
+
+        for _ in 0..16 {
+            let sum = generic_sum(10i32..20).new_value(&mut runner)
+                .unwrap().current();
+            assert!(sum >= 19 && sum < 40, "Bad sum: {}", sum);
+        }
+    }
+
+    prop_compose! {
+        fn ordered_pair()(a in 0i32..100, let b_strat = a + 1..200,
+                          b in b_strat) -> (i32, i32) {
+            (a, b)
+        }
+    }
+
+    prop_compose! {
+        fn chained_ranges(max: i32)
+                         (a in 0..max, let from_a = a..max, b in from_a,
+                          width in 0..3,
+                          let from_b = b..b + width + 1, c in from_b,)
+                         -> (i32, i32, i32, i32) {
+            (a, b, width, c)
+        }
+    }
+
+    prop_compose! {
+        fn repeated<T : Clone + ::std::fmt::Debug + 'static>(value: T)
+                   (len in 1usize..5,
+                    let elements = ::collection::vec(
+                        Just(value.clone()), len),
+                    v in elements) -> (usize, Vec<T>) {
+            (len, v)
+        }
+    }
+
+    #[test]
+    fn prop_compose_let_sees_earlier_values() {
+        use strategy::*;
+        use test_runner::*;
+
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = ordered_pair().new_value(&mut runner).unwrap();
+            let (a, b) = case.current();
+            assert!(a < b && b < 200, "Bad pair: {:?}", (a, b));
+            while case.simplify() { }
+            assert_eq!((0, 1), case.current());
+
+            let (a, b, width, c) = chained_ranges(50).new_value(&mut runner)
+                .unwrap().current();
+            assert!(a <= b && b < 50 && b <= c && c <= b + width,
+                    "Bad values: {:?}", (a, b, width, c));
+
+            let (len, v) = repeated('x').new_value(&mut runner)
+                .unwrap().current();
+            assert_eq!(vec!['x'; len], v);
+        }
+    }
+
+    #[test]
+    fn prop_assume_or_abort_panics_on_impossible_assumption() {
+        use test_runner::*;
+
+        let mut runner = TestRunner::new(Config {
+            max_assume_rejects: 10,
+            .. Config::default()
+        });
+        let result = runner.run(&(0u32..100), |&x| {
+            prop_assume_or_abort!(x > 1000, "{} is too small", x);
+            Ok(())
+        });
+
+        match result {
+            Err(TestError::Fail(why, _)) => {
+                // Shrinking reruns the test, rejecting yet more inputs.
+                assert!(why.message().contains(
+                    "times in a row, so it is likely impossible to \
+                     satisfy: "), "{}", why);
+                assert!(why.message().ends_with("0 is too small"),
+                        "{}", why);
+            },
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn prop_assume_or_abort_uses_config_of_run_one_runner() {
+        use test_runner::*;
+
+        let mut runner = TestRunner::new(Config {
+            max_assume_rejects: 2,
+            .. Config::default()
+        });
+        let mut run = || runner.run_one(Just(0u32), |&x| {
+            prop_assume_or_abort!(x > 0);
+            Ok(())
+        });
+
+        assert!(run().is_ok());
+        assert!(run().is_ok());
+        match run() {
+            Err(TestError::Fail(why, _)) => assert!(
+                why.message().contains("assumption rejected 3 times"),
+                "{}", why),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn prop_assume_or_abort_rejects_possible_assumption() {
+        use test_runner::*;
+
+        let mut runner = TestRunner::new(Config {
+            max_assume_rejects: 64,
+            .. Config::default()
+        });
+        let stats = runner.run(&(0u32..100), |&x| {
+            prop_assume_or_abort!(x % 3 == 0);
+            Ok(())
+        }).unwrap();
+        assert!(stats.total_rejects > 0);
+    }
+
     proptest! {
         #[test]
         fn test_something(a in 0u32..42u32, b in 1u32..10u32) {
@@ -880,6 +2188,103 @@ mod test {
         }
     }
 
+    mod test_config_fields {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering::SeqCst;
+
+        static BLOCK_CASES: AtomicUsize = AtomicUsize::new(0);
+        static FN_CASES: AtomicUsize = AtomicUsize::new(0);
+
+        proptest! {
+            #![proptest_config(cases = 7, max_shrink_iters = 0)]
+
+            fn uses_block_config(_a in 0..10) {
+                BLOCK_CASES.fetch_add(1, SeqCst);
+            }
+
+            #[allow(unused_variables)]
+            #[proptest_config(cases = 3)]
+            fn uses_fn_config(a in 0..10) {
+                FN_CASES.fetch_add(1, SeqCst);
+            }
+        }
+
+        #[test]
+        fn config_fields_override_defaults() {
+            uses_block_config();
+            uses_fn_config();
+            assert_eq!(7, BLOCK_CASES.load(SeqCst));
+            assert_eq!(3, FN_CASES.load(SeqCst));
+        }
+    }
+
+    mod test_many_fns {
+        // The expansion of a block must not recurse once per function, or a
+        // block with this many functions exceeds the recursion limit.
+        macro_rules! many_fns {
+            ($($name:ident)*) => {
+                proptest! {
+                    $(
+                        #[allow(dead_code)]
+                        fn $name(x in 0..1u8) {
+                            prop_assert_eq!(0, x);
+                        }
+                    )*
+                }
+            }
+        }
+
+        many_fns!(f00 f01 f02 f03 f04 f05 f06 f07 f08 f09 f10 f11 f12 f13
+                  f14 f15 f16 f17 f18 f19 f20 f21 f22 f23 f24 f25 f26 f27
+                  f28 f29 f30 f31 f32 f33 f34 f35 f36 f37 f38 f39 f40 f41
+                  f42 f43 f44 f45 f46 f47 f48 f49 f50 f51 f52 f53 f54 f55
+                  f56 f57 f58 f59 f60 f61 f62 f63 f64 f65 f66 f67 f68 f69);
+
+        #[test]
+        fn block_with_many_fns_expands() {
+            f00();
+            f69();
+        }
+    }
+
+    #[test]
+    fn approx_eq_respects_epsilon() {
+        use test_runner::TestCaseResult;
+
+        fn check(left: f64, right: f64) -> TestCaseResult {
+            prop_assert_approx_eq!(left, right, 0.01);
+            prop_assert_approx_eq!(left, right, 0.01, "left = {}", left);
+            Ok(())
+        }
+
+        assert!(check(1.0, 1.005).is_ok());
+        assert!(check(1.005, 1.0).is_ok());
+        assert!(check(1.0, 1.02).is_err());
+        assert!(check(1.02, 1.0).is_err());
+        assert!(check(::std::f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn assert_matches_reports_value() {
+        use test_runner::{TestCaseError, TestCaseResult};
+
+        fn check(v: Option<u32>) -> TestCaseResult {
+            prop_assert_matches!(v, Some(1..=9) | None);
+            prop_assert_matches!(v, Some(0..=9) | None, "v = {:?}", v);
+            Ok(())
+        }
+
+        assert!(check(Some(5)).is_ok());
+        assert!(check(None).is_ok());
+        match check(Some(42)) {
+            Err(TestCaseError::Fail(why)) => {
+                assert!(why.message().contains("Some(42)"), "{}", why);
+                assert!(why.message().contains("Some(1..=9)"), "{}", why);
+            },
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
     #[test]
     fn named_arguments_is_debug_for_needed_cases() {
         use super::NamedArguments;
@@ -1017,4 +2422,260 @@ mod test {
             J(10i32),
         ]));
     }
+
+    #[test]
+    fn oneof_supports_many_options() {
+        use strategy::*;
+        use test_runner::*;
+
+        let input = prop_oneof![
+            Just(0usize), Just(1usize), Just(2usize), Just(3usize),
+            Just(4usize), Just(5usize), Just(6usize), Just(7usize),
+            Just(8usize), Just(9usize), Just(10usize), Just(11usize),
+            Just(12usize), Just(13usize), Just(14usize), Just(15usize),
+            Just(16usize), Just(17usize), Just(18usize),
+            2 => Just(19usize),
+        ];
+
+        let mut seen = [false; 20];
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            seen[case.current()] = true;
+            while case.simplify() { }
+            assert_eq!(0, case.current());
+        }
+
+        assert!(seen.iter().all(|&s| s), "Not all options chosen: {:?}", seen);
+    }
+
+    #[test]
+    fn oneof_weights_bias_choice() {
+        use strategy::*;
+        use test_runner::*;
+
+        let input = prop_oneof![
+            1 => Just(0usize),
+            3 => Just(1usize),
+            Just(2usize),
+        ];
+
+        let mut counts = [0, 0, 0];
+        let mut runner = TestRunner::default();
+        for _ in 0..4096 {
+            counts[input.new_value(&mut runner).unwrap().current()] += 1;
+        }
+
+        assert!(counts[1] > counts[0] * 2, "Bad counts: {:?}", counts);
+        assert!(counts[1] > counts[2] * 2, "Bad counts: {:?}", counts);
+    }
+
+    #[test]
+    fn assert_eq_diffs_multiline_values() {
+        fn check(left: Vec<u32>, right: Vec<u32>)
+                 -> Result<(), ::test_runner::TestCaseError> {
+            prop_assert_eq!(left, right, "with {}", "context");
+            Ok(())
+        }
+
+        let message = match check(vec![1, 2, 3, 4, 5, 6, 7, 8],
+                                  vec![1, 2, 3, 4, 50, 6, 7, 8]) {
+            Err(::test_runner::TestCaseError::Fail(why)) => why,
+            e => panic!("Unexpected result: {:?}", e),
+        };
+        let message = message.message();
+        assert!(message.contains("): with context"), "{}", message);
+        assert!(message.contains(
+            "\n      4,\n-     5,\n+     50,\n      6,\n"), "{}", message);
+        // Only the nearest 3 unchanged lines are shown.
+        assert!(!message.contains("      1,"), "{}", message);
+    }
+
+    #[test]
+    fn single_line_values_have_no_diff() {
+        assert_eq!("", ::sugar::multiline_diff(&1, &2));
+        assert!(!::sugar::multiline_diff(&[1], &[2]).is_empty());
+    }
+
+    #[test]
+    fn assert_ne_reports_values() {
+        fn check(left: i32, right: i32)
+                 -> Result<(), ::test_runner::TestCaseError> {
+            prop_assert_ne!(left, right);
+            prop_assert_ne!(left + 1, right, "off by {}", 1);
+            Ok(())
+        }
+
+        assert!(check(1, 3).is_ok());
+        match check(2, 2) {
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(why.message().starts_with(
+                    "assertion failed: `(left != right)` \
+                     (left: `2`, right: `2`)"), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        match check(1, 2) {
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(why.message().contains(
+                    "(left: `2`, right: `2`): off by 1"), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn assert_reports_operand_values() {
+        fn double(x: i32) -> i32 { x * 2 }
+        fn check(x: i32, y: &str)
+                 -> Result<(), ::test_runner::TestCaseError> {
+            prop_assert!(double(x) >= x);
+            prop_assert!(y.len() != 0 && x < 100);
+            prop_assert!(double(x) == y.len() as i32);
+            Ok(())
+        }
+
+        assert!(check(2, "four").is_ok());
+        match check(-1, "") {
+            Err(::test_runner::TestCaseError::Fail(why)) => {
+                assert!(why.message().starts_with(
+                    "assertion failed: double(x) >= x at "), "{}", why);
+                assert!(why.message().ends_with(
+                    "\n  double(x) = -2\n  x = -1"), "{}", why);
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        match check(1, "") {
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(!why.message().contains('\n'), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        match check(1, "abc") {
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(why.message().ends_with(
+                    "\n  double(x) = 2\n  y.len() as i32 = 3"), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn assert_accepts_long_conditions() {
+        fn check(x: i32) -> Result<(), ::test_runner::TestCaseError> {
+            // Far more tokens than the default recursion limit.
+            prop_assert!(x == 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9
+                         + 0 + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9);
+            Ok(())
+        }
+
+        assert!(check(585).is_ok());
+        match check(0) {
+            // The right operand is too long for the values to be shown.
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(!why.message().contains("\n  x = 0"), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn assert_accepts_operands_without_debug() {
+        #[derive(PartialEq)]
+        struct NoDebug(i32);
+
+        fn check(a: NoDebug) -> Result<(), ::test_runner::TestCaseError> {
+            prop_assert!(a == NoDebug(0));
+            prop_assert!(::std::vec::Vec::<u8>::new() <= vec![0u8]);
+            Ok(())
+        }
+
+        assert!(check(NoDebug(0)).is_ok());
+        match check(NoDebug(1)) {
+            Err(::test_runner::TestCaseError::Fail(why)) =>
+                assert!(why.message().ends_with(
+                    "\n  a = <no Debug representation>\n  \
+                     NoDebug(0) = <no Debug representation>"), "{}", why),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn cartesian_indices_cover_every_combination() {
+        let all = super::CartesianIndices::new(&[2, 3]).collect::<Vec<_>>();
+        assert_eq!(vec![vec![0, 0], vec![0, 1], vec![0, 2],
+                        vec![1, 0], vec![1, 1], vec![1, 2]], all);
+        assert_eq!(0, super::CartesianIndices::new(&[2, 0]).count());
+        assert_eq!(1, super::CartesianIndices::new(&[1]).count());
+    }
+
+    #[test]
+    fn matrix_runs_every_combination() {
+        use std::cell::RefCell;
+
+        let seen = RefCell::new(Vec::new());
+        prop_matrix!(a in 1..4, b in vec![true, false], |a, b| {
+            prop_assume!(a != 2);
+            seen.borrow_mut().push((a, b));
+        });
+        assert_eq!(vec![(1, true), (1, false), (3, true), (3, false)],
+                   seen.into_inner());
+    }
+
+    #[test]
+    #[should_panic(expected = "combination: a = 2, b = false")]
+    fn matrix_reports_failing_combination() {
+        prop_matrix!(a in 1..4, b in vec![true, false], |a, b| {
+            prop_assert!(b || a != 2);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "combination: s = \"b\"")]
+    fn matrix_reports_panicking_combination() {
+        prop_matrix!(s in vec!["a", "b"], |s| {
+            assert_eq!("a", s);
+        });
+    }
+
+    #[test]
+    fn table_runs_rows_then_random_values() {
+        use std::cell::RefCell;
+
+        let seen = RefCell::new(Vec::new());
+        proptest_table![(1, true), (2, false), (3, true);
+                        |a: u8, b: bool| {
+            prop_assume!(a != 2);
+            seen.borrow_mut().push((a, b));
+        }];
+
+        let seen = seen.into_inner();
+        assert_eq!(&[(1, true), (3, true)], &seen[..2]);
+        assert!(seen.len() > 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1: a = 2, b = -2")]
+    fn table_reports_failing_row() {
+        proptest_table![(1, 1), (2, -2), (3, -3); |a: i32, b: i32| {
+            prop_assert!(a + b != 0 || a == 1);
+        }];
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal failing input: x = 1000")]
+    fn table_reports_failing_random_input() {
+        proptest_table![
+            #![proptest_config(
+                failure_persistence = ::test_runner::FailurePersistence::Off)]
+            (0); |x: u32| {
+                prop_assert!(x < 1000);
+            }];
+    }
 }
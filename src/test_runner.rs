@@ -13,70 +13,60 @@
 //! when implementing new low-level strategies.
 
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::ffi::OsString;
 use std::fmt;
 use std::fs;
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, Read, Write};
 use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::SeqCst;
+use std::time::{Duration, Instant};
 
 use rand::{self, Rand, SeedableRng, XorShiftRng};
 
 use strategy::*;
 
-/// The default config, computed by combining environment variables and
-/// defaults.
+fn builtin_default_config() -> Config {
+    Config {
+        cases: 256,
+        max_local_rejects: 65536,
+        max_total_local_rejects: 1_048_576,
+        max_global_rejects: 1024,
+        max_assume_rejects: 256,
+        max_flat_map_regens: 1_000_000,
+        failure_persistence: FailurePersistence::default(),
+        max_shrink_iters: 65536,
+        max_shrink_time: None,
+        #[cfg(feature = "timeout")]
+        case_timeout: None,
+        #[cfg(all(feature = "fork", unix))]
+        fork: false,
+        reject_warning_ratio: 0.9,
+        print_stats: false,
+        verbose: true,
+        seed_from_test_name: false,
+        json_report_file: None,
+        json_report_always: false,
+        _non_exhaustive: (),
+    }
+}
+
 lazy_static! {
+    /// The default config, computed by combining environment variables and
+    /// defaults.
+    ///
+    /// Invalid environment variables are reported and then ignored.
     static ref DEFAULT_CONFIG: Config = {
-        let mut result = Config {
-            cases: 256,
-            max_local_rejects: 65536,
-            max_global_rejects: 1024,
-            max_flat_map_regens: 1_000_000,
-            failure_persistence: FailurePersistence::default(),
-            _non_exhaustive: (),
-        };
-
-        fn parse_or_warn(dst: &mut u32, value: OsString, var: &str) {
-            if let Some(value) = value.to_str() {
-                if let Ok(value) = value.parse() {
-                    *dst = value;
-                } else {
-                    eprintln!(
-                        "proptest: The env-var {}={} can't be parsed as u32, \
-                         using default of {}.", var, value, *dst);
-                }
-            } else {
-                eprintln!(
-                    "proptest: The env-var {} is not valid, using \
-                     default of {}.", var, *dst);
-            }
-        }
+        let mut result = builtin_default_config();
 
         for (var, value) in env::vars_os() {
-            if let Some(var) = var.to_str() {
-                match var {
-                    "PROPTEST_CASES" => parse_or_warn(
-                        &mut result.cases, value, "PROPTEST_CASES"),
-                    "PROPTEST_MAX_LOCAL_REJECTS" => parse_or_warn(
-                        &mut result.max_local_rejects, value,
-                        "PROPTEST_MAX_LOCAL_REJECTS"),
-                    "PROPTEST_MAX_GLOBAL_REJECTS" => parse_or_warn(
-                        &mut result.max_global_rejects, value,
-                        "PROPTEST_MAX_GLOBAL_REJECTS"),
-                    "PROPTEST_MAX_FLAT_MAP_REGENS" => parse_or_warn(
-                        &mut result.max_flat_map_regens, value,
-                        "PROPTEST_MAX_FLAT_MAP_REGENS"),
-                    _ => if var.starts_with("PROPTEST_") {
-                        eprintln!("proptest: Ignoring unknown env-var {}.",
-                                  var);
-                    },
-                }
+            if let Err(e) = result.merge_env_var(var, value) {
+                eprintln!("proptest: {}; ignoring it.", e);
             }
         }
 
@@ -84,6 +74,103 @@ lazy_static! {
     };
 }
 
+quick_error! {
+    /// Errors which may occur when reading a `Config` from the environment.
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum EnvConfigError {
+        /// The named environment variable does not contain valid Unicode.
+        NotUnicode(var: String) {
+            description("Environment variable is not valid Unicode")
+            display("The env-var {} is not valid Unicode", var)
+        }
+        /// The named environment variable has a value which could not be
+        /// parsed.
+        InvalidValue(var: String, value: String) {
+            description("Environment variable has an invalid value")
+            display("The env-var {}={} can't be parsed", var, value)
+        }
+    }
+}
+
+quick_error! {
+    /// Errors which may occur when reading a `Config` from a file.
+    #[derive(Debug)]
+    pub enum FileConfigError {
+        /// The file at the given path could not be read.
+        Io(path: PathBuf, err: io::Error) {
+            description("Config file could not be read")
+            display("Failed to read {}: {}", path.display(), err)
+            cause(err)
+        }
+        /// The given line is not a `key = value` pair with a valid value.
+        Syntax(line: usize, text: String) {
+            description("Config file has invalid syntax")
+            display("Line {} of the config file is invalid: {}", line, text)
+        }
+        /// The key on the given line is not a field which can be set.
+        UnknownField(line: usize, key: String) {
+            description("Config file sets an unknown field")
+            display("Line {} of the config file sets unknown field {}",
+                    line, key)
+        }
+        /// The value on the given line could not be parsed.
+        InvalidValue(line: usize, key: String, value: String) {
+            description("Config file has an invalid value")
+            display("Line {} of the config file sets {}={}, which can't be \
+                     parsed", line, key, value)
+        }
+    }
+}
+
+/// Parse the TOML string, integer or boolean `value`, which may be followed
+/// by a comment, into the text it represents.
+fn parse_toml_value(value: &str) -> Option<String> {
+    let quote = match value.chars().next() {
+        Some(quote) if '"' == quote || '\'' == quote => quote,
+        _ => {
+            let value = value.split('#').next().unwrap_or("").trim();
+            return if value.is_empty() {
+                None
+            } else {
+                Some(value.replace('_', ""))
+            };
+        },
+    };
+
+    let mut result = String::new();
+    let mut chars = value[1..].chars();
+    loop {
+        match chars.next()? {
+            c if c == quote => break,
+            // Only basic strings, delimited by `"`, have escapes.
+            '\\' if '"' == quote => result.push(match chars.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                't' => '\t',
+                _ => return None,
+            }),
+            c => result.push(c),
+        }
+    }
+
+    let rest = chars.as_str().trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// Find the nearest `.config/proptest.toml` or `proptest.toml` in `dir` or
+/// one of its ancestors.
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|dir| vec![dir.join(".config").join("proptest.toml"),
+                             dir.join("proptest.toml")])
+        .find(|path| path.is_file())
+}
+
 /// Configuration for how a proptest test should be run.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Config {
@@ -95,18 +182,39 @@ pub struct Config {
     /// The default is 256, which can be overridden by setting the
     /// `PROPTEST_CASES` environment variable.
     pub cases: u32,
-    /// The maximum number of individual inputs that may be rejected before the
-    /// test as a whole aborts.
+    /// The maximum number of individual inputs that any one filter (such as
+    /// `prop_filter`) may reject while generating a single test case before
+    /// the test as a whole aborts.
+    ///
+    /// Each filter is identified by its `whence` and has its own budget,
+    /// which is reset at the start of every test case, so a very selective
+    /// filter does not use up the budget of the others nor of later cases.
+    /// The total over all filters and cases is instead limited by
+    /// `max_total_local_rejects`. Rejections from `prop_assume!` are counted
+    /// against `max_global_rejects`.
     ///
     /// The default is 65536, which can be overridden by setting the
     /// `PROPTEST_MAX_LOCAL_REJECTS` environment variable.
     pub max_local_rejects: u32,
+    /// The maximum number of individual inputs that all filters together may
+    /// reject over the whole test before it aborts.
+    ///
+    /// The default is 1_048_576, which can be overridden by setting the
+    /// `PROPTEST_MAX_TOTAL_LOCAL_REJECTS` environment variable.
+    pub max_total_local_rejects: u32,
     /// The maximum number of combined inputs that may be rejected before the
     /// test as a whole aborts.
     ///
     /// The default is 1024, which can be overridden by setting the
     /// `PROPTEST_MAX_GLOBAL_REJECTS` environment variable.
     pub max_global_rejects: u32,
+    /// The maximum number of consecutive times a single
+    /// `prop_assume_or_abort!` may reject inputs within one test before it
+    /// panics, since its assumption is then likely impossible to satisfy.
+    ///
+    /// The default is 256, which can be overridden by setting the
+    /// `PROPTEST_MAX_ASSUME_REJECTS` environment variable.
+    pub max_assume_rejects: u32,
     /// The maximum number of times all `Flatten` combinators will attempt to
     /// regenerate values. This puts a limit on the worst-case exponential
     /// explosion that can happen with nested `Flatten`s.
@@ -120,9 +228,118 @@ pub struct Config {
     /// See the docs of [`FailurePersistence`](enum.FailurePersistence.html)
     /// for more information.
     ///
-    /// The default is `FailurePersistence::SourceParallel("proptest-regressions")`.
-    /// The default cannot currently be overridden by an environment variable.
+    /// The default is `FailurePersistence::SourceParallel("proptest-regressions")`,
+    /// which can be overridden by setting the `PROPTEST_FAILURE_PERSISTENCE`
+    /// environment variable as described in `Config::from_env()`.
     pub failure_persistence: FailurePersistence,
+    /// The maximum number of times the test function may be run while
+    /// shrinking a failing input. Once exhausted, the simplest failing input
+    /// found so far is reported.
+    ///
+    /// The default is 65536, which can be overridden by setting the
+    /// `PROPTEST_MAX_SHRINK_ITERS` environment variable.
+    pub max_shrink_iters: u32,
+    /// The maximum amount of time to spend shrinking a failing input, if any.
+    /// Once exhausted, the simplest failing input found so far is reported.
+    ///
+    /// The default is `None` (no limit), which can be overridden by setting
+    /// the `PROPTEST_MAX_SHRINK_TIME` environment variable to a number of
+    /// milliseconds.
+    ///
+    /// The clock is only read when this is set, so it must be left as `None`
+    /// on targets without a system clock, such as `wasm32-unknown-unknown`.
+    pub max_shrink_time: Option<Duration>,
+    /// The maximum amount of time a single run of the test function may
+    /// take, if any. A run which takes longer fails with the message
+    /// "timed out", and is then shrunk like any other failure.
+    ///
+    /// On Unix, each case runs in a forked child process (as with `fork`)
+    /// while this is set, and the child is killed once the timeout expires,
+    /// so a run which never returns (e.g., due to an infinite loop or
+    /// deadlock) does not hang the test. Elsewhere, the test function cannot
+    /// be interrupted, so the timeout only takes effect once the run
+    /// completes.
+    ///
+    /// This is only available with the `timeout` feature. The default is
+    /// `None` (no limit), which can be overridden by setting the
+    /// `PROPTEST_CASE_TIMEOUT` environment variable to a number of
+    /// milliseconds.
+    ///
+    /// As with `max_shrink_time`, the clock is only read when this is set.
+    #[cfg(feature = "timeout")]
+    pub case_timeout: Option<Duration>,
+    /// Whether to run each test case in a child process forked from the test
+    /// process, so that a case which crashes (e.g., with a segmentation fault
+    /// in unsafe or FFI code) or calls `process::exit` fails like one which
+    /// panics, and is shrunk as usual, rather than ending the whole test run.
+    ///
+    /// Inputs are still generated and shrunk in the test process; only the
+    /// test function runs in the child, which reports its result back through
+    /// a pipe. Only the thread running the test exists in the child, and any
+    /// changes the test makes to memory are lost when the child exits.
+    ///
+    /// With the `timeout` feature, a child which is still running when
+    /// `case_timeout` expires is killed, and the case fails.
+    ///
+    /// This is only available on Unix with the `fork` feature. The default is
+    /// `false`, which can be overridden by setting the `PROPTEST_FORK`
+    /// environment variable.
+    #[cfg(all(feature = "fork", unix))]
+    pub fork: bool,
+    /// If more than this fraction of the test cases a successful test ran
+    /// were rejected (e.g., by `prop_assume!`), a warning is printed
+    /// suggesting that the strategy be made to produce acceptable inputs more
+    /// often.
+    ///
+    /// The default is 0.9. Any value of 1.0 or greater disables the warning.
+    pub reject_warning_ratio: f64,
+    /// Whether to print the `Stats` of every successful test to standard
+    /// error.
+    ///
+    /// The default is `false`, which can be overridden by setting the
+    /// `PROPTEST_PRINT_STATS` environment variable to `1` or `true` (or `0`
+    /// or `false` to turn it off).
+    pub print_stats: bool,
+    /// Whether the `TestRunner` prints anything at all, i.e., warnings (such
+    /// as about failure persistence or a high rejection ratio) and the
+    /// `Stats` requested by `print_stats`.
+    ///
+    /// Output goes to standard error unless redirected with
+    /// `TestRunner::set_output()`. Failing tests are still reported by
+    /// `proptest!` panicking.
+    ///
+    /// The default is `true`, which can be overridden by setting the
+    /// `PROPTEST_VERBOSE` environment variable to `0` or `false` (or `1` or
+    /// `true` to turn it back on).
+    pub verbose: bool,
+    /// Whether a runner given a test name by `TestRunner::set_test_name()`,
+    /// as the runners of `proptest!` tests are, seeds its RNG from the name
+    /// alone, so that the test generates the same inputs on every run.
+    ///
+    /// Otherwise, the seed derived from the name is mixed with a fresh
+    /// random seed, so each run explores new inputs while different tests
+    /// still never share a seed.
+    ///
+    /// The default is `false`, which can be overridden by setting the
+    /// `PROPTEST_SEED_FROM_TEST_NAME` environment variable to `1` or `true`.
+    pub seed_from_test_name: bool,
+    /// If set, a JSON report is appended to this file whenever a test fails,
+    /// so that CI tooling can track failures without parsing test output.
+    ///
+    /// Each report is a single line holding one JSON object, so that several
+    /// tests may share the same file. See `TestRunner::run()` for its
+    /// format.
+    ///
+    /// The default is `None`, which can be overridden by setting the
+    /// `PROPTEST_JSON_REPORT_FILE` environment variable to a path.
+    pub json_report_file: Option<PathBuf>,
+    /// Whether to also append a report to `json_report_file` when a test
+    /// passes.
+    ///
+    /// The default is `false`, which can be overridden by setting the
+    /// `PROPTEST_JSON_REPORT_ALWAYS` environment variable to `1` or `true`
+    /// (or `0` or `false` to turn it off).
+    pub json_report_always: bool,
     // Needs to be public so FRU syntax can be used.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -148,6 +365,262 @@ impl Config {
             .. Config::default()
         }
     }
+
+    /// Constructs a `Config` from proptest's built-in defaults overridden by
+    /// any `PROPTEST_*` environment variables.
+    ///
+    /// Unlike `Config::default()`, which warns about and ignores invalid
+    /// environment variables, this fails if any variable cannot be parsed.
+    /// The following variables are understood:
+    ///
+    /// - `PROPTEST_CASES`, `PROPTEST_MAX_LOCAL_REJECTS`,
+    ///   `PROPTEST_MAX_TOTAL_LOCAL_REJECTS`, `PROPTEST_MAX_GLOBAL_REJECTS`,
+    ///   `PROPTEST_MAX_ASSUME_REJECTS`, `PROPTEST_MAX_FLAT_MAP_REGENS` and
+    ///   `PROPTEST_MAX_SHRINK_ITERS` set the field of the same name.
+    ///
+    /// - `PROPTEST_MAX_SHRINK_TIME` and, with the `timeout` feature,
+    ///   `PROPTEST_CASE_TIMEOUT` set `max_shrink_time` and `case_timeout`,
+    ///   respectively, to the given number of milliseconds.
+    ///
+    /// - `PROPTEST_PRINT_STATS`, `PROPTEST_VERBOSE`,
+    ///   `PROPTEST_SEED_FROM_TEST_NAME` and `PROPTEST_JSON_REPORT_ALWAYS` set
+    ///   `print_stats`, `verbose`, `seed_from_test_name` and
+    ///   `json_report_always`, respectively. They may be `1` or `true`, or
+    ///   `0` or `false`. With the `fork` feature, `PROPTEST_FORK` likewise
+    ///   sets `fork`.
+    ///
+    /// - `PROPTEST_JSON_REPORT_FILE` sets `json_report_file` to the given
+    ///   path.
+    ///
+    /// - `PROPTEST_FAILURE_PERSISTENCE` sets `failure_persistence`. It may be
+    ///   `off`, or one of `source-parallel:NAME`, `with-source:EXT` or
+    ///   `direct:PATH` for the corresponding `FailurePersistence` variant. The
+    ///   string given is leaked to satisfy the `'static` lifetime.
+    ///
+    /// Unknown variables starting with `PROPTEST_` are reported on standard
+    /// error but otherwise ignored.
+    pub fn from_env() -> Result<Self, EnvConfigError> {
+        builtin_default_config().merge_env()
+    }
+
+    /// Overrides the fields of this `Config` with any `PROPTEST_*`
+    /// environment variables.
+    ///
+    /// See `Config::from_env()` for the variables understood.
+    ///
+    /// ```
+    /// # use proptest::test_runner::Config;
+    /// // Run 1000 cases unless CI asks for something else.
+    /// let config = Config::with_cases(1000).merge_env();
+    /// # let _ = config;
+    /// ```
+    pub fn merge_env(mut self) -> Result<Self, EnvConfigError> {
+        for (var, value) in env::vars_os() {
+            self.merge_env_var(var, value)?;
+        }
+
+        Ok(self)
+    }
+
+    fn merge_env_var(&mut self, var: OsString, value: OsString)
+                     -> Result<(), EnvConfigError> {
+        let var = match var.to_str() {
+            Some(var) if var.starts_with("PROPTEST_") => var,
+            _ => return Ok(()),
+        };
+        let value = value.to_str().ok_or_else(
+            || EnvConfigError::NotUnicode(var.to_owned()))?;
+
+        if !self.merge_var(var, value)? {
+            eprintln!("proptest: Ignoring unknown env-var {}.", var);
+        }
+
+        Ok(())
+    }
+
+    /// Set the field corresponding to the env-var `var` to `value`, returning
+    /// whether `var` is known.
+    fn merge_var(&mut self, var: &str, value: &str)
+                 -> Result<bool, EnvConfigError> {
+        fn parse<T : ::std::str::FromStr>(var: &str, value: &str)
+                                           -> Result<T, EnvConfigError> {
+            value.parse().map_err(|_| EnvConfigError::InvalidValue(
+                var.to_owned(), value.to_owned()))
+        }
+
+        fn parse_bool(var: &str, value: &str) -> Result<bool, EnvConfigError> {
+            match value {
+                "1" | "true" => Ok(true),
+                "0" | "false" => Ok(false),
+                _ => Err(EnvConfigError::InvalidValue(
+                    var.to_owned(), value.to_owned())),
+            }
+        }
+
+        match var {
+            "PROPTEST_CASES" =>
+                self.cases = parse(var, value)?,
+            "PROPTEST_MAX_LOCAL_REJECTS" =>
+                self.max_local_rejects = parse(var, value)?,
+            "PROPTEST_MAX_TOTAL_LOCAL_REJECTS" =>
+                self.max_total_local_rejects = parse(var, value)?,
+            "PROPTEST_MAX_GLOBAL_REJECTS" =>
+                self.max_global_rejects = parse(var, value)?,
+            "PROPTEST_MAX_ASSUME_REJECTS" =>
+                self.max_assume_rejects = parse(var, value)?,
+            "PROPTEST_MAX_FLAT_MAP_REGENS" =>
+                self.max_flat_map_regens = parse(var, value)?,
+            "PROPTEST_MAX_SHRINK_ITERS" =>
+                self.max_shrink_iters = parse(var, value)?,
+            "PROPTEST_MAX_SHRINK_TIME" =>
+                self.max_shrink_time = Some(Duration::from_millis(
+                    parse(var, value)?)),
+            "PROPTEST_PRINT_STATS" =>
+                self.print_stats = parse_bool(var, value)?,
+            "PROPTEST_VERBOSE" =>
+                self.verbose = parse_bool(var, value)?,
+            "PROPTEST_SEED_FROM_TEST_NAME" =>
+                self.seed_from_test_name = parse_bool(var, value)?,
+            "PROPTEST_JSON_REPORT_FILE" =>
+                self.json_report_file = Some(PathBuf::from(value)),
+            "PROPTEST_JSON_REPORT_ALWAYS" =>
+                self.json_report_always = parse_bool(var, value)?,
+            #[cfg(feature = "timeout")]
+            "PROPTEST_CASE_TIMEOUT" =>
+                self.case_timeout = Some(Duration::from_millis(
+                    parse(var, value)?)),
+            #[cfg(all(feature = "fork", unix))]
+            "PROPTEST_FORK" =>
+                self.fork = parse_bool(var, value)?,
+            "PROPTEST_FAILURE_PERSISTENCE" =>
+                self.failure_persistence = FailurePersistence::parse(value)
+                .ok_or_else(|| EnvConfigError::InvalidValue(
+                    var.to_owned(), value.to_owned()))?,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Constructs a `Config` from proptest's built-in defaults overridden by
+    /// the settings in the TOML file at `path`.
+    ///
+    /// Each setting is a `key = value` line, where the key names a field of
+    /// `Config` which can be set by one of the environment variables listed
+    /// for `Config::from_env()`, and the value is a TOML string, integer or
+    /// boolean which is understood as for that variable. For example:
+    ///
+    /// ```toml
+    /// # Run more cases, but shrink each failure for at most ten seconds.
+    /// cases = 1000
+    /// max_shrink_time = 10_000
+    /// failure_persistence = "direct:proptest-regressions.txt"
+    /// ```
+    ///
+    /// The settings may be put under a `[proptest]` table header, so that
+    /// the file can follow the layout of other tools' configuration.
+    ///
+    /// Proptest parses the file itself rather than depending on the `toml`
+    /// and `serde` crates, so only this restricted subset of TOML is
+    /// supported: any other table header is a syntax error, and dotted keys,
+    /// arrays, inline tables and multi-line strings are not understood.
+    /// Environment variables are not consulted, but can be made to take
+    /// precedence by calling `merge_env()` on the result.
+    pub fn from_file<P : AsRef<Path>>(path: P)
+                                      -> Result<Self, FileConfigError> {
+        let path = path.as_ref();
+        let mut contents = String::new();
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|e| FileConfigError::Io(path.to_owned(), e))?;
+
+        let mut config = builtin_default_config();
+        for (ix, line) in contents.lines().enumerate() {
+            config.merge_file_line(ix + 1, line)?;
+        }
+        Ok(config)
+    }
+
+    /// Constructs a `Config` as `from_file()` does from the nearest
+    /// `.config/proptest.toml` or `proptest.toml`, looking first in the
+    /// current directory and then in each of its ancestors.
+    ///
+    /// Since `cargo test` runs tests in the root directory of their crate,
+    /// this finds a file in the root of the crate or, failing that, of its
+    /// workspace, which lets all crates of a workspace share one
+    /// configuration.
+    ///
+    /// If there is no such file, this returns proptest's built-in defaults.
+    pub fn from_workspace_root() -> Result<Self, FileConfigError> {
+        let dir = env::current_dir().map_err(
+            |e| FileConfigError::Io(PathBuf::from("."), e))?;
+        match find_config_file(&dir) {
+            Some(path) => Self::from_file(path),
+            None => Ok(builtin_default_config()),
+        }
+    }
+
+    fn merge_file_line(&mut self, line_number: usize, line: &str)
+                       -> Result<(), FileConfigError> {
+        let syntax_error = || FileConfigError::Syntax(
+            line_number, line.to_owned());
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        if line.starts_with('[') {
+            let header = line.split('#').next().unwrap_or("").trim();
+            return if "[proptest]" == header {
+                Ok(())
+            } else {
+                Err(syntax_error())
+            };
+        }
+
+        let eq = line.find('=').ok_or_else(syntax_error)?;
+        let key = line[..eq].trim();
+        if key.is_empty() ||
+            !key.chars().all(|c| c.is_ascii_lowercase() || '_' == c)
+        {
+            return Err(syntax_error());
+        }
+        let value = parse_toml_value(line[eq+1..].trim())
+            .ok_or_else(syntax_error)?;
+
+        match self.merge_var(&format!("PROPTEST_{}", key.to_uppercase()),
+                             &value) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(FileConfigError::UnknownField(
+                line_number, key.to_owned())),
+            Err(_) => Err(FileConfigError::InvalidValue(
+                line_number, key.to_owned(), value)),
+        }
+    }
+
+    /// Whether test cases should be run in forked child processes.
+    #[cfg(all(feature = "fork", unix))]
+    fn forks(&self) -> bool {
+        self.fork
+    }
+
+    /// Whether test cases should be run in forked child processes.
+    #[cfg(not(all(feature = "fork", unix)))]
+    fn forks(&self) -> bool {
+        false
+    }
+
+    /// The maximum time a single test case may take to run, if any.
+    #[cfg(feature = "timeout")]
+    fn case_timeout(&self) -> Option<Duration> {
+        self.case_timeout
+    }
+
+    /// The maximum time a single test case may take to run, if any.
+    #[cfg(not(feature = "timeout"))]
+    fn case_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 impl Default for Config {
@@ -213,10 +686,36 @@ impl Default for FailurePersistence {
     }
 }
 
+impl FailurePersistence {
+    /// Parse the format used by the `PROPTEST_FAILURE_PERSISTENCE` environment
+    /// variable. Any string payload is leaked.
+    fn parse(s: &str) -> Option<Self> {
+        fn leak(s: &str) -> &'static str {
+            Box::leak(s.to_owned().into_boxed_str())
+        }
+
+        if "off" == s {
+            return Some(FailurePersistence::Off);
+        }
+
+        let mut parts = s.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("source-parallel"), Some(name)) =>
+                Some(FailurePersistence::SourceParallel(leak(name))),
+            (Some("with-source"), Some(ext)) =>
+                Some(FailurePersistence::WithSource(leak(ext))),
+            (Some("direct"), Some(path)) =>
+                Some(FailurePersistence::Direct(leak(path))),
+            _ => None,
+        }
+    }
+}
+
 impl FailurePersistence {
     /// Given the nominal source path, determine the location of the failure
     /// persistence file, if any.
-    fn resolve(&self, source: Option<&Path>) -> Option<PathBuf> {
+    fn resolve(&self, source: Option<&Path>, output: &Output)
+               -> Option<PathBuf> {
         match *self {
             FailurePersistence::Off => None,
 
@@ -234,10 +733,11 @@ impl FailurePersistence {
                     }
 
                     if !found {
-                        eprintln!(
+                        output.print(format_args!(
                             "proptest: FailurePersistence::SourceParallel set, \
-                             but failed to find lib.rs or main.rs");
-                        FailurePersistence::WithSource(sibling).resolve(source)
+                             but failed to find lib.rs or main.rs"));
+                        FailurePersistence::WithSource(sibling)
+                            .resolve(source, output)
                     } else {
                         let suffix = source_path.strip_prefix(&dir)
                             .expect("parent of source is not a prefix of it?")
@@ -254,9 +754,9 @@ impl FailurePersistence {
                     }
                 },
                 None => {
-                    eprintln!(
+                    output.print(format_args!(
                         "proptest: FailurePersistence::SourceParallel set, \
-                         but no source file known");
+                         but no source file known"));
                     None
                 },
             },
@@ -269,8 +769,9 @@ impl FailurePersistence {
                 },
 
                 None => {
-                    eprintln!("proptest: FailurePersistence::WithSource set, \
-                               but no source file known");
+                    output.print(format_args!(
+                        "proptest: FailurePersistence::WithSource set, \
+                         but no source file known"));
                     None
                 },
             },
@@ -286,13 +787,16 @@ impl FailurePersistence {
 
 /// The reason for why something, such as a generated value, was rejected.
 ///
-/// Currently this is merely a wrapper around a message, but more properties
-/// may be added in the future.
+/// This is a message plus, for test failures, any context recorded with
+/// `prop_add_context!` while the failing test case ran.
 ///
 /// This is constructed via `.into()` on a `String`, `&'static str`, or
 /// `Box<str>`.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Reason(Cow<'static, str>);
+pub struct Reason {
+    message: Cow<'static, str>,
+    context: Vec<(String, String)>,
+}
 
 impl Reason {
     /// Return the message for this `Reason`.
@@ -300,34 +804,132 @@ impl Reason {
     /// The message is intended for human consumption, and is not guaranteed to
     /// have any format in particular.
     pub fn message(&self) -> &str {
-        &*self.0
+        &*self.message
+    }
+
+    /// Return the key-value pairs of context attached to this `Reason`, in
+    /// the order they were added.
+    pub fn context(&self) -> &[(String, String)] {
+        &self.context
+    }
+
+    /// Add a key-value pair to the context of this `Reason`.
+    pub fn with_context<K : Into<String>, V : Into<String>>(
+        mut self, key: K, value: V) -> Self
+    {
+        self.context.push((key.into(), value.into()));
+        self
+    }
+
+    /// Format the context as one indented line per entry, each preceded by
+    /// a newline, or an empty string if there is none.
+    pub(crate) fn context_lines(&self) -> String {
+        self.context.iter()
+            .map(|&(ref key, ref value)| format!("\n    {} = {}", key, value))
+            .collect()
     }
 }
 
 impl From<&'static str> for Reason {
     fn from(s: &'static str) -> Self {
-        Reason(s.into())
+        Reason { message: s.into(), context: vec![] }
     }
 }
 
 impl From<String> for Reason {
     fn from(s: String) -> Self {
-        Reason(s.into())
+        Reason { message: s.into(), context: vec![] }
     }
 }
 
 impl From<Box<str>> for Reason {
     fn from(s: Box<str>) -> Self {
-        Reason(String::from(s).into())
+        Reason { message: String::from(s).into(), context: vec![] }
     }
 }
 
+// Only the message is displayed; the context is shown by the `Display`
+// implementations of the failures containing the `Reason`.
 impl fmt::Display for Reason {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(self.message(), f)
     }
 }
 
+thread_local! {
+    /// Context added by `prop_add_context!` in the currently running test
+    /// case.
+    static CONTEXT: RefCell<Vec<(String, String)>> = RefCell::new(vec![]);
+}
+
+/// Add a key-value pair to the context of the currently running test case.
+///
+/// This is an implementation detail of `prop_add_context!`.
+#[doc(hidden)]
+pub fn add_context(key: String, value: String) {
+    CONTEXT.with(|context| context.borrow_mut().push((key, value)));
+}
+
+/// Consecutive rejections by each `prop_assume_or_abort!` call site, keyed
+/// by file, line and column, within the test run by a `TestRunner`.
+#[derive(Debug)]
+struct AssumeRejects {
+    limit: u32,
+    counts: HashMap<(&'static str, u32, u32), u32>,
+}
+
+impl AssumeRejects {
+    /// Create an empty record of rejections, shared by the threads which run
+    /// the cases of a test, which allows `limit` consecutive rejections.
+    fn shared(limit: u32) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(AssumeRejects { limit, counts: HashMap::new() }))
+    }
+}
+
+thread_local! {
+    /// The rejections of the runner whose test case is currently running on
+    /// this thread, if any.
+    static ASSUME_REJECTS: RefCell<Option<Arc<Mutex<AssumeRejects>>>> =
+        RefCell::new(None);
+}
+
+/// Run `f`, which runs a test case, recording the rejections of any
+/// `prop_assume_or_abort!` it reaches in `rejects`.
+fn with_assume_rejects<R, F : FnOnce() -> R>
+    (rejects: &Arc<Mutex<AssumeRejects>>, f: F) -> R
+{
+    let outer = ASSUME_REJECTS.with(
+        |current| current.replace(Some(Arc::clone(rejects))));
+    // `f` catches panics from the test, so this is always restored.
+    let result = f();
+    ASSUME_REJECTS.with(|current| current.replace(outer));
+    result
+}
+
+/// Record whether the assumption of the `prop_assume_or_abort!` at
+/// `location` held. If it did not, return the number of consecutive
+/// rejections at `location` if that exceeds the `Config::max_assume_rejects`
+/// of the runner running the current test case.
+///
+/// Outside of a test case run by a `TestRunner`, this always returns `None`.
+///
+/// This is an implementation detail of `prop_assume_or_abort!`.
+#[doc(hidden)]
+pub fn check_assumption(held: bool, location: (&'static str, u32, u32))
+                        -> Option<u32> {
+    let rejects = ASSUME_REJECTS.with(|current| current.borrow().clone())?;
+    let mut rejects = rejects.lock().unwrap_or_else(|e| e.into_inner());
+    if held {
+        rejects.counts.remove(&location);
+        return None;
+    }
+
+    let limit = rejects.limit;
+    let count = rejects.counts.entry(location).or_insert(0);
+    *count += 1;
+    if *count > limit { Some(*count) } else { None }
+}
+
 /// Errors which can be returned from test cases to indicate non-successful
 /// completion.
 ///
@@ -376,7 +978,7 @@ impl fmt::Display for TestCaseError {
             TestCaseError::Reject(ref whence) =>
                 write!(f, "Input rejected at {}", whence),
             TestCaseError::Fail(ref why) =>
-                write!(f, "Case failed: {}", why),
+                write!(f, "Case failed: {}{}", why, why.context_lines()),
         }
     }
 }
@@ -405,8 +1007,8 @@ impl<T : fmt::Debug> fmt::Display for TestError<T> {
             TestError::Abort(ref why) =>
                 write!(f, "Test aborted: {}", why),
             TestError::Fail(ref why, ref what) =>
-                write!(f, "Test failed: {}; minimal failing input: {:?}",
-                       why, what),
+                write!(f, "Test failed: {}; minimal failing input: {:?}{}",
+                       why, what, why.context_lines()),
         }
     }
 }
@@ -420,8 +1022,94 @@ impl<T : fmt::Debug> ::std::error::Error for TestError<T> {
     }
 }
 
+/// Statistics about the test cases a `TestRunner` has run.
+///
+/// Returned by `TestRunner::run()` on success, and also available at any time
+/// from `TestRunner::stats()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of test cases which passed.
+    pub successes: u32,
+    /// The number of test cases the test function was run against, whether
+    /// they passed or were rejected.
+    pub total_cases: u32,
+    /// The number of test cases rejected by the test function, e.g., via
+    /// `prop_assume!`.
+    pub total_rejects: u32,
+    /// The number of values rejected while generating test cases, e.g., by
+    /// `prop_filter()`.
+    pub local_rejects: u32,
+    /// The number of simplified inputs tried while shrinking failing cases.
+    pub shrink_steps: u32,
+    /// The time spent in `TestRunner::run()`.
+    ///
+    /// This is always zero on targets without a system clock, such as
+    /// `wasm32-unknown-unknown`.
+    pub wall_time: Duration,
+    // Needs to be public so FRU syntax can be used.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Stats {
+    /// Return the fraction of `total_cases` which were rejected, or 0 if no
+    /// cases were run.
+    pub fn reject_ratio(&self) -> f64 {
+        if 0 == self.total_cases {
+            0.0
+        } else {
+            f64::from(self.total_rejects) / f64::from(self.total_cases)
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} successes, {} of {} cases rejected ({:.1}%), \
+                   {} local rejects, {} shrink steps, took {:?}",
+               self.successes, self.total_rejects, self.total_cases,
+               100.0 * self.reject_ratio(), self.local_rejects,
+               self.shrink_steps, self.wall_time)
+    }
+}
+
 type RejectionDetail = BTreeMap<Reason, u32>;
 
+/// Where a `TestRunner` prints warnings and statistics.
+#[derive(Clone)]
+pub(crate) struct Output {
+    /// The writer set by `TestRunner::set_output()`, or `None` for standard
+    /// error.
+    sink: Option<Arc<Mutex<Box<Write + Send>>>>,
+    /// Whether to print anything at all, as per `Config::verbose`.
+    verbose: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Output { sink: None, verbose: true }
+    }
+}
+
+impl Output {
+    /// Print `message` as a line of output, unless output is disabled.
+    ///
+    /// Errors writing to the sink are ignored, as is a sink poisoned by a
+    /// panic on another thread.
+    pub(crate) fn print(&self, message: fmt::Arguments) {
+        if !self.verbose {
+            return;
+        }
+
+        match self.sink {
+            Some(ref sink) => if let Ok(mut sink) = sink.lock() {
+                let _ = writeln!(sink, "{}", message);
+            },
+            None => eprintln!("{}", message),
+        }
+    }
+}
+
 /// State used when running a proptest test.
 #[derive(Clone)]
 pub struct TestRunner {
@@ -429,13 +1117,24 @@ pub struct TestRunner {
     successes: u32,
     local_rejects: u32,
     global_rejects: u32,
+    shrink_steps: u32,
+    wall_time: Duration,
     rng: XorShiftRng,
     flat_map_regens: Arc<AtomicUsize>,
+    /// Local rejections made by `DeferredGeneration`s split off this runner.
+    deferred_local_rejects: Arc<AtomicUsize>,
 
     local_reject_detail: RejectionDetail,
     global_reject_detail: RejectionDetail,
+    /// Local rejections from each filter while generating the current test
+    /// case, checked against `max_local_rejects`.
+    case_local_rejects: RejectionDetail,
+    /// Rejections by `prop_assume_or_abort!` in the current test.
+    assume_rejects: Arc<Mutex<AssumeRejects>>,
 
     source_file: Option<Cow<'static, Path>>,
+    test_name: Option<String>,
+    output: Output,
 }
 
 impl fmt::Debug for TestRunner {
@@ -445,11 +1144,22 @@ impl fmt::Debug for TestRunner {
             .field("successes", &self.successes)
             .field("local_rejects", &self.local_rejects)
             .field("global_rejects", &self.global_rejects)
+            .field("shrink_steps", &self.shrink_steps)
+            .field("wall_time", &self.wall_time)
             .field("rng", &"<XorShiftRng>")
             .field("flat_map_regens", &self.flat_map_regens)
+            .field("deferred_local_rejects", &self.deferred_local_rejects)
             .field("local_reject_detail", &self.local_reject_detail)
             .field("global_reject_detail", &self.global_reject_detail)
+            .field("case_local_rejects", &self.case_local_rejects)
+            .field("assume_rejects", &self.assume_rejects)
             .field("source_file", &self.source_file)
+            .field("test_name", &self.test_name)
+            .field("output", &if self.output.sink.is_some() {
+                "<custom>"
+            } else {
+                "<stderr>"
+            })
             .finish()
     }
 }
@@ -458,7 +1168,7 @@ impl fmt::Display for TestRunner {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "\tsuccesses: {}\n\
                    \tlocal rejects: {}\n",
-               self.successes, self.local_rejects)?;
+               self.successes, self.stats().local_rejects)?;
         for (whence, count) in &self.local_reject_detail {
             writeln!(f, "\t\t{} times at {}", count, whence)?;
         }
@@ -471,6 +1181,54 @@ impl fmt::Display for TestRunner {
     }
 }
 
+/// Enough of a `TestRunner` to generate a value tree after the strategy's
+/// `new_value()` has returned, as `TupleUnion` does for options that are only
+/// needed once shrinking moves to them.
+///
+/// This holds the runner's config and a seed drawn from its RNG rather than a
+/// copy of the runner. The `flat_map` regeneration counter is shared with the
+/// runner it was split off, and local rejections made while generating are
+/// added to that runner's local rejections.
+#[derive(Clone, Debug)]
+pub(crate) struct DeferredGeneration {
+    seed: [u32;4],
+    config: Config,
+    flat_map_regens: Arc<AtomicUsize>,
+    local_rejects: Arc<AtomicUsize>,
+}
+
+impl DeferredGeneration {
+    /// Generate a new value tree from `strategy`.
+    ///
+    /// Each call starts from the same seed.
+    pub(crate) fn new_value<S : Strategy>(&self, strategy: &S)
+                                          -> NewTree<S> {
+        let mut runner = TestRunner {
+            config: self.config.clone(),
+            successes: 0,
+            local_rejects: 0,
+            global_rejects: 0,
+            shrink_steps: 0,
+            wall_time: Duration::from_secs(0),
+            rng: XorShiftRng::from_seed(self.seed),
+            flat_map_regens: Arc::clone(&self.flat_map_regens),
+            // Generations deferred in turn count towards the same runner.
+            deferred_local_rejects: Arc::clone(&self.local_rejects),
+            local_reject_detail: BTreeMap::new(),
+            global_reject_detail: BTreeMap::new(),
+            case_local_rejects: BTreeMap::new(),
+            assume_rejects: AssumeRejects::shared(
+                self.config.max_assume_rejects),
+            source_file: None,
+            test_name: None,
+            output: Output { sink: None, verbose: self.config.verbose },
+        };
+        let result = strategy.new_value(&mut runner);
+        self.local_rejects.fetch_add(runner.local_rejects as usize, SeqCst);
+        result
+    }
+}
+
 /// Equivalent to: `TestRunner::default(Config::default())`.
 impl Default for TestRunner {
     fn default() -> Self {
@@ -488,7 +1246,8 @@ lazy_static! {
     static ref PERSISTENCE_LOCK: RwLock<()> = RwLock::new(());
 }
 
-fn load_persisted_failures(path: Option<&PathBuf>) -> Vec<[u32;4]> {
+fn load_persisted_failures(path: Option<&PathBuf>, output: &Output)
+                           -> Vec<[u32;4]> {
     let result: io::Result<Vec<[u32;4]>> =
         path.map_or_else(|| Ok(vec![]), |path| {
             // .ok() instead of .unwrap() so we don't propagate panics here
@@ -513,14 +1272,15 @@ fn load_persisted_failures(path: Option<&PathBuf>) -> Vec<[u32;4]> {
                     if let Ok(seed) = seed {
                         ret.push(seed);
                     } else {
-                        eprintln!(
+                        output.print(format_args!(
                             "proptest: {}:{}: unparsable line, \
-                             ignoring", path.display(), lineno + 1);
+                             ignoring", path.display(), lineno + 1));
                     }
                 } else if parts.len() > 1 {
-                    eprintln!("proptest: {}:{}: unknown case type `{}` \
-                               (corrupt file or newer proptest version?)",
-                              path.display(), lineno + 1, parts[0]);
+                    output.print(format_args!(
+                        "proptest: {}:{}: unknown case type `{}` \
+                         (corrupt file or newer proptest version?)",
+                        path.display(), lineno + 1, parts[0]));
                 }
             }
 
@@ -531,10 +1291,10 @@ fn load_persisted_failures(path: Option<&PathBuf>) -> Vec<[u32;4]> {
         Ok(r) => r,
         Err(err) => {
             if io::ErrorKind::NotFound != err.kind() {
-                eprintln!(
+                output.print(format_args!(
                     "proptest: failed to open {}: {}",
                     path.map(|x| &**x).unwrap_or(Path::new("??")).display(),
-                    err);
+                    err));
             }
             vec![]
         },
@@ -543,7 +1303,8 @@ fn load_persisted_failures(path: Option<&PathBuf>) -> Vec<[u32;4]> {
 
 fn save_persisted_failure(path: Option<&PathBuf>,
                           seed: [u32;4],
-                          value: &fmt::Debug) {
+                          value: &fmt::Debug,
+                          output: &Output) {
     if let Some(path) = path {
         // .ok() instead of .unwrap() so we don't propagate panics here
         let _lock = PERSISTENCE_LOCK.write().ok();
@@ -587,48 +1348,416 @@ fn save_persisted_failure(path: Option<&PathBuf>,
         }
 
         if let Err(e) = do_write(path, &to_write) {
-            eprintln!(
+            output.print(format_args!(
                 "proptest: failed to append to {}: {}",
-                path.display(), e);
+                path.display(), e));
         } else if is_new {
-            eprintln!(
+            output.print(format_args!(
                 "proptest: Saving this and future failures in {}",
-                path.display());
+                path.display()));
+        }
+    }
+}
+
+/// The version of the format of the reports written to
+/// `Config::json_report_file`, incremented on incompatible changes.
+const JSON_REPORT_VERSION: u32 = 1;
+
+/// Quote and escape `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if ch < ' ' =>
+                result.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Append `report` as one line to `path`.
+fn write_json_report(path: &Path, report: &str, output: &Output) {
+    fn do_write(dst: &Path, data: &str) -> io::Result<()> {
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
         }
+
+        let mut options = fs::OpenOptions::new();
+        options.append(true).create(true);
+        let mut out = options.open(dst)?;
+        // Write the line at once so that reports from concurrent tests are
+        // not interleaved.
+        out.write_all(format!("{}\n", data).as_bytes())?;
+
+        Ok(())
+    }
+
+    if let Err(e) = do_write(path, report) {
+        output.print(format_args!(
+            "proptest: failed to write JSON report to {}: {}",
+            path.display(), e));
     }
 }
 
-fn panic_guard<V, F>(case: &V, test: &F) -> TestCaseResult
+pub(crate) fn panic_guard<V, F>(case: &V, test: &F) -> TestCaseResult
 where
     F: Fn(&V) -> TestCaseResult
 {
-    match panic::catch_unwind(AssertUnwindSafe(|| test(&case))) {
+    // Save the context of any enclosing test case, in case `test` runs
+    // proptest itself.
+    let outer_context = CONTEXT.with(|context| context.replace(vec![]));
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| test(&case))) {
         Ok(r) => r,
         Err(what) => Err(TestCaseError::Fail(
             what.downcast::<&'static str>().map(|s| (*s).into())
                 .or_else(|what| what.downcast::<String>().map(|b| (*b).into()))
                 .or_else(|what| what.downcast::<Box<str>>().map(|b| (*b).into()))
                 .unwrap_or_else(|_| "<unknown panic value>".into()))),
+    };
+    let context = CONTEXT.with(|context| context.replace(outer_context));
+
+    match result {
+        Err(TestCaseError::Fail(mut why)) => {
+            why.context.extend(context);
+            Err(TestCaseError::Fail(why))
+        },
+        result => result,
     }
 }
 
-impl TestRunner {
-    /// Create a fresh `TestRunner` with the given configuration.
-    pub fn new(config: Config) -> Self {
-        TestRunner {
-            config: config,
-            successes: 0,
-            local_rejects: 0,
-            global_rejects: 0,
-            rng: rand::weak_rng(),
-            flat_map_regens: Arc::new(AtomicUsize::new(0)),
-            local_reject_detail: BTreeMap::new(),
-            global_reject_detail: BTreeMap::new(),
-            source_file: None,
-        }
+/// The failure reported for a test case which ran for longer than `timeout`.
+fn timed_out(timeout: Duration) -> TestCaseError {
+    let mut why = Reason::from("timed out");
+    why.context.push(("case_timeout".to_owned(), format!("{:?}", timeout)));
+    TestCaseError::Fail(why)
+}
+
+/// Run `test` against `case`, failing it if it panics or if it returns after
+/// more than `timeout`.
+///
+/// `test` runs to completion on the calling thread, so a case which never
+/// returns hangs here. It cannot be moved to a thread which could be
+/// abandoned, since neither `test` nor `case` need be `Send` or `'static`;
+/// `run_case_isolated()` instead runs such cases in a child process which
+/// can be killed where that is supported.
+fn run_case_with_timeout<V, F>(timeout: Option<Duration>, case: &V, test: &F)
+                               -> TestCaseResult
+where
+    F: Fn(&V) -> TestCaseResult
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return panic_guard(case, test),
+    };
+
+    let start_time = Instant::now();
+    let result = panic_guard(case, test);
+    // Genuine failures take precedence over timing out.
+    if let Err(TestCaseError::Fail(..)) = result {
+        return result;
     }
 
-    /// Create a fresh `TestRunner` with the same config and global counters as
+    if start_time.elapsed() > timeout {
+        Err(timed_out(timeout))
+    } else {
+        result
+    }
+}
+
+/// Run `test` against `case` as `run_case_with_timeout()` does, but in a
+/// forked child process if `fork` is set or `timeout` is given, so that the
+/// child can be killed once the timeout expires.
+fn run_case_isolated<V, F>(fork: bool, timeout: Option<Duration>, case: &V,
+                           test: &F) -> TestCaseResult
+where
+    F: Fn(&V) -> TestCaseResult
+{
+    #[cfg(all(feature = "fork", unix))]
+    {
+        if fork || timeout.is_some() {
+            return fork::run_case_in_child(timeout, case, test);
+        }
+    }
+    #[cfg(not(all(feature = "fork", unix)))]
+    debug_assert!(!fork);
+
+    run_case_with_timeout(timeout, case, test)
+}
+
+#[cfg(all(feature = "fork", unix))]
+mod fork {
+    use std::cmp::min;
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use std::thread;
+    use std::time::Duration;
+
+    use libc;
+
+    use super::*;
+
+    const PASS: u8 = 0;
+    const REJECT: u8 = 1;
+    const FAIL: u8 = 2;
+
+    fn failure(what: &str, error: io::Error) -> TestCaseError {
+        TestCaseError::Fail(format!("{}: {}", what, error).into())
+    }
+
+    /// Encode `result` as a tag byte followed by the NUL-separated message
+    /// and context of its `Reason`, if any.
+    fn encode(result: &TestCaseResult) -> Vec<u8> {
+        let (tag, reason) = match *result {
+            Ok(()) => return vec![PASS],
+            Err(TestCaseError::Reject(ref reason)) => (REJECT, reason),
+            Err(TestCaseError::Fail(ref reason)) => (FAIL, reason),
+        };
+
+        let mut data = vec![tag];
+        data.extend(reason.message.as_bytes());
+        for &(ref key, ref value) in &reason.context {
+            data.push(0);
+            data.extend(key.as_bytes());
+            data.push(0);
+            data.extend(value.as_bytes());
+        }
+        data
+    }
+
+    /// Decode a result encoded by `encode()`, or return `None` if `data` is
+    /// not a complete result.
+    fn decode(data: &[u8]) -> Option<TestCaseResult> {
+        let (&tag, rest) = data.split_first()?;
+        if PASS == tag {
+            return if rest.is_empty() { Some(Ok(())) } else { None };
+        }
+
+        let rest = String::from_utf8_lossy(rest);
+        let mut fields = rest.split('\0').map(str::to_owned);
+        let mut reason = Reason::from(fields.next()?);
+        while let Some(key) = fields.next() {
+            reason.context.push((key, fields.next()?));
+        }
+
+        match tag {
+            REJECT => Some(Err(TestCaseError::Reject(reason))),
+            FAIL => Some(Err(TestCaseError::Fail(reason))),
+            _ => None,
+        }
+    }
+
+    /// How often to check whether a child process with a deadline has
+    /// exited.
+    const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+    /// Wait for the child process `pid` to exit and return its status.
+    ///
+    /// If `timeout` is given and the child is still running once it expires,
+    /// the child is killed and `Ok(None)` is returned.
+    fn wait_for_child(pid: libc::pid_t, timeout: Option<Duration>)
+                      -> io::Result<Option<libc::c_int>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let flags = if deadline.is_some() { libc::WNOHANG } else { 0 };
+        let mut status = 0;
+        loop {
+            match unsafe { libc::waitpid(pid, &mut status, flags) } {
+                0 => (),
+                ret if ret > 0 => return Ok(Some(status)),
+                _ => {
+                    let error = io::Error::last_os_error();
+                    if io::ErrorKind::Interrupted != error.kind() {
+                        return Err(error);
+                    }
+                    continue;
+                },
+            }
+
+            // Only reached with `WNOHANG` while the child is still running.
+            let deadline = deadline.expect("waitpid() returned 0 without \
+                                            WNOHANG");
+            let now = Instant::now();
+            if now >= deadline {
+                unsafe { libc::kill(pid, libc::SIGKILL); }
+                // Reap the child so that it does not linger as a zombie.
+                while unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+                    let error = io::Error::last_os_error();
+                    if io::ErrorKind::Interrupted != error.kind() {
+                        return Err(error);
+                    }
+                }
+                return Ok(None);
+            }
+            thread::sleep(min(deadline - now, POLL_INTERVAL));
+        }
+    }
+
+    /// Run `test` against `case` in a child process, failing the case if the
+    /// child does not exit normally after reporting its result, or if it is
+    /// still running once `timeout` expires, in which case it is killed.
+    pub(super) fn run_case_in_child<V, F>(timeout: Option<Duration>,
+                                          case: &V, test: &F)
+                                          -> TestCaseResult
+    where
+        F: Fn(&V) -> TestCaseResult
+    {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(failure("Failed to create pipe",
+                               io::Error::last_os_error()));
+        }
+        let (mut read_end, mut write_end) = unsafe {
+            (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]))
+        };
+
+        let pid = unsafe { libc::fork() };
+        if pid < 0 {
+            return Err(failure("Failed to fork", io::Error::last_os_error()));
+        }
+
+        if 0 == pid {
+            drop(read_end);
+            let result = run_case_with_timeout(timeout, case, test);
+            let status = match write_end.write_all(&encode(&result)) {
+                Ok(()) => 0,
+                Err(_) => 1,
+            };
+            // Exit without running destructors or `atexit` handlers, which
+            // belong to the test process.
+            unsafe { libc::_exit(status) }
+        }
+
+        drop(write_end);
+        // Read the report on another thread so that a child writing a large
+        // report cannot block on a full pipe while this thread waits for it.
+        let reader = thread::spawn(move || {
+            let mut report = vec![];
+            read_end.read_to_end(&mut report).map(|_| report)
+        });
+
+        let status = wait_for_child(pid, timeout);
+        // The child has exited, so its end of the pipe is closed and this
+        // does not block.
+        let report = reader.join().ok().and_then(Result::ok);
+        let status = match status {
+            Ok(Some(status)) => status,
+            Ok(None) => return Err(timed_out(
+                timeout.expect("child killed without a timeout"))),
+            Err(error) =>
+                return Err(failure("Failed to wait for test process", error)),
+        };
+
+        if libc::WIFSIGNALED(status) {
+            Err(TestCaseError::Fail(format!(
+                "Test process killed by signal {}",
+                libc::WTERMSIG(status)).into()))
+        } else if libc::WIFEXITED(status) && 0 != libc::WEXITSTATUS(status) {
+            Err(TestCaseError::Fail(format!(
+                "Test process exited with status {}",
+                libc::WEXITSTATUS(status)).into()))
+        } else {
+            report.and_then(|report| decode(&report)).unwrap_or_else(
+                || Err(TestCaseError::Fail(
+                    "Test process exited without reporting a result".into())))
+        }
+    }
+}
+
+/// Note in `why` that shrinking stopped after `iters` iterations because the
+/// shrink budget was exhausted.
+fn note_shrink_budget_exhausted(why: &mut Reason, iters: u32) {
+    why.message = format!(
+        "{} (shrinking stopped after {} iterations since the shrink budget \
+         was exhausted; the input may not be minimal)",
+        why.message, iters).into();
+}
+
+/// Create the RNG for a fresh `TestRunner`, seeded from the OS.
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+fn default_rng() -> XorShiftRng {
+    rand::weak_rng()
+}
+
+/// Create the RNG for a fresh `TestRunner`.
+///
+/// There is no OS entropy source on bare WASM, where `rand::weak_rng()` would
+/// panic, so every runner starts from the same fixed seed instead.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn default_rng() -> XorShiftRng {
+    XorShiftRng::from_seed([0x193a_6754, 0xa8a7_d469, 0x9783_0e05, 0x113b_a7bb])
+}
+
+/// Derive an RNG seed from `name`.
+///
+/// This uses FNV-1a followed by SplitMix64 rather than `std`'s hasher so that
+/// the seed for a given name never changes between Rust versions.
+fn seed_from_name(name: &str) -> [u32;4] {
+    let mut state = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    let (a, b) = (next(), next());
+    let mut seed = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+    // XorShiftRng does not accept an all-zero seed.
+    if [0; 4] == seed {
+        seed[0] = 1;
+    }
+    seed
+}
+
+/// Read the clock.
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+/// There is no clock to read on bare WASM, where `Instant::now()` panics.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn now() -> Option<Instant> {
+    None
+}
+
+impl TestRunner {
+    /// Create a fresh `TestRunner` with the given configuration.
+    pub fn new(config: Config) -> Self {
+        let output = Output { sink: None, verbose: config.verbose };
+        let assume_rejects = AssumeRejects::shared(config.max_assume_rejects);
+        TestRunner {
+            config: config,
+            successes: 0,
+            local_rejects: 0,
+            global_rejects: 0,
+            shrink_steps: 0,
+            wall_time: Duration::from_secs(0),
+            rng: default_rng(),
+            flat_map_regens: Arc::new(AtomicUsize::new(0)),
+            deferred_local_rejects: Arc::new(AtomicUsize::new(0)),
+            local_reject_detail: BTreeMap::new(),
+            global_reject_detail: BTreeMap::new(),
+            case_local_rejects: BTreeMap::new(),
+            assume_rejects: assume_rejects,
+            source_file: None,
+            test_name: None,
+            output: output,
+        }
+    }
+
+    /// Create a fresh `TestRunner` with the same config and global counters as
     /// this one, but with local state reset and an independent `Rng` (but
     /// deterministic).
     pub(crate) fn partial_clone(&mut self) -> Self {
@@ -639,11 +1768,19 @@ impl TestRunner {
             successes: 0,
             local_rejects: 0,
             global_rejects: 0,
+            shrink_steps: 0,
+            wall_time: Duration::from_secs(0),
             rng: rng,
             flat_map_regens: Arc::clone(&self.flat_map_regens),
+            deferred_local_rejects: Arc::clone(&self.deferred_local_rejects),
             local_reject_detail: BTreeMap::new(),
             global_reject_detail: BTreeMap::new(),
+            case_local_rejects: BTreeMap::new(),
+            assume_rejects: AssumeRejects::shared(
+                self.config.max_assume_rejects),
             source_file: self.source_file.clone(),
+            test_name: self.test_name.clone(),
+            output: self.output.clone(),
         }
     }
 
@@ -663,12 +1800,74 @@ impl TestRunner {
         seed
     }
 
+    /// Split off a `DeferredGeneration` with an independent but
+    /// deterministic seed, whose local rejections count towards this runner.
+    pub(crate) fn defer(&mut self) -> DeferredGeneration {
+        DeferredGeneration {
+            seed: self.new_rng_seed(),
+            config: self.config.clone(),
+            flat_map_regens: Arc::clone(&self.flat_map_regens),
+            local_rejects: Arc::clone(&self.deferred_local_rejects),
+        }
+    }
+
     /// Create a new, independent but deterministic RNG from the RNG in this
     /// runner.
     pub fn new_rng(&mut self) -> XorShiftRng {
         XorShiftRng::from_seed(self.new_rng_seed())
     }
 
+    /// Reseed this runner's RNG from `name`, which should uniquely identify
+    /// the test being run, such as its full module path and function name.
+    ///
+    /// Runners given different names generate unrelated sequences of values,
+    /// so tests never explore the same inputs as each other by accident. If
+    /// `Config::seed_from_test_name` is set, the seed is derived from the
+    /// name alone, so runners given the same name generate the same sequence
+    /// and the test is reproducible. Otherwise, the seed derived from the
+    /// name is mixed with the runner's current random seed.
+    ///
+    /// This is normally called automatically by the `proptest!` macro, which
+    /// passes the module path and name of the test function.
+    ///
+    /// The name is also recorded in any report written to
+    /// `Config::json_report_file`.
+    pub fn set_test_name(&mut self, name: &str) {
+        let mut seed = seed_from_name(name);
+        if !self.config.seed_from_test_name {
+            let fresh = <[u32;4] as Rand>::rand(&mut self.rng);
+            for (word, fresh) in seed.iter_mut().zip(&fresh) {
+                *word ^= fresh;
+            }
+            // XorShiftRng does not accept an all-zero seed.
+            if [0; 4] == seed {
+                seed[0] = 1;
+            }
+        }
+        self.rng = XorShiftRng::from_seed(seed);
+        self.test_name = Some(name.to_owned());
+    }
+
+    /// Send the warnings and statistics this runner prints to `sink` instead
+    /// of standard error.
+    ///
+    /// Each message is written as one line. Errors writing to `sink` are
+    /// ignored. Nothing is written if `Config::verbose` is `false`.
+    ///
+    /// ```
+    /// use std::io;
+    /// use proptest::test_runner::{Config, TestRunner};
+    ///
+    /// let mut runner = TestRunner::new(Config {
+    ///     print_stats: true, .. Config::default()
+    /// });
+    /// runner.set_output(io::stdout());
+    /// runner.run(&(0..10), |_| Ok(())).unwrap();
+    /// ```
+    pub fn set_output<W : Write + Send + 'static>(&mut self, sink: W) {
+        self.output.sink = Some(Arc::new(Mutex::new(Box::new(sink))));
+    }
+
     /// Returns the configuration of this runner.
     pub fn config(&self) -> &Config {
         &self.config
@@ -720,21 +1919,21 @@ impl TestRunner {
                         }
 
                         if !cwd.pop() {
-                            eprintln!(
+                            self.output.print(format_args!(
                                 "proptest: Failed to find absolute path of \
                                  source file '{:?}'. Ensure the test is \
                                  being run from somewhere within the crate \
-                                 directory hierarchy.", source);
+                                 directory hierarchy.", source));
                             break None;
                         }
                     }
                 },
 
                 Err(e) => {
-                    eprintln!("proptest: Failed to determine current \
-                               directory, so the relative source path \
-                               '{:?}' cannot be resolved: {}",
-                              source, e);
+                    self.output.print(format_args!(
+                        "proptest: Failed to determine current directory, \
+                         so the relative source path '{:?}' cannot be \
+                         resolved: {}", source, e));
                     None
                 }
             }
@@ -755,43 +1954,298 @@ impl TestRunner {
     /// tested first. If a later non-persisted case fails, its seed is
     /// persisted before returning failure.
     ///
-    /// Returns success or failure indicating why the test as a whole failed.
+    /// Returns the `Stats` of the run on success, or the reason the test as a
+    /// whole failed. On failure, `stats()` still describes the cases run
+    /// before the failing one was found; in particular, `stats().successes`
+    /// is the number of cases which passed first. The `Display` form of the
+    /// runner, which `proptest!` includes when reporting a failure, shows the
+    /// same numbers.
+    ///
+    /// On success, the stats are printed if `Config::print_stats` is set, and
+    /// a warning is printed if the fraction of rejected cases exceeds
+    /// `Config::reject_warning_ratio`.
+    ///
+    /// If `Config::json_report_file` is set, a report is appended to it if
+    /// the test fails (or, with `Config::json_report_always`, passes). The
+    /// report is one line holding a JSON object with the following fields:
+    ///
+    /// - `version`: the version of the report format, currently 1.
+    /// - `test`: the name given to `set_test_name()`, or `null`.
+    /// - `result`: `"pass"`, `"fail"` or `"abort"`.
+    /// - `seed`: the four words of the seed of the failing case, as persisted
+    ///   in failure persistence files, or `null`.
+    /// - `case`: the number of test cases run, including the failing one.
+    /// - `input`: the `Debug` representation of the minimal failing input,
+    ///   or `null`. `Debug` is used since generated values are not required
+    ///   to be serialisable.
+    /// - `message`: the reason the test failed or aborted, or `null`.
+    /// - `context`: the context added with `prop_add_context!` to the
+    ///   minimal failing case, as an object.
+    /// - `shrink_steps`: the number of shrinking steps taken.
+    ///
+    /// Errors writing the report are printed but do not affect the result.
     pub fn run<S : Strategy,
                F : Fn (&ValueFor<S>) -> TestCaseResult>
         (&mut self, strategy: &S, test: F)
+         -> Result<Stats, TestError<ValueFor<S>>>
+    {
+        self.run_and_report(
+            |runner, seed| runner.run_timed(strategy, &test, seed))
+    }
+
+    /// Like `run()`, but generates and tests the cases on a `rayon` thread
+    /// pool, so that slow tests can use every core.
+    ///
+    /// Persisted failures are replayed on the calling thread first. The
+    /// remaining cases are then run in parallel in rounds, each case with an
+    /// RNG seeded deterministically from this runner's RNG, so a run is
+    /// reproducible regardless of how the cases are scheduled. Failures are
+    /// collected, and the first one, in the order the seeds were drawn, is
+    /// generated again and shrunk on the calling thread exactly as by `run()`.
+    /// Later failures of the same round are discarded.
+    ///
+    /// `strategy` and `test` must be safe to use on several threads at once.
+    /// Each case is tested once without shrinking on a worker thread, so the
+    /// failing case is tested a second time before it is shrunk.
+    ///
+    /// This method is only available with the `rayon` feature.
+    ///
+    /// ```
+    /// use proptest::test_runner::TestRunner;
+    ///
+    /// let mut runner = TestRunner::default();
+    /// runner.run_par(&(0u32..1000), |&v| {
+    ///     assert!(v < 1000);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn run_par<S : Strategy + Sync,
+                   F : Fn (&ValueFor<S>) -> TestCaseResult + Sync>
+        (&mut self, strategy: &S, test: F)
+         -> Result<Stats, TestError<ValueFor<S>>>
+    {
+        self.run_and_report(
+            |runner, seed| runner.run_par_timed(strategy, &test, seed))
+    }
+
+    /// Time `run`, which runs the test cases of `run()` or `run_par()`, and
+    /// report its result.
+    fn run_and_report<T : fmt::Debug, R>(&mut self, run: R)
+                                          -> Result<Stats, TestError<T>>
+    where R : FnOnce(&mut Self, &mut Option<[u32;4]>)
+                     -> Result<(), TestError<T>>
+    {
+        let start_time = now();
+        // Each test tracks the rejections of `prop_assume_or_abort!` afresh.
+        self.assume_rejects =
+            AssumeRejects::shared(self.config.max_assume_rejects);
+        let mut seed = None;
+        let result = run(self, &mut seed);
+        if let Some(start_time) = start_time {
+            self.wall_time += start_time.elapsed();
+        }
+        if let Some(ref path) = self.config.json_report_file {
+            if result.is_err() || self.config.json_report_always {
+                let report = self.json_report(seed, result.as_ref().err());
+                write_json_report(path, &report, &self.output);
+            }
+        }
+        result?;
+
+        let stats = self.stats();
+        if self.config.print_stats {
+            self.output.print(format_args!("proptest: {}", stats));
+        }
+        if stats.reject_ratio() > self.config.reject_warning_ratio {
+            self.output.print(format_args!(
+                "proptest: {:.1}% of test cases were rejected; consider \
+                 changing the strategy to generate fewer inputs that need \
+                 to be rejected.", 100.0 * stats.reject_ratio()));
+        }
+
+        Ok(stats)
+    }
+
+    /// Run the test cases of `run()`, setting `seed` to the seed of each
+    /// case before it runs.
+    fn run_timed<S : Strategy,
+                 F : Fn (&ValueFor<S>) -> TestCaseResult>
+        (&mut self, strategy: &S, test: &F, seed: &mut Option<[u32;4]>)
          -> Result<(), TestError<ValueFor<S>>>
+    {
+        let persist_path = self.run_persisted(strategy, test, seed)?;
+
+        while self.successes < self.config.cases {
+            // Generate a new seed and make an RNG from that so that we know
+            // what seed to persist if this case fails.
+            let new_seed = self.new_rng_seed();
+            *seed = Some(new_seed);
+            self.gen_and_run_new_case(strategy, test, new_seed,
+                                      persist_path.as_ref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the cases of `run_par()`, setting `seed` as `run_timed()` does.
+    #[cfg(feature = "rayon")]
+    fn run_par_timed<S : Strategy + Sync,
+                     F : Fn (&ValueFor<S>) -> TestCaseResult + Sync>
+        (&mut self, strategy: &S, test: &F, seed: &mut Option<[u32;4]>)
+         -> Result<(), TestError<ValueFor<S>>>
+    {
+        use rayon::prelude::*;
+
+        let persist_path = self.run_persisted(strategy, test, seed)?;
+        let pool = rayon::ThreadPoolBuilder::new().build()
+            .map_err(|e| TestError::Abort(format!(
+                "Failed to start test threads: {}", e).into()))?;
+
+        while self.successes < self.config.cases {
+            let seeds = (self.successes..self.config.cases)
+                .map(|_| self.new_rng_seed()).collect::<Vec<_>>();
+            let config = &self.config;
+            let outcomes: Vec<_> = pool.install(|| {
+                seeds.par_iter().map(|&case_seed| {
+                    let mut runner = TestRunner::new(config.clone());
+                    runner.rng = XorShiftRng::from_seed(case_seed);
+                    let case = strategy.new_value(&mut runner)?;
+                    let result = runner.run_case(&case.current(), test);
+                    Ok((result, runner.local_reject_detail))
+                }).collect::<Vec<Result<_, Reason>>>()
+            });
+
+            for (&case_seed, outcome) in seeds.iter().zip(outcomes) {
+                *seed = Some(case_seed);
+                let (result, local_reject_detail) =
+                    outcome.map_err(TestError::Abort)?;
+                for (whence, count) in local_reject_detail {
+                    self.local_rejects += count;
+                    *self.local_reject_detail.entry(whence).or_insert(0) +=
+                        count;
+                }
+
+                match result {
+                    Ok(()) => self.successes += 1,
+                    Err(TestCaseError::Reject(whence)) =>
+                        self.reject_global(whence)?,
+                    Err(TestCaseError::Fail(_)) => {
+                        // Shrink the failure on this thread, as `run()`
+                        // would have.
+                        self.gen_and_run_new_case(strategy, test, case_seed,
+                                                  persist_path.as_ref())?;
+                    },
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replay the failures persisted for this test, setting `seed` to the
+    /// seed of each, and return the path to persist new failures to.
+    fn run_persisted<S : Strategy,
+                     F : Fn (&ValueFor<S>) -> TestCaseResult>
+        (&mut self, strategy: &S, test: &F, seed: &mut Option<[u32;4]>)
+         -> Result<Option<PathBuf>, TestError<ValueFor<S>>>
     {
         let persist_path = self.config.failure_persistence.resolve(
-            self.source_file());
+            self.source_file(), &self.output);
 
         let old_rng = self.rng.clone();
-        for persisted_seed in load_persisted_failures(persist_path.as_ref())
+        for persisted_seed in load_persisted_failures(
+            persist_path.as_ref(), &self.output)
         {
+            *seed = Some(persisted_seed);
             self.rng = XorShiftRng::from_seed(persisted_seed);
-            self.gen_and_run_case(strategy, &test)?;
+            self.gen_and_run_case(strategy, test)?;
         }
         self.rng = old_rng;
 
-        while self.successes < self.config.cases {
-            // Generate a new seed and make an RNG from that so that we know
-            // what seed to persist if this case fails.
-            let seed = self.new_rng_seed();
-            self.rng = XorShiftRng::from_seed(seed);
-            let result = self.gen_and_run_case(strategy, &test);
-            if let Err(TestError::Fail(_, ref value)) = result {
-                save_persisted_failure(persist_path.as_ref(), seed, value);
-            }
+        Ok(persist_path)
+    }
 
-            let _ = result?;
+    /// Generate and run the case seeded by `new_seed`, persisting its seed
+    /// to `persist_path` if it fails.
+    fn gen_and_run_new_case<S : Strategy,
+                            F : Fn (&ValueFor<S>) -> TestCaseResult>
+        (&mut self, strategy: &S, test: &F, new_seed: [u32;4],
+         persist_path: Option<&PathBuf>)
+         -> Result<(), TestError<ValueFor<S>>>
+    {
+        self.rng = XorShiftRng::from_seed(new_seed);
+        let result = self.gen_and_run_case(strategy, test);
+        if let Err(TestError::Fail(_, ref value)) = result {
+            save_persisted_failure(persist_path, new_seed, value,
+                                   &self.output);
         }
+        result
+    }
 
-        Ok(())
+    /// Format the report written to `Config::json_report_file` by `run()`
+    /// for a test which failed with `error` (or passed, if `None`).
+    fn json_report<T : fmt::Debug>(&self, seed: Option<[u32;4]>,
+                                   error: Option<&TestError<T>>) -> String {
+        let (result, why, input) = match error {
+            None => ("pass", None, None),
+            Some(TestError::Abort(why)) => ("abort", Some(why), None),
+            Some(TestError::Fail(why, input)) =>
+                ("fail", Some(why), Some(format!("{:?}", input))),
+        };
+        // A failing case is neither a success nor a rejection.
+        let case = self.successes + self.global_rejects +
+            if error.is_some() { 1 } else { 0 };
+
+        let mut report = format!(
+            "{{\"version\":{},\"test\":{},\"result\":\"{}\",\"seed\":",
+            JSON_REPORT_VERSION,
+            self.test_name.as_ref().map_or("null".to_owned(),
+                                           |name| json_string(name)),
+            result);
+        match seed {
+            Some(s) if error.is_some() => report.push_str(&format!(
+                "[{},{},{},{}]", s[0], s[1], s[2], s[3])),
+            _ => report.push_str("null"),
+        }
+        report.push_str(&format!(
+            ",\"case\":{},\"input\":{},\"message\":{},\"context\":{{",
+            case,
+            input.map_or("null".to_owned(), |input| json_string(&input)),
+            why.map_or("null".to_owned(), |why| json_string(why.message()))));
+        for (ix, (key, value)) in why.map_or(&[][..], Reason::context)
+            .iter().enumerate()
+        {
+            if ix > 0 {
+                report.push(',');
+            }
+            report.push_str(&format!("{}:{}", json_string(key),
+                                     json_string(value)));
+        }
+        report.push_str(&format!("}},\"shrink_steps\":{}}}",
+                                 self.shrink_steps));
+        report
+    }
+
+    /// Return the statistics of the test cases run so far.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            successes: self.successes,
+            total_cases: self.successes + self.global_rejects,
+            total_rejects: self.global_rejects,
+            local_rejects: self.local_rejects.saturating_add(
+                self.deferred_local_rejects.load(SeqCst) as u32),
+            shrink_steps: self.shrink_steps,
+            wall_time: self.wall_time,
+            _non_exhaustive: (),
+        }
     }
 
     fn gen_and_run_case<S : Strategy, F : Fn (&ValueFor<S>) -> TestCaseResult>
         (&mut self, strategy: &S, f: &F)
         -> Result<(), TestError<ValueFor<S>>>
     {
+        self.case_local_rejects.clear();
         let case = match strategy.new_value(self) {
             Ok(v) => v,
             Err(msg) => return Err(TestError::Abort(msg)),
@@ -806,20 +2260,62 @@ impl TestRunner {
     ///
     /// If the test fails, finds the minimal failing test case. If the test
     /// does not fail, returns whether it succeeded or was filtered out.
+    ///
+    /// While shrinking, inputs which the test rejects (e.g., with
+    /// `prop_assume!`) are treated like passing ones rather than as new
+    /// minima, so the minimal failing input always satisfies the test's
+    /// assumptions.
+    ///
+    /// Shrinking stops early if the `max_shrink_iters` or `max_shrink_time`
+    /// budget of the configuration is exhausted, in which case the reason of
+    /// the failure notes that the reported input may not be minimal.
+    ///
+    /// This can be used to debug a specific input, such as one copied out of
+    /// a failure report, by starting from a `ValueTree` positioned at that
+    /// input. Many value trees can be created that way directly; for example,
+    /// `num::i32::BinarySearch::new(x)` starts at `x` and shrinks towards 0.
+    /// Passing a `Just(x)` value tree tests `x` without shrinking it.
+    ///
+    /// ```
+    /// use proptest::num;
+    /// use proptest::test_runner::{TestError, TestRunner};
+    ///
+    /// let mut runner = TestRunner::default();
+    /// let result = runner.run_one(num::i32::BinarySearch::new(1234), |&x| {
+    ///     assert!(x < 100);
+    ///     Ok(())
+    /// });
+    ///
+    /// match result {
+    ///     Err(TestError::Fail(_, x)) => assert_eq!(100, x),
+    ///     _ => panic!("Unexpected result: {:?}", result),
+    /// }
+    /// ```
     pub fn run_one<V : ValueTree,
                    F : Fn (&V::Value) -> TestCaseResult>
         (&mut self, mut case: V, test: F) -> Result<bool, TestError<V::Value>>
     {
         let curr = case.current();
-        match panic_guard(&curr, &test) {
+        match self.run_case(&curr, &test) {
             Ok(_) => Ok(true),
             Err(TestCaseError::Fail(why)) => {
                 let mut last_failure = (why, curr);
+                let start_time = self.config.max_shrink_time
+                    .map(|_| Instant::now());
+                let mut iters = 0u32;
+                let mut budget_exhausted = false;
 
-                if case.simplify() {
+                if !case.is_terminal() && case.simplify() {
                     loop {
+                        if self.shrink_budget_exhausted(start_time, iters) {
+                            budget_exhausted = true;
+                            break;
+                        }
+                        iters += 1;
+                        self.shrink_steps += 1;
+
                         let curr = case.current();
-                        let passed = match panic_guard(&curr, &test) {
+                        let passed = match self.run_case(&curr, &test) {
                             // Rejections are effectively a pass here,
                             // since they indicate that any behaviour of
                             // the function under test is acceptable.
@@ -835,13 +2331,18 @@ impl TestRunner {
                             if !case.complicate() {
                                 break;
                             }
-                        } else if !case.simplify() {
+                        } else if case.is_terminal() || !case.simplify() {
                             break;
                         }
                     }
                 }
 
-                Err(TestError::Fail(last_failure.0, last_failure.1))
+                let (mut why, minimal) = last_failure;
+                if budget_exhausted {
+                    note_shrink_budget_exhausted(&mut why, iters);
+                }
+
+                Err(TestError::Fail(why, minimal))
             },
             Err(TestCaseError::Reject(whence)) => {
                 self.reject_global(whence)?;
@@ -850,18 +2351,196 @@ impl TestRunner {
         }
     }
 
+    /// Like `run_one()`, but shrinks a failing input by trying up to
+    /// `workers` simplifications of it at once on a `rayon` thread pool.
+    ///
+    /// In each round, the test is run in parallel against `workers` clones
+    /// of the failing value tree, simplified one to `workers` times,
+    /// respectively. Of the candidates which still fail, the one with the
+    /// lowest `current_complexity()` becomes the new failing tree, ties going
+    /// to the most simplified one. If every candidate passes, shrinking
+    /// continues by complicating the once-simplified candidate, just as in
+    /// `run_one()`. The outcome therefore does not depend on which worker
+    /// finishes first, and with a single worker it is identical to that of
+    /// `run_one()`.
+    ///
+    /// Each candidate counts against the `max_shrink_iters` budget. This is
+    /// worthwhile when `test` is slow and the tree has many independent
+    /// values to simplify; `test` must be safe to run on several threads at
+    /// once.
+    ///
+    /// This method is only available with the `rayon` feature.
+    ///
+    /// ```
+    /// use proptest::num;
+    /// use proptest::test_runner::{TestError, TestRunner};
+    ///
+    /// let mut runner = TestRunner::default();
+    /// let result = runner.shrink_parallel(
+    ///     num::i32::BinarySearch::new(1234), |&x| {
+    ///         assert!(x < 100);
+    ///         Ok(())
+    ///     }, 4);
+    ///
+    /// match result {
+    ///     Err(TestError::Fail(_, x)) => assert_eq!(100, x),
+    ///     _ => panic!("Unexpected result: {:?}", result),
+    /// }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn shrink_parallel<V, F>(&mut self, case: V, test: F, workers: usize)
+                                 -> Result<bool, TestError<V::Value>>
+    where V : ValueTree + Clone + Send, V::Value : Send,
+          F : Fn (&V::Value) -> TestCaseResult + Sync
+    {
+        use std::cmp;
+        use rayon::prelude::*;
+
+        let curr = case.current();
+        let why = match self.run_case(&curr, &test) {
+            Ok(_) => return Ok(true),
+            Err(TestCaseError::Fail(why)) => why,
+            Err(TestCaseError::Reject(whence)) => {
+                self.reject_global(whence)?;
+                return Ok(false);
+            },
+        };
+
+        let workers = cmp::max(1, workers);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(workers)
+            .build().map_err(|e| TestError::Abort(format!(
+                "Failed to start shrinking threads: {}", e).into()))?;
+        let timeout = self.config.case_timeout();
+        let fork = self.config.forks();
+        let assume_rejects = &self.assume_rejects;
+        let start_time = self.config.max_shrink_time.map(|_| Instant::now());
+        let mut last_failure = (why, curr);
+        let mut iters = 0u32;
+        let mut budget_exhausted = false;
+        let mut case = case;
+        // Whether `case` is known to fail, rather than having just been
+        // complicated and not run yet.
+        let mut failing = true;
+
+        loop {
+            if self.shrink_budget_exhausted(start_time, iters) {
+                budget_exhausted = true;
+                break;
+            }
+
+            let limit = cmp::min(
+                workers, (self.config.max_shrink_iters - iters) as usize);
+            let mut candidates = Vec::with_capacity(limit);
+            if !failing {
+                candidates.push(case.clone());
+            }
+            while candidates.len() < limit &&
+                !case.is_terminal() && case.simplify()
+            {
+                candidates.push(case.clone());
+            }
+            if candidates.is_empty() {
+                break;
+            }
+            iters += candidates.len() as u32;
+            self.shrink_steps += candidates.len() as u32;
+
+            let results: Vec<_> = pool.install(|| {
+                candidates.into_par_iter().map(|candidate| {
+                    let curr = candidate.current();
+                    let result = with_assume_rejects(
+                        assume_rejects,
+                        || run_case_isolated(fork, timeout, &curr, &test));
+                    (candidate, curr, result)
+                }).collect()
+            });
+
+            let mut first_passed = None;
+            let mut best_failure: Option<(V, Reason, V::Value)> = None;
+            for (candidate, curr, result) in results {
+                match result {
+                    Err(TestCaseError::Fail(why)) => {
+                        let better = best_failure.as_ref().map_or(
+                            true, |&(ref best, _, _)|
+                            candidate.current_complexity() <=
+                                best.current_complexity());
+                        if better {
+                            best_failure = Some((candidate, why, curr));
+                        }
+                    },
+                    // As in `run_one()`, rejections count as passes.
+                    _ => if first_passed.is_none() && best_failure.is_none() {
+                        first_passed = Some(candidate);
+                    },
+                }
+            }
+
+            if let Some((candidate, why, curr)) = best_failure {
+                last_failure = (why, curr);
+                case = candidate;
+                failing = true;
+            } else {
+                case = first_passed.expect("no candidate passed or failed");
+                if !case.complicate() {
+                    break;
+                }
+                failing = false;
+            }
+        }
+
+        let (mut why, minimal) = last_failure;
+        if budget_exhausted {
+            note_shrink_budget_exhausted(&mut why, iters);
+        }
+
+        Err(TestError::Fail(why, minimal))
+    }
+
+    /// Return whether shrinking which started at `start_time` (if the
+    /// configuration limits the shrinking time) must stop after `iters`
+    /// iterations.
+    fn shrink_budget_exhausted(&self, start_time: Option<Instant>,
+                               iters: u32) -> bool {
+        let out_of_time = self.config.max_shrink_time
+            .and_then(|max| start_time.map(|start| start.elapsed() >= max))
+            .unwrap_or(false);
+        iters >= self.config.max_shrink_iters || out_of_time
+    }
+
+    /// Run `test` against `case`, failing it if it panics or exceeds the
+    /// configured `case_timeout`, in a child process if so configured.
+    fn run_case<V, F>(&self, case: &V, test: &F) -> TestCaseResult
+    where
+        F: Fn(&V) -> TestCaseResult
+    {
+        with_assume_rejects(
+            &self.assume_rejects,
+            || run_case_isolated(self.config.forks(),
+                                 self.config.case_timeout(), case, test))
+    }
+
     /// Update the state to account for a local rejection from `whence`, and
     /// return `Ok` if the caller should keep going or `Err` to abort.
+    ///
+    /// Each distinct `whence` may reject up to `max_local_rejects` inputs
+    /// per test case, and all of them together up to
+    /// `max_total_local_rejects` inputs over the whole test.
     pub fn reject_local<R>(&mut self, whence: R) -> Result<(), Reason>
     where
         R: Into<Reason>
     {
-        if self.local_rejects >= self.config.max_local_rejects {
+        let whence = whence.into();
+        let case_rejects = self.case_local_rejects.get(&whence)
+            .cloned().unwrap_or(0);
+        if self.local_rejects >= self.config.max_total_local_rejects {
             Err("Too many local rejects".into())
+        } else if case_rejects >= self.config.max_local_rejects {
+            Err(format!("Too many local rejects from {}", whence).into())
         } else {
             self.local_rejects += 1;
-            Self::insert_or_increment(&mut self.local_reject_detail,
-                whence.into());
+            Self::insert_or_increment(&mut self.case_local_rejects,
+                whence.clone());
+            Self::insert_or_increment(&mut self.local_reject_detail, whence);
             Ok(())
         }
     }
@@ -905,15 +2584,466 @@ impl TestRunner {
 mod test {
     use std::cell::Cell;
     use std::fs;
+    use std::io::Read;
 
     use super::*;
     use strategy::Strategy;
+    use rand::Rng;
 
+    #[test]
+    fn test_name_determines_seed_if_configured() {
+        fn first_values(name: &str, seed_from_test_name: bool) -> Vec<u32> {
+            let mut runner = TestRunner::new(Config {
+                seed_from_test_name,
+                .. Config::default()
+            });
+            runner.set_test_name(name);
+            (0..4).map(|_| runner.rng().next_u32()).collect()
+        }
+
+        assert_eq!(first_values("foo::bar", true),
+                   first_values("foo::bar", true));
+        assert!(first_values("foo::bar", true) !=
+                first_values("foo::baz", true));
+        assert!(first_values("", true) != first_values("foo::bar", true));
+        assert!(first_values("foo::bar", false) !=
+                first_values("foo::bar", false));
+    }
 
     #[test]
-    fn gives_up_after_too_many_rejections() {
-        let config = Config::default();
-        let mut runner = TestRunner::new(config.clone());
+    fn deferred_generation_shares_config_and_flat_map_regens() {
+        #[derive(Debug)]
+        struct CanRegen;
+        impl Strategy for CanRegen {
+            type Value = Just<bool>;
+            fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+                Ok(Just(runner.flat_map_regen()))
+            }
+        }
+
+        let mut runner = TestRunner::new(Config {
+            max_flat_map_regens: 2,
+            .. Config::default()
+        });
+        let deferred = runner.defer();
+        assert!(runner.flat_map_regen());
+        assert!(deferred.new_value(&CanRegen).unwrap().current());
+        assert!(!deferred.new_value(&CanRegen).unwrap().current());
+        assert!(!runner.flat_map_regen());
+    }
+
+    #[test]
+    fn merge_env_var_parses_known_vars() {
+        let mut config = builtin_default_config();
+        config.merge_env_var("PROPTEST_CASES".into(), "42".into()).unwrap();
+        config.merge_env_var("PROPTEST_MAX_SHRINK_TIME".into(), "1500".into())
+            .unwrap();
+        #[cfg(feature = "timeout")]
+        config.merge_env_var("PROPTEST_CASE_TIMEOUT".into(), "250".into())
+            .unwrap();
+        config.merge_env_var("PROPTEST_PRINT_STATS".into(), "1".into())
+            .unwrap();
+        config.merge_env_var("PROPTEST_VERBOSE".into(), "false".into())
+            .unwrap();
+        config.merge_env_var("PROPTEST_SEED_FROM_TEST_NAME".into(),
+                             "true".into()).unwrap();
+        config.merge_env_var("PROPTEST_FAILURE_PERSISTENCE".into(),
+                             "direct:some/file.txt".into()).unwrap();
+        config.merge_env_var("PROPTEST_JSON_REPORT_FILE".into(),
+                             "report.json".into()).unwrap();
+        config.merge_env_var("PROPTEST_JSON_REPORT_ALWAYS".into(),
+                             "true".into()).unwrap();
+        config.merge_env_var("PROPTEST_SOMETHING_ELSE".into(),
+                             "whatever".into()).unwrap();
+        config.merge_env_var("NOT_PROPTEST".into(), "whatever".into())
+            .unwrap();
+
+        assert_eq!(42, config.cases);
+        assert_eq!(Some(Duration::from_millis(1500)), config.max_shrink_time);
+        #[cfg(feature = "timeout")]
+        assert_eq!(Some(Duration::from_millis(250)), config.case_timeout);
+        assert!(config.print_stats);
+        assert!(!config.verbose);
+        assert!(config.seed_from_test_name);
+        assert_eq!(FailurePersistence::Direct("some/file.txt"),
+                   config.failure_persistence);
+        assert_eq!(Some(PathBuf::from("report.json")),
+                   config.json_report_file);
+        assert!(config.json_report_always);
+    }
+
+    #[test]
+    fn shrinking_stops_when_budget_exhausted() {
+        let mut runner = TestRunner::new(Config {
+            max_shrink_iters: 4,
+            .. Config::default()
+        });
+        let runs = Cell::new(0);
+        let result = runner.run_one(
+            ::num::u64::BinarySearch::new(1 << 40), |&v| {
+                runs.set(runs.get() + 1);
+                prop_assert!(v < 10);
+                Ok(())
+            });
+
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert!(v > 10);
+                assert!(why.message().contains("shrink budget"),
+                        "Bad reason: {}", why);
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        // The initial run plus the 4 shrinking iterations.
+        assert_eq!(5, runs.get());
+    }
+
+    #[cfg(feature = "timeout")]
+    #[test]
+    fn slow_cases_time_out_and_shrink() {
+        let mut runner = TestRunner::new(Config {
+            case_timeout: Some(Duration::from_millis(10)),
+            .. Config::default()
+        });
+        let result = runner.run_one(
+            ::num::u64::BinarySearch::new(100), |&v| {
+                if v >= 20 {
+                    ::std::thread::sleep(Duration::from_millis(v));
+                }
+                Ok(())
+            });
+
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert_eq!(20, v);
+                assert_eq!("timed out", why.message());
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[cfg(all(feature = "fork", unix))]
+    #[test]
+    fn forked_cases_which_exit_or_crash_fail_and_shrink() {
+        let mut runner = TestRunner::new(Config {
+            fork: true,
+            .. Config::default()
+        });
+        let result = runner.run_one(
+            ::num::u32::BinarySearch::new(100), |&v| {
+                if v >= 20 {
+                    ::std::process::exit(0);
+                }
+                Ok(())
+            });
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert_eq!(20, v);
+                assert_eq!("Test process exited without reporting a result",
+                           why.message());
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+
+        let result = runner.run_one(
+            ::num::u32::BinarySearch::new(100), |&v| {
+                if v >= 20 {
+                    unsafe { ::libc::abort(); }
+                }
+                Ok(())
+            });
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert_eq!(20, v);
+                assert_eq!(format!("Test process killed by signal {}",
+                                   ::libc::SIGABRT),
+                           why.message());
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[cfg(all(feature = "timeout", unix))]
+    #[test]
+    fn cases_which_hang_are_killed_after_timeout() {
+        let mut runner = TestRunner::new(Config {
+            case_timeout: Some(Duration::from_millis(100)),
+            .. Config::default()
+        });
+        let result = runner.run_one(
+            ::num::u32::BinarySearch::new(100), |&v| {
+                if v >= 20 {
+                    ::std::thread::sleep(Duration::from_secs(3600));
+                }
+                Ok(())
+            });
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert_eq!(20, v);
+                assert_eq!("timed out", why.message());
+                assert_eq!(
+                    &[("case_timeout".to_owned(), "100ms".to_owned())][..],
+                    why.context());
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[cfg(all(feature = "fork", unix))]
+    #[test]
+    fn forked_cases_report_results_with_context() {
+        let mut runner = TestRunner::new(Config {
+            fork: true,
+            .. Config::default()
+        });
+        let result = runner.run_one(
+            ::num::u32::BinarySearch::new(100), |&v| {
+                prop_add_context!("half", v / 2);
+                prop_assert!(v < 20, "too big");
+                Ok(())
+            });
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert_eq!(20, v);
+                assert!(why.message().starts_with("too big"),
+                        "Bad reason: {}", why);
+                assert_eq!(&[("half".to_owned(), "10".to_owned())][..],
+                           why.context());
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+
+        let result = runner.run_one(
+            ::num::u32::BinarySearch::new(1), |_| {
+                Err(TestCaseError::reject("nope"))
+            });
+        assert_eq!(Ok(false), result.map_err(|e| e.to_string()));
+    }
+
+    #[test]
+    fn unexhausted_shrink_budget_is_not_reported() {
+        let mut runner = TestRunner::default();
+        let result = runner.run_one(
+            ::num::u64::BinarySearch::new(1 << 10), |&v| {
+                if v < 10 {
+                    Ok(())
+                } else {
+                    Err(TestCaseError::fail("too big"))
+                }
+            });
+        assert_eq!(Err(TestError::Fail("too big".into(), 10)), result);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_shrinking_matches_sequential_shrinking() {
+        use num::i32::BinarySearch;
+        use tuple::TupleValueTree;
+
+        fn test(&(a, b): &(i32, i32)) -> TestCaseResult {
+            prop_assert!(a + b < 500 || b % 7 != 0);
+            Ok(())
+        }
+
+        let case = TupleValueTree::new((BinarySearch::new(700),
+                                        BinarySearch::new(840)));
+        let sequential = TestRunner::default().run_one(case.clone(), test);
+        assert_eq!(sequential, TestRunner::default()
+                   .shrink_parallel(case.clone(), test, 1));
+
+        for &workers in &[2, 4, 16] {
+            match TestRunner::default()
+                .shrink_parallel(case.clone(), test, workers)
+            {
+                Err(TestError::Fail(_, (a, b))) => {
+                    assert!(a + b >= 500 && b % 7 == 0, "{:?}", (a, b));
+                    // Neither value can be simplified any further.
+                    assert!(a == 0 || a + b - 1 < 500, "{:?}", (a, b));
+                },
+                e => panic!("Unexpected result: {:?}", e),
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn run_par_runs_all_cases_and_counts_rejects() {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        let runs = AtomicUsize::new(0);
+        let stats = runner.run_par(
+            &(0u32..10).prop_filter("odd", |v| 0 == v % 2), |&v| {
+                runs.fetch_add(1, SeqCst);
+                if v < 4 {
+                    Err(TestCaseError::reject("too small"))
+                } else {
+                    Ok(())
+                }
+            }).unwrap();
+
+        assert_eq!(256, stats.successes);
+        assert!(stats.total_rejects > 0 && stats.local_rejects > 0,
+                "Unexpected stats: {}", stats);
+        assert_eq!(stats.total_cases as usize, runs.load(SeqCst));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn run_par_shrinks_first_failure_reproducibly() {
+        fn run() -> Result<Stats, TestError<u32>> {
+            let mut runner = TestRunner::new(Config {
+                failure_persistence: FailurePersistence::Off,
+                seed_from_test_name: true,
+                .. Config::default()
+            });
+            runner.set_test_name("run_par_test");
+            runner.run_par(&(0u32..1_000_000).no_shrink(), |&v| {
+                prop_assert!(v < 990_000);
+                Ok(())
+            })
+        }
+
+        let result = run();
+        match result {
+            Err(TestError::Fail(_, v)) => assert!(v >= 990_000),
+            ref e => panic!("Unexpected result: {:?}", e),
+        }
+        assert_eq!(result, run());
+
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        match runner.run_par(&(0u32..10_000), |&v| {
+            prop_assert!(v < 500);
+            Ok(())
+        }) {
+            Err(TestError::Fail(_, v)) => assert_eq!(500, v),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_shrinking_respects_budget() {
+        let mut runner = TestRunner::new(Config {
+            max_shrink_iters: 6,
+            .. Config::default()
+        });
+        let runs = AtomicUsize::new(0);
+        let result = runner.shrink_parallel(
+            ::num::u64::BinarySearch::new(1 << 40), |&v| {
+                runs.fetch_add(1, SeqCst);
+                prop_assert!(v < 10);
+                Ok(())
+            }, 4);
+
+        match result {
+            Err(TestError::Fail(why, v)) => {
+                assert!(v > 10);
+                assert!(why.message().contains("shrink budget"),
+                        "Bad reason: {}", why);
+            },
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        // The initial run plus the 6 shrinking iterations.
+        assert_eq!(7, runs.load(SeqCst));
+    }
+
+    #[test]
+    fn merge_env_var_rejects_invalid_values() {
+        let mut config = builtin_default_config();
+        assert_eq!(
+            Err(EnvConfigError::InvalidValue(
+                "PROPTEST_MAX_LOCAL_REJECTS".to_owned(), "lots".to_owned())),
+            config.merge_env_var("PROPTEST_MAX_LOCAL_REJECTS".into(),
+                                 "lots".into()));
+        assert!(config.merge_env_var("PROPTEST_FAILURE_PERSISTENCE".into(),
+                                     "sometimes".into()).is_err());
+        assert!(config.merge_env_var("PROPTEST_PRINT_STATS".into(),
+                                     "yes".into()).is_err());
+        assert_eq!(builtin_default_config(), config);
+    }
+
+    #[test]
+    fn from_file_reads_toml_settings() {
+        const DIR: &'static str = "config-file-test";
+        let _ = fs::remove_dir_all(DIR);
+        fs::create_dir_all(Path::new(DIR).join(".config")).unwrap();
+        fs::create_dir_all(Path::new(DIR).join("a/b")).unwrap();
+        let path = Path::new(DIR).join(".config/proptest.toml");
+        fs::write(&path, "# Shared settings\n\
+                          [proptest] # optional\n\
+                          cases = 1_000\n\
+                          \n\
+                          max_shrink_time = 1500 # milliseconds\n\
+                          verbose = false\n\
+                          failure_persistence = \"direct:some/file.txt\"\n\
+                          json_report_file = 'C:\\reports\\x.json'\n")
+            .unwrap();
+
+        let found = find_config_file(&Path::new(DIR).join("a/b"));
+        let config = Config::from_file(&path);
+        let _ = fs::remove_dir_all(DIR);
+
+        assert_eq!(Some(path), found);
+        let config = config.unwrap();
+        assert_eq!(Config {
+            cases: 1000,
+            max_shrink_time: Some(Duration::from_millis(1500)),
+            verbose: false,
+            failure_persistence: FailurePersistence::Direct("some/file.txt"),
+            json_report_file: Some(PathBuf::from("C:\\reports\\x.json")),
+            .. builtin_default_config()
+        }, config);
+    }
+
+    #[test]
+    fn merge_file_line_rejects_invalid_lines() {
+        let mut config = builtin_default_config();
+        for line in &["cases", "= 4", "cases = ", "cases = \"4", "Cases = 4",
+                      "cases = \"4\" 5", "[profile]", "[proptest.x]",
+                      "[[proptest]]"] {
+            match config.merge_file_line(3, line) {
+                Err(FileConfigError::Syntax(3, ref text)) =>
+                    assert_eq!(line, text),
+                result => panic!("Unexpected result for {}: {:?}",
+                                 line, result),
+            }
+        }
+        match config.merge_file_line(4, "max_local_rejects = \"lots\"") {
+            Err(FileConfigError::InvalidValue(4, ref key, ref value)) => {
+                assert_eq!("max_local_rejects", key);
+                assert_eq!("lots", value);
+            },
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        match config.merge_file_line(5, "reject_warning_ratio = 0.5") {
+            Err(FileConfigError::UnknownField(5, ref key)) =>
+                assert_eq!("reject_warning_ratio", key),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(builtin_default_config(), config);
+    }
+
+    #[test]
+    fn failure_persistence_parse() {
+        assert_eq!(Some(FailurePersistence::Off),
+                   FailurePersistence::parse("off"));
+        assert_eq!(Some(FailurePersistence::SourceParallel("regs")),
+                   FailurePersistence::parse("source-parallel:regs"));
+        assert_eq!(Some(FailurePersistence::WithSource("txt")),
+                   FailurePersistence::parse("with-source:txt"));
+        assert_eq!(None, FailurePersistence::parse("direct"));
+    }
+
+
+    #[test]
+    fn gives_up_after_too_many_rejections() {
+        let config = Config::default();
+        let mut runner = TestRunner::new(config.clone());
         let runs = Cell::new(0);
         let result = runner.run(&(0u32..), |_| {
             runs.set(runs.get() + 1);
@@ -926,11 +3056,223 @@ mod test {
         assert_eq!(config.max_global_rejects + 1, runs.get());
     }
 
+    #[test]
+    fn shrinking_skips_rejected_inputs() {
+        use num::u32::BinarySearch;
+
+        let mut runner = TestRunner::default();
+        let result = runner.run_one(BinarySearch::new(10_001), |&v| {
+            prop_assume!(1 == v % 2);
+            prop_assert!(v < 100);
+            Ok(())
+        });
+
+        match result {
+            // Binary search cannot necessarily find 101 since every even
+            // value passes, but it must never stop at an even value.
+            Err(TestError::Fail(_, v)) =>
+                assert!(1 == v % 2 && v >= 101, "{}", v),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
     #[test]
     fn test_pass() {
         let mut runner = TestRunner::default();
         let result = runner.run(&(1u32..), |&v| { assert!(v > 0); Ok(()) });
-        assert_eq!(Ok(()), result);
+        assert_eq!(Ok(runner.config().cases), result.map(|s| s.successes));
+    }
+
+    #[test]
+    fn run_reports_reject_stats() {
+        let mut runner = TestRunner::new(Config {
+            cases: 100,
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        let stats = runner.run(&((0u32..10).prop_filter("odd", |v| 0 == v % 2)),
+                               |&v| {
+            if v < 4 {
+                Err(TestCaseError::reject("too small"))
+            } else {
+                Ok(())
+            }
+        }).unwrap();
+
+        assert_eq!(100, stats.successes);
+        assert_eq!(stats.successes + stats.total_rejects, stats.total_cases);
+        // Of the even values, 0 and 2 are rejected, so about 40% of cases.
+        assert!(stats.total_rejects > 20 && stats.total_rejects < 200,
+                "Unexpected stats: {}", stats);
+        assert!(stats.local_rejects > 20, "Unexpected stats: {}", stats);
+        assert_eq!(stats, runner.stats());
+    }
+
+    #[test]
+    fn local_reject_budget_is_per_filter_and_per_case() {
+        let mut runner = TestRunner::new(Config {
+            cases: 64,
+            max_local_rejects: 16,
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        // Each filter rejects about half its inputs, so they would exhaust a
+        // budget of 16 shared by both or by the whole run, but essentially
+        // never reject 16 times in a row for one case.
+        let input = (
+            (0u32..1000).prop_filter("even", |v| 0 == v % 2),
+            (0u32..1000).prop_filter("odd", |v| 1 == v % 2));
+        let stats = runner.run(&input, |_| Ok(())).unwrap();
+        assert!(stats.local_rejects > 16, "Unexpected stats: {}", stats);
+
+        let mut runner = TestRunner::new(runner.config().clone());
+        let result = runner.run(
+            &(Just(0u32), (0u32..10).prop_filter("never", |_| false)),
+            |_| Ok(()));
+        match result {
+            Err(TestError::Abort(reason)) =>
+                assert_eq!("Too many local rejects from never",
+                           reason.message()),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let mut runner = TestRunner::new(Config {
+            max_total_local_rejects: 16,
+            .. runner.config().clone()
+        });
+        match runner.run(&input, |_| Ok(())) {
+            Err(TestError::Abort(reason)) =>
+                assert_eq!("Too many local rejects", reason.message()),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn stats_count_successes_before_failure() {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        let passes_before_failure = Cell::new(0);
+        let failed = Cell::new(false);
+        let result = runner.run(&(0u32..100), |&v| {
+            if v >= 90 {
+                failed.set(true);
+                Err(TestCaseError::fail("too big"))
+            } else {
+                if !failed.get() {
+                    passes_before_failure.set(passes_before_failure.get() + 1);
+                }
+                Ok(())
+            }
+        });
+
+        assert!(result.is_err());
+        assert_eq!(passes_before_failure.get(), runner.stats().successes);
+        // Binary searching down to 90 always tries at least two values.
+        assert!(runner.stats().shrink_steps >= 2);
+        assert!(format!("{}", runner).contains(&format!(
+            "successes: {}", passes_before_failure.get())));
+    }
+
+    /// A `Write` whose output can still be inspected after it has been given
+    /// to a `TestRunner`.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+    impl SharedBuf {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn output_goes_to_configured_sink() {
+        let buf = SharedBuf::default();
+        let mut runner = TestRunner::new(Config {
+            cases: 10,
+            print_stats: true,
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        runner.set_output(buf.clone());
+        runner.run(&(0u32..10), |_| Ok(())).unwrap();
+
+        let output = buf.contents();
+        assert!(output.starts_with("proptest: "), "Bad output: {:?}", output);
+        assert!(output.contains("10 successes"), "Bad output: {:?}", output);
+    }
+
+    #[test]
+    fn non_verbose_runner_prints_nothing() {
+        let buf = SharedBuf::default();
+        let mut runner = TestRunner::new(Config {
+            cases: 10,
+            print_stats: true,
+            reject_warning_ratio: 0.0,
+            verbose: false,
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        runner.set_output(buf.clone());
+        runner.run(&(0u32..10), |&v| if 0 == v % 2 {
+            Err(TestCaseError::reject("odd"))
+        } else {
+            Ok(())
+        }).unwrap();
+
+        assert_eq!("", buf.contents());
+    }
+
+    #[test]
+    fn context_of_minimal_failure_is_reported() {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: FailurePersistence::Off,
+            .. Config::default()
+        });
+        let result = runner.run(&(0u32..100), |&v| {
+            prop_add_context!("doubled", v * 2);
+            prop_assert!(v < 10);
+            Ok(())
+        });
+
+        match result {
+            Err(TestError::Fail(why, 10)) => {
+                assert_eq!(&[("doubled".to_owned(), "20".to_owned())][..],
+                           why.context());
+                let message = TestError::Fail(why, 10).to_string();
+                assert!(message.ends_with("input: 10\n    doubled = 20"),
+                        "Bad message: {:?}", message);
+            },
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn context_attached_to_panics_and_restored_after_nested_case() {
+        CONTEXT.with(|context| context.borrow_mut().clear());
+        add_context("outer".to_owned(), "0".to_owned());
+
+        let result = panic_guard(&(), &|_: &()| -> TestCaseResult {
+            prop_add_context!("inner", "x");
+            panic!("boom")
+        });
+        match result {
+            Err(TestCaseError::Fail(why)) => {
+                assert_eq!("boom", why.message());
+                assert_eq!(&[("inner".to_owned(), "\"x\"".to_owned())][..],
+                           why.context());
+            },
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert_eq!(vec![("outer".to_owned(), "0".to_owned())],
+                   CONTEXT.with(|context| context.replace(vec![])));
     }
 
     #[test]
@@ -992,18 +3334,21 @@ mod test {
 
     #[test]
     fn persistence_file_location_resolved_correctly() {
+        let output = Output::default();
+
         // If off, there is never a file
-        assert_eq!(None, FailurePersistence::Off.resolve(None));
+        assert_eq!(None, FailurePersistence::Off.resolve(None, &output));
         assert_eq!(None, FailurePersistence::Off.resolve(
-            Some(&TEST_PATHS.subdir_file)));
+            Some(&TEST_PATHS.subdir_file), &output));
 
         // For direct, we don't care about the source file, and instead always
         // use whatever is in the config.
         assert_eq!(Some(Path::new("bar.txt").to_owned()),
-                   FailurePersistence::Direct("bar.txt").resolve(None));
+                   FailurePersistence::Direct("bar.txt").resolve(
+                       None, &output));
         assert_eq!(Some(Path::new("bar.txt").to_owned()),
                    FailurePersistence::Direct("bar.txt").resolve(
-                       Some(&TEST_PATHS.subdir_file)));
+                       Some(&TEST_PATHS.subdir_file), &output));
 
         // For WithSource, only the extension changes, but we get nothing if no
         // source file was configured.
@@ -1013,31 +3358,34 @@ mod test {
         fn absolute_path_case() {
             assert_eq!(Some(Path::new("/foo/bar.ext").to_owned()),
                        FailurePersistence::WithSource("ext").resolve(
-                           Some(Path::new("/foo/bar.rs"))));
+                           Some(Path::new("/foo/bar.rs")),
+                           &Output::default()));
         }
         #[cfg(not(unix))]
         fn absolute_path_case() { }
         absolute_path_case();
         assert_eq!(None,
-                   FailurePersistence::WithSource("ext").resolve(None));
+                   FailurePersistence::WithSource("ext").resolve(
+                       None, &output));
 
         // For SourceParallel, we make a sibling directory tree and change the
         // extensions to .txt ...
         assert_eq!(Some(TEST_PATHS.crate_root.join("sib").join("foo.txt")),
                    FailurePersistence::SourceParallel("sib").resolve(
-                       Some(&TEST_PATHS.src_file)));
+                       Some(&TEST_PATHS.src_file), &output));
         assert_eq!(Some(TEST_PATHS.crate_root.join("sib")
                         .join("strategy").join("foo.txt")),
                    FailurePersistence::SourceParallel("sib").resolve(
-                       Some(&TEST_PATHS.subdir_file)));
+                       Some(&TEST_PATHS.subdir_file), &output));
         // ... but if we can't find lib.rs / main.rs, give up and set the
         // extension instead ...
         assert_eq!(Some(TEST_PATHS.crate_root.join("foo.sib")),
                    FailurePersistence::SourceParallel("sib").resolve(
-                       Some(&TEST_PATHS.misplaced_file)));
+                       Some(&TEST_PATHS.misplaced_file), &output));
         // ... and if no source is configured, we do nothing
         assert_eq!(None,
-                   FailurePersistence::SourceParallel("ext").resolve(None));
+                   FailurePersistence::SourceParallel("ext").resolve(
+                       None, &output));
     }
 
     #[derive(Clone, Copy, PartialEq)]
@@ -1108,6 +3456,57 @@ mod test {
         assert_eq!(first_super_failure, second_super_failure);
     }
 
+    #[test]
+    fn json_reports_written_on_failure_and_optionally_success() {
+        const FILE: &'static str = "json-report-test.json";
+        let _ = fs::remove_file(FILE);
+
+        let config = Config {
+            failure_persistence: FailurePersistence::Off,
+            json_report_file: Some(PathBuf::from(FILE)),
+            .. Config::default()
+        };
+
+        let mut runner = TestRunner::new(config.clone());
+        runner.set_test_name("json_report_test::passes");
+        runner.run(&(0..10), |_| Ok(())).unwrap();
+        assert!(!Path::new(FILE).exists());
+
+        runner = TestRunner::new(config.clone());
+        runner.set_test_name("json_report_test::fails");
+        runner.run(&(0..10000), |&v| {
+            prop_add_context!("half", v / 2);
+            prop_assert!(v < 500, "\"too big\"");
+            Ok(())
+        }).unwrap_err();
+
+        runner = TestRunner::new(Config {
+            json_report_always: true,
+            .. config
+        });
+        runner.run(&(0..10), |_| Ok(())).unwrap();
+
+        let mut contents = String::new();
+        fs::File::open(FILE).unwrap().read_to_string(&mut contents).unwrap();
+        let _ = fs::remove_file(FILE);
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len(), "Unexpected reports: {}", contents);
+
+        assert!(lines[0].starts_with(
+            "{\"version\":1,\"test\":\"json_report_test::fails\",\
+             \"result\":\"fail\",\"seed\":["), "{}", lines[0]);
+        assert!(lines[0].contains(
+            ",\"input\":\"500\",\"message\":\"\\\"too big\\\" at "),
+                "{}", lines[0]);
+        assert!(lines[0].contains(
+            ",\"context\":{\"half\":\"250\"},\"shrink_steps\":"),
+                "{}", lines[0]);
+        assert_eq!(
+            "{\"version\":1,\"test\":null,\"result\":\"pass\",\
+             \"seed\":null,\"case\":256,\"input\":null,\"message\":null,\
+             \"context\":{},\"shrink_steps\":0}", lines[1]);
+    }
+
     #[test]
     fn relative_source_files_absolutified() {
         let expected = [
@@ -0,0 +1,283 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `std::time` values.
+//!
+//! All strategies in this module shrink by binary searching towards the
+//! earliest value of their range, to nanosecond precision.
+
+use std::time::{Duration, SystemTime};
+use std::ops::Range;
+
+use rand::Rng;
+
+use strategy::*;
+use test_runner::*;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+fn duration_to_nanos(d: Duration) -> u128 {
+    u128::from(d.as_secs()) * NANOS_PER_SEC + u128::from(d.subsec_nanos())
+}
+
+fn nanos_to_duration(nanos: u128) -> Duration {
+    Duration::new((nanos / NANOS_PER_SEC) as u64,
+                  (nanos % NANOS_PER_SEC) as u32)
+}
+
+/// Strategy to create `Duration`s.
+///
+/// Created by the `duration()` and `duration_range()` functions in the same
+/// module.
+#[derive(Clone, Copy, Debug)]
+pub struct DurationStrategy {
+    min: Duration,
+    max_offset: u128,
+}
+
+/// `ValueTree` corresponding to `DurationStrategy`.
+///
+/// Binary searches the offset from the minimum value of the range.
+#[derive(Clone, Copy, Debug)]
+pub struct DurationValueTree {
+    min: Duration,
+    lo: u128,
+    curr: u128,
+    hi: u128,
+}
+
+/// Create a strategy to generate arbitrary `Duration`s, from zero up to and
+/// including the largest representable `Duration`.
+pub fn duration() -> DurationStrategy {
+    duration_range(Duration::from_secs(0),
+                   Duration::new(!0, 999_999_999))
+}
+
+/// Create a strategy to generate `Duration`s between `min` and `max`, both
+/// inclusive.
+///
+/// ## Panics
+///
+/// Panics if `max` is less than `min`.
+pub fn duration_range(min: Duration, max: Duration) -> DurationStrategy {
+    assert!(min <= max, "Invalid duration range {:?}..={:?}", min, max);
+
+    DurationStrategy {
+        min,
+        max_offset: duration_to_nanos(max) - duration_to_nanos(min),
+    }
+}
+
+impl Strategy for DurationStrategy {
+    type Value = DurationValueTree;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        // `rand` can't produce `u128`s directly, so combine two `u64`s and
+        // reject anything beyond the smallest enclosing power of two.
+        let mask = match self.max_offset.leading_zeros() {
+            128 => 0,
+            zeros => !0u128 >> zeros,
+        };
+        let offset = loop {
+            let rng = runner.rng();
+            let bits = (u128::from(rng.next_u64()) << 64) |
+                u128::from(rng.next_u64());
+            if bits & mask <= self.max_offset {
+                break bits & mask;
+            }
+        };
+
+        Ok(DurationValueTree {
+            min: self.min,
+            lo: 0,
+            curr: offset,
+            hi: offset,
+        })
+    }
+}
+
+impl DurationValueTree {
+    fn reposition(&mut self) -> bool {
+        let new_mid = self.lo + (self.hi - self.lo) / 2;
+
+        if new_mid == self.curr {
+            false
+        } else {
+            self.curr = new_mid;
+            true
+        }
+    }
+}
+
+impl ValueTree for DurationValueTree {
+    type Value = Duration;
+
+    fn current(&self) -> Duration {
+        self.min + nanos_to_duration(self.curr)
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.hi <= self.lo { return false; }
+
+        self.hi = self.curr;
+        self.reposition()
+    }
+
+    fn complicate(&mut self) -> bool {
+        if self.hi <= self.lo { return false; }
+
+        self.lo = self.curr + 1;
+        self.reposition()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        // Saturate rather than truncate offsets beyond 584 years.
+        if self.curr > u128::from(!0u64) { !0 } else { self.curr as u64 }
+    }
+}
+
+/// Strategy to create `SystemTime`s.
+///
+/// Created by the `system_time()` function in the same module.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemTimeStrategy {
+    start: SystemTime,
+    offset: DurationStrategy,
+}
+
+/// `ValueTree` corresponding to `SystemTimeStrategy`.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemTimeValueTree {
+    start: SystemTime,
+    offset: DurationValueTree,
+}
+
+/// Create a strategy to generate `SystemTime`s within the given range.
+///
+/// Values shrink towards `range.start`.
+///
+/// ## Panics
+///
+/// Panics if `range` is empty.
+pub fn system_time(range: Range<SystemTime>) -> SystemTimeStrategy {
+    let span = range.end.duration_since(range.start)
+        .ok().and_then(|span| span.checked_sub(Duration::new(0, 1)))
+        .expect("Empty SystemTime range");
+
+    SystemTimeStrategy {
+        start: range.start,
+        offset: duration_range(Duration::from_secs(0), span),
+    }
+}
+
+impl Strategy for SystemTimeStrategy {
+    type Value = SystemTimeValueTree;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(SystemTimeValueTree {
+            start: self.start,
+            offset: self.offset.new_value(runner)?,
+        })
+    }
+}
+
+impl ValueTree for SystemTimeValueTree {
+    type Value = SystemTime;
+
+    fn current(&self) -> SystemTime {
+        self.start + self.offset.current()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.offset.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.offset.complicate()
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.offset.current_complexity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn duration_range_stays_in_bounds() {
+        let min = Duration::new(5, 500);
+        let max = Duration::new(7, 10);
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = duration_range(min, max)
+                .new_value(&mut runner).unwrap();
+            loop {
+                let d = case.current();
+                assert!(d >= min && d <= max, "Out of range: {:?}", d);
+                if !case.simplify() { break; }
+            }
+
+            assert_eq!(min, case.current());
+        }
+    }
+
+    #[test]
+    fn duration_covers_whole_range() {
+        let mut runner = TestRunner::default();
+        let mut saw_large = false;
+        for _ in 0..256 {
+            let d = duration().new_value(&mut runner).unwrap().current();
+            saw_large |= d.as_secs() > 1 << 63;
+        }
+
+        assert!(saw_large);
+    }
+
+    #[test]
+    fn degenerate_duration_range() {
+        let d = Duration::new(3, 0);
+        let mut runner = TestRunner::default();
+        let mut case = duration_range(d, d).new_value(&mut runner).unwrap();
+        assert_eq!(d, case.current());
+        assert!(!case.simplify());
+    }
+
+    #[test]
+    fn system_time_stays_in_bounds() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let end = start + Duration::from_secs(60);
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = system_time(start..end)
+                .new_value(&mut runner).unwrap();
+            loop {
+                let t = case.current();
+                assert!(t >= start && t < end);
+                if !case.simplify() { break; }
+            }
+
+            assert_eq!(start, case.current());
+        }
+    }
+
+    #[test]
+    fn test_sanity() {
+        check_strategy_sanity(duration(), None);
+        check_strategy_sanity(
+            duration_range(Duration::from_millis(10), Duration::from_secs(10)),
+            None);
+        check_strategy_sanity(
+            system_time(UNIX_EPOCH..UNIX_EPOCH + Duration::from_secs(3600)),
+            None);
+    }
+}
@@ -16,10 +16,15 @@ use strategy::*;
 use test_runner::*;
 
 /// Common `ValueTree` implementation for all tuple strategies.
+///
+/// Rather than fully shrinking each element in turn, each call to
+/// `simplify()` shrinks whichever element that can still be simplified has the
+/// greatest `current_complexity()`, preferring the leftmost element on ties.
 #[derive(Clone, Copy, Debug)]
 pub struct TupleValueTree<T> {
     tree: T,
-    shrinker: u32,
+    /// Bit set of the elements which can no longer be simplified.
+    exhausted: u32,
     prev_shrinker: Option<u32>,
 }
 
@@ -31,7 +36,7 @@ impl<T> TupleValueTree<T> {
     pub fn new(inner: T) -> Self {
         TupleValueTree {
             tree: inner,
-            shrinker: 0,
+            exhausted: 0,
             prev_shrinker: None,
         }
     }
@@ -57,24 +62,42 @@ macro_rules! tuple {
             }
 
             fn simplify(&mut self) -> bool {
-                $(
-                    if $fld == self.shrinker {
-                        if self.tree.$fld.simplify() {
-                            self.prev_shrinker = Some(self.shrinker);
-                            return true;
-                        } else {
-                            self.shrinker += 1;
+                loop {
+                    let mut best: Option<(u32, u64)> = None;
+                    $(
+                        if 0 == self.exhausted & (1 << $fld) {
+                            let complexity =
+                                self.tree.$fld.current_complexity();
+                            if best.map(|(_, c)| complexity > c)
+                                .unwrap_or(true)
+                            {
+                                best = Some(($fld, complexity));
+                            }
                         }
-                    }
-                )*
-                false
+                    )*
+
+                    let shrinker = match best {
+                        Some((shrinker, _)) => shrinker,
+                        None => return false,
+                    };
+
+                    $(
+                        if $fld == shrinker {
+                            if self.tree.$fld.simplify() {
+                                self.prev_shrinker = Some(shrinker);
+                                return true;
+                            } else {
+                                self.exhausted |= 1 << $fld;
+                            }
+                        }
+                    )*
+                }
             }
 
             fn complicate(&mut self) -> bool {
                 if let Some(shrinker) = self.prev_shrinker {$(
                     if $fld == shrinker {
                         if self.tree.$fld.complicate() {
-                            self.shrinker = shrinker;
                             return true;
                         } else {
                             self.prev_shrinker = None;
@@ -84,6 +107,10 @@ macro_rules! tuple {
                 )*}
                 false
             }
+
+            fn current_complexity(&self) -> u64 {
+                0u64 $(.saturating_add(self.tree.$fld.current_complexity()))*
+            }
         }
     }
 }
@@ -105,6 +132,40 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn complexity_is_sum_of_elements() {
+        let input = (0u32..100, 0u32..100, ::bool::ANY);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let (a, b, c) = case.current();
+            assert_eq!(u64::from(a) + u64::from(b) + c as u64,
+                       case.current_complexity());
+
+            while case.simplify() { }
+            assert_eq!(0, case.current_complexity());
+        }
+    }
+
+    #[test]
+    fn shrinks_most_complex_element_first() {
+        let input = (0u32..10, 1000u32..2000);
+        let mut runner = TestRunner::default();
+
+        for _ in 0..256 {
+            let mut case = input.new_value(&mut runner).unwrap();
+            let (a, b) = case.current();
+            // Already minimal, so the first element would be shrunk instead
+            if 1000 == b { continue; }
+
+            assert!(case.simplify());
+            let (a2, b2) = case.current();
+            assert_eq!(a, a2);
+            assert!(b2 < b);
+        }
+    }
+
     #[test]
     fn shrinks_fully_ltr() {
         fn pass(a: (i32, i32)) -> bool {
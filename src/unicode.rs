@@ -0,0 +1,247 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `char`s restricted to broad Unicode categories.
+//!
+//! `char::any()` may produce control, private-use and unassigned code
+//! points, which many parsers and APIs reject. The strategies here only
+//! produce characters belonging to (a union of) the chosen categories.
+//!
+//! Category membership is determined by the standard library's `char`
+//! predicates, so it follows the Unicode version that `std` was built with.
+//! The set of characters in each category is computed the first time the
+//! category is used.
+//!
+//! Like all `CharStrategy`s, values shrink towards lower code points within
+//! the contiguous run of allowed characters they were generated in.
+
+use std::borrow::Cow;
+use std::char as stdchar;
+use std::cmp;
+
+use char::{self, CharStrategy};
+
+/// A broad classification of characters, corresponding to one of the
+/// character predicates of the standard library.
+///
+/// These are not exactly the Unicode general categories; each variant
+/// documents the property it actually selects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GeneralCategory {
+    /// Characters with the Unicode `Alphabetic` property
+    /// (`char::is_alphabetic`), which covers all letters (`L`), letter
+    /// numbers (`Nl`) and some marks.
+    Letter,
+    /// Characters with the Unicode `Uppercase` property
+    /// (`char::is_uppercase`).
+    UppercaseLetter,
+    /// Characters with the Unicode `Lowercase` property
+    /// (`char::is_lowercase`).
+    LowercaseLetter,
+    /// Characters in the Unicode `Nd`, `Nl` or `No` categories
+    /// (`char::is_numeric`).
+    Number,
+    /// Characters with the Unicode `White_Space` property
+    /// (`char::is_whitespace`).
+    Whitespace,
+    /// Characters in the Unicode `Cc` category (`char::is_control`).
+    Control,
+    /// ASCII punctuation and symbols (`char::is_ascii_punctuation`).
+    AsciiPunctuation,
+}
+
+impl GeneralCategory {
+    fn contains(self, ch: char) -> bool {
+        match self {
+            GeneralCategory::Letter => ch.is_alphabetic(),
+            GeneralCategory::UppercaseLetter => ch.is_uppercase(),
+            GeneralCategory::LowercaseLetter => ch.is_lowercase(),
+            GeneralCategory::Number => ch.is_numeric(),
+            GeneralCategory::Whitespace => ch.is_whitespace(),
+            GeneralCategory::Control => ch.is_control(),
+            GeneralCategory::AsciiPunctuation => ch.is_ascii_punctuation(),
+        }
+    }
+
+    /// Return the sorted, non-overlapping ranges of characters in this
+    /// category.
+    fn ranges(self) -> &'static [(char, char)] {
+        lazy_static! {
+            static ref LETTER: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::Letter);
+            static ref UPPERCASE_LETTER: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::UppercaseLetter);
+            static ref LOWERCASE_LETTER: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::LowercaseLetter);
+            static ref NUMBER: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::Number);
+            static ref WHITESPACE: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::Whitespace);
+            static ref CONTROL: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::Control);
+            static ref ASCII_PUNCTUATION: Vec<(char, char)> =
+                compute_ranges(GeneralCategory::AsciiPunctuation);
+        }
+
+        match self {
+            GeneralCategory::Letter => &LETTER,
+            GeneralCategory::UppercaseLetter => &UPPERCASE_LETTER,
+            GeneralCategory::LowercaseLetter => &LOWERCASE_LETTER,
+            GeneralCategory::Number => &NUMBER,
+            GeneralCategory::Whitespace => &WHITESPACE,
+            GeneralCategory::Control => &CONTROL,
+            GeneralCategory::AsciiPunctuation => &ASCII_PUNCTUATION,
+        }
+    }
+}
+
+fn compute_ranges(category: GeneralCategory) -> Vec<(char, char)> {
+    let mut ranges: Vec<(char, char)> = Vec::new();
+    for ch in (0..stdchar::MAX as u32 + 1).filter_map(stdchar::from_u32) {
+        if !category.contains(ch) { continue; }
+
+        match ranges.last_mut() {
+            Some(&mut (_, ref mut hi)) if *hi as u32 + 1 == ch as u32 =>
+                *hi = ch,
+            _ => ranges.push((ch, ch)),
+        }
+    }
+    ranges
+}
+
+/// Merge sorted lists of inclusive ranges into one sorted list of disjoint,
+/// non-adjacent ranges.
+fn merge_ranges(lists: &[&[(char, char)]]) -> Vec<(char, char)> {
+    let mut all: Vec<(char, char)> = lists.iter()
+        .flat_map(|list| list.iter().cloned()).collect();
+    all.sort();
+
+    let mut merged: Vec<(char, char)> = Vec::with_capacity(all.len());
+    for (lo, hi) in all {
+        match merged.last_mut() {
+            Some(&mut (_, ref mut prev_hi))
+                if lo as u32 <= *prev_hi as u32 + 1 =>
+                *prev_hi = cmp::max(*prev_hi, hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+/// Creates a `CharStrategy` which only generates characters belonging to at
+/// least one of `categories`, using the default biases.
+///
+/// ## Panics
+///
+/// Panics if `categories` is empty.
+///
+/// ```
+/// use proptest::unicode::{char_of, GeneralCategory};
+///
+/// let letters_and_digits = char_of(
+///     &[GeneralCategory::Letter, GeneralCategory::Number]);
+/// # let _ = letters_and_digits;
+/// ```
+pub fn char_of(categories: &[GeneralCategory]) -> CharStrategy<'static> {
+    assert!(!categories.is_empty(), "No categories given to char_of()");
+
+    if 1 == categories.len() {
+        char::ranges(Cow::Borrowed(categories[0].ranges()))
+    } else {
+        let lists = categories.iter().map(|c| c.ranges()).collect::<Vec<_>>();
+        char::ranges(Cow::Owned(merge_ranges(&lists)))
+    }
+}
+
+/// Creates a `CharStrategy` which generates alphabetic characters.
+///
+/// This is equivalent to `char_of(&[GeneralCategory::Letter])`.
+pub fn char_alphabetic() -> CharStrategy<'static> {
+    char_of(&[GeneralCategory::Letter])
+}
+
+/// Creates a `CharStrategy` which generates alphabetic or numeric characters.
+///
+/// This is equivalent to
+/// `char_of(&[GeneralCategory::Letter, GeneralCategory::Number])`.
+pub fn char_alphanumeric() -> CharStrategy<'static> {
+    char_of(&[GeneralCategory::Letter, GeneralCategory::Number])
+}
+
+/// Creates a `CharStrategy` which generates ASCII characters, including
+/// control characters.
+pub fn char_ascii() -> CharStrategy<'static> {
+    char::range('\x00', '\x7F')
+}
+
+/// Creates a `CharStrategy` which generates printable ASCII characters, i.e.,
+/// space through `~`.
+pub fn char_ascii_printable() -> CharStrategy<'static> {
+    char::range(' ', '~')
+}
+
+#[cfg(test)]
+mod test {
+    use strategy::*;
+    use test_runner::*;
+
+    use super::*;
+
+    fn assert_all(strategy: CharStrategy, pred: impl Fn (char) -> bool) {
+        let mut runner = TestRunner::default();
+        for _ in 0..1024 {
+            let mut case = strategy.new_value(&mut runner).unwrap();
+            assert!(pred(case.current()), "Bad char: {:?}", case.current());
+            while case.simplify() {
+                assert!(pred(case.current()),
+                        "Bad char: {:?}", case.current());
+            }
+        }
+    }
+
+    #[test]
+    fn char_of_stays_in_categories() {
+        assert_all(char_of(&[GeneralCategory::Letter]), char::is_alphabetic);
+        assert_all(char_of(&[GeneralCategory::UppercaseLetter]),
+                   char::is_uppercase);
+        assert_all(char_of(&[GeneralCategory::Whitespace]),
+                   char::is_whitespace);
+        assert_all(char_of(&[GeneralCategory::Control,
+                             GeneralCategory::AsciiPunctuation]),
+                   |ch| ch.is_control() || ch.is_ascii_punctuation());
+        assert_all(char_alphanumeric(), char::is_alphanumeric);
+        assert_all(char_ascii(), |ch| ch.is_ascii());
+        assert_all(char_ascii_printable(),
+                   |ch| ch.is_ascii() && !ch.is_control());
+    }
+
+    #[test]
+    fn ranges_are_exact() {
+        let ranges = GeneralCategory::Number.ranges();
+        assert!(ranges.iter().any(|&(lo, hi)| lo == '0' && hi == '9'));
+        for w in ranges.windows(2) {
+            assert!((w[0].1 as u32) + 1 < w[1].0 as u32);
+        }
+    }
+
+    #[test]
+    fn merge_ranges_joins_adjacent_and_overlapping() {
+        assert_eq!(vec![('a', 'f'), ('x', 'z')],
+                   merge_ranges(&[&[('a', 'c'), ('x', 'z')],
+                                  &[('b', 'f'), ('y', 'y')]]));
+        assert_eq!(vec![('a', 'd')],
+                   merge_ranges(&[&[('a', 'b')], &[('c', 'd')]]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn char_of_requires_categories() {
+        char_of(&[]);
+    }
+}
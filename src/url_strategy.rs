@@ -0,0 +1,230 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `url::Url`s.
+//!
+//! This module is only available with the `url` feature.
+//!
+//! Generated URLs have a scheme, a domain name host, and optionally a port,
+//! path segments, query parameters and a fragment. Every value is built with
+//! `Url::parse()`, so it is always a valid `Url`.
+//!
+//! Shrinking first removes query parameters, then path segments, then reduces
+//! the host towards `a.example.com`, and only then simplifies the port,
+//! fragment and scheme.
+
+use std::fmt::Write;
+
+use url::Url;
+
+use collection;
+use num;
+use option;
+use sample;
+use strategy::*;
+use test_runner::*;
+
+/// Strategy to create `Url`s.
+///
+/// Created by the `url()` and `http_url()` functions in the same module.
+#[derive(Debug)]
+pub struct UrlStrategy {
+    scheme: SBoxedStrategy<&'static str>,
+    host: SBoxedStrategy<String>,
+    port: SBoxedStrategy<Option<u16>>,
+    path: SBoxedStrategy<Vec<String>>,
+    query: SBoxedStrategy<Vec<(String, String)>>,
+    fragment: SBoxedStrategy<Option<String>>,
+}
+
+/// `ValueTree` corresponding to `UrlStrategy`.
+pub struct UrlValueTree {
+    scheme: Box<ValueTree<Value = &'static str>>,
+    host: Box<ValueTree<Value = String>>,
+    port: Box<ValueTree<Value = Option<u16>>>,
+    path: Box<ValueTree<Value = Vec<String>>>,
+    query: Box<ValueTree<Value = Vec<(String, String)>>>,
+    fragment: Box<ValueTree<Value = Option<String>>>,
+    /// Index, in shrinking order, of the component currently being
+    /// simplified.
+    shrinker: usize,
+    /// The component which was last simplified, if it may be complicated.
+    prev_shrinker: Option<usize>,
+}
+
+/// Number of components of a `UrlValueTree`.
+const COMPONENTS: usize = 6;
+
+impl UrlValueTree {
+    /// Simplify (or, if `simplify` is false, complicate) the component at
+    /// index `ix` in shrinking order.
+    fn shrink_component(&mut self, ix: usize, simplify: bool) -> bool {
+        fn shrink<T : ValueTree + ?Sized>(t: &mut T, simplify: bool) -> bool {
+            if simplify { t.simplify() } else { t.complicate() }
+        }
+
+        match ix {
+            0 => shrink(&mut *self.query, simplify),
+            1 => shrink(&mut *self.path, simplify),
+            2 => shrink(&mut *self.host, simplify),
+            3 => shrink(&mut *self.port, simplify),
+            4 => shrink(&mut *self.fragment, simplify),
+            5 => shrink(&mut *self.scheme, simplify),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Strategy for UrlStrategy {
+    type Value = UrlValueTree;
+
+    fn new_value(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(UrlValueTree {
+            scheme: self.scheme.new_value(runner)?,
+            host: self.host.new_value(runner)?,
+            port: self.port.new_value(runner)?,
+            path: self.path.new_value(runner)?,
+            query: self.query.new_value(runner)?,
+            fragment: self.fragment.new_value(runner)?,
+            shrinker: 0,
+            prev_shrinker: None,
+        })
+    }
+}
+
+impl ValueTree for UrlValueTree {
+    type Value = Url;
+
+    fn current(&self) -> Url {
+        let mut url = format!("{}://{}", self.scheme.current(),
+                              self.host.current());
+        if let Some(port) = self.port.current() {
+            write!(url, ":{}", port).unwrap();
+        }
+        url.push('/');
+        url.push_str(&self.path.current().join("/"));
+
+        let query = self.query.current();
+        for (ix, &(ref key, ref value)) in query.iter().enumerate() {
+            url.push(if 0 == ix { '?' } else { '&' });
+            write!(url, "{}={}", key, value).unwrap();
+        }
+
+        if let Some(fragment) = self.fragment.current() {
+            url.push('#');
+            url.push_str(&fragment);
+        }
+
+        Url::parse(&url).expect("generated invalid URL")
+    }
+
+    fn simplify(&mut self) -> bool {
+        while self.shrinker < COMPONENTS {
+            let shrinker = self.shrinker;
+            if self.shrink_component(shrinker, true) {
+                self.prev_shrinker = Some(shrinker);
+                return true;
+            }
+            self.shrinker += 1;
+        }
+
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        if let Some(shrinker) = self.prev_shrinker {
+            if self.shrink_component(shrinker, false) {
+                // Move back to this component so `simplify()` resumes with
+                // it, even if it was previously exhausted.
+                self.shrinker = shrinker;
+                return true;
+            }
+            self.prev_shrinker = None;
+        }
+
+        false
+    }
+
+    fn current_complexity(&self) -> u64 {
+        self.scheme.current_complexity()
+            .saturating_add(self.host.current_complexity())
+            .saturating_add(self.port.current_complexity())
+            .saturating_add(self.path.current_complexity())
+            .saturating_add(self.query.current_complexity())
+            .saturating_add(self.fragment.current_complexity())
+    }
+}
+
+fn url_with_schemes(schemes: &'static [&'static str]) -> UrlStrategy {
+    UrlStrategy {
+        scheme: sample::select(schemes).sboxed(),
+        host: (collection::vec("[a-z][a-z0-9]{0,9}", 1..4),
+               sample::select(&["example.com", "example.org",
+                                "example.net", "test"][..]))
+            .prop_map(|(labels, domain)| {
+                format!("{}.{}", labels.join("."), domain)
+            }).sboxed(),
+        port: option::of(num::u16::ANY).sboxed(),
+        path: collection::vec("[a-zA-Z0-9_~-]{1,10}", 0..5).sboxed(),
+        query: collection::vec(("[a-z]{1,8}", "[a-zA-Z0-9]{0,8}"), 0..5)
+            .sboxed(),
+        fragment: option::of("[a-zA-Z0-9]{0,8}").sboxed(),
+    }
+}
+
+/// Create a strategy to generate `Url`s with any of the schemes `http`,
+/// `https`, `ftp`, `ws`, `wss`, `git` and `ssh`.
+///
+/// Schemes shrink towards `http`.
+pub fn url() -> UrlStrategy {
+    url_with_schemes(&["http", "https", "ftp", "ws", "wss", "git", "ssh"])
+}
+
+/// Create a strategy to generate `Url`s with the scheme `http` or `https`.
+///
+/// Schemes shrink towards `http`.
+pub fn http_url() -> UrlStrategy {
+    url_with_schemes(&["http", "https"])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn http_urls_have_http_scheme() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let url = http_url().new_value(&mut runner).unwrap().current();
+            assert!("http" == url.scheme() || "https" == url.scheme(),
+                    "Bad scheme: {}", url);
+            assert!(url.host_str().is_some());
+        }
+    }
+
+    #[test]
+    fn urls_shrink_query_then_path_then_host() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let mut case = url().new_value(&mut runner).unwrap();
+            let initial = case.current();
+            while case.simplify() {
+                let url = case.current();
+                if url.path() != initial.path() {
+                    assert_eq!(None, url.query(), "{} from {}", url, initial);
+                }
+                if url.host_str() != initial.host_str() {
+                    assert_eq!("/", url.path(), "{} from {}", url, initial);
+                }
+            }
+
+            assert_eq!("http://a.example.com/", case.current().as_str());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//-
+// Copyright 2018 The proptest developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strategies for generating `uuid::Uuid`s.
+//!
+//! This module is only available with the `uuid` feature.
+//!
+//! The bits of a generated UUID which are not fixed by its version and variant
+//! shrink towards zero, so UUIDs shrink as close to `Uuid::nil()` as their
+//! version allows.
+
+use uuid::{Builder, Uuid, Variant, Version};
+
+use array::{ArrayValueTree, UniformArrayStrategy};
+use strategy::*;
+use test_runner::*;
+
+/// Builds a UUID of the given version from random bytes.
+#[derive(Clone, Copy, Debug)]
+struct BytesToUuid(Version);
+
+impl statics::MapFn<[u8; 16]> for BytesToUuid {
+    type Output = Uuid;
+
+    fn apply(&self, bytes: [u8; 16]) -> Uuid {
+        Builder::from_bytes(bytes)
+            .set_variant(Variant::RFC4122)
+            .set_version(self.0)
+            .build()
+    }
+}
+
+opaque_strategy_wrapper! {
+    /// Strategy to create `Uuid`s of a particular version.
+    ///
+    /// Created by the `uuid()` and `uuid_v1()` functions in the same module.
+    #[derive(Clone, Debug)]
+    pub struct UuidStrategy[][](
+        statics::Map<UniformArrayStrategy<::num::u8::Any, [u8; 16]>,
+                     BytesToUuid>)
+        -> UuidValueTree;
+    /// `ValueTree` corresponding to `UuidStrategy`.
+    #[derive(Clone, Debug)]
+    pub struct UuidValueTree[][](
+        statics::Map<ArrayValueTree<[::num::u8::BinarySearch; 16]>,
+                     BytesToUuid>)
+        -> Uuid;
+}
+
+fn uuid_with_version(version: Version) -> UuidStrategy {
+    UuidStrategy(statics::Map::new(
+        ::array::uniform16(::num::u8::ANY), BytesToUuid(version)))
+}
+
+/// Create a strategy to generate random (version 4) `Uuid`s.
+///
+/// Values shrink towards `00000000-0000-4000-8000-000000000000`.
+pub fn uuid() -> UuidStrategy {
+    uuid_with_version(Version::Random)
+}
+
+/// Create a strategy to generate time-based (version 1) `Uuid`s.
+///
+/// The timestamp, clock sequence and node ID are all random. Values shrink
+/// towards `00000000-0000-1000-8000-000000000000`.
+pub fn uuid_v1() -> UuidStrategy {
+    uuid_with_version(Version::Mac)
+}
+
+/// Create a strategy which always generates `Uuid::nil()`.
+pub fn uuid_nil() -> Const<Uuid> {
+    Const(Uuid::nil())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uuids_have_requested_version() {
+        let mut runner = TestRunner::default();
+        for _ in 0..256 {
+            let v4 = uuid().new_value(&mut runner).unwrap().current();
+            assert_eq!(Some(Version::Random), v4.get_version());
+            assert_eq!(Some(Variant::RFC4122), v4.get_variant());
+            let v1 = uuid_v1().new_value(&mut runner).unwrap().current();
+            assert_eq!(Some(Version::Mac), v1.get_version());
+            assert_eq!(Some(Variant::RFC4122), v1.get_variant());
+        }
+    }
+
+    #[test]
+    fn uuids_shrink_towards_nil() {
+        let mut runner = TestRunner::default();
+        let mut case = uuid().new_value(&mut runner).unwrap();
+        while case.simplify() { }
+        assert_eq!("00000000-0000-4000-8000-000000000000",
+                   case.current().to_string());
+
+        assert!(uuid_nil().new_value(&mut runner).unwrap().current().is_nil());
+    }
+}